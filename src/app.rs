@@ -1,18 +1,38 @@
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
 
+use crate::config::AppConfig;
+use crate::diagnostics::{self, Diagnostic, Diagnostics, Severity};
 use crate::error::{Result, TuicrError};
 use crate::model::{
-    Comment, CommentType, DiffFile, DiffLine, LineOrigin, LineRange, LineSide, ReviewSession,
-    SessionDiffSource,
+    Comment, CommentType, CommentTypes, DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin,
+    LineRange, LineSide, MergeOptions, PathFilter, PathPatterns, ReviewSession, ReviewedPolicy,
+    SessionDiffSource, full_file_hunk,
+    path_filter::IGNORE_FILE_NAME,
+    snippet::{Snippet, SnippetContext},
+};
+use crate::output::annotate::{
+    AnnotatedFile, CommentSyntax, file_annotations, generate_patch, strip_markers,
+};
+use crate::output::forge::{DiffRefs, generate_github_review, generate_gitlab_discussions};
+use crate::output::sarif::generate_sarif;
+use crate::output::{ExportFormat, generate_export_content};
+use crate::persistence::{
+    REPO_REVIEWS_DIR, SessionLock, StoredSession, autosave_session, delete_session,
+    discard_autosave, find_autosave, find_newer_session, is_same_repo, list_sessions,
+    load_latest_session_for_context, load_session, lock_session, save_session, update_session,
+    write_atomic,
 };
-use crate::persistence::load_latest_session_for_context;
 use crate::theme::Theme;
+use crate::ui::comment_text::{self, CodeHighlights};
 use crate::update::UpdateInfo;
 use crate::vcs::git::calculate_gap;
-use crate::vcs::{CommitInfo, VcsBackend, VcsInfo, detect_vcs};
+use crate::vcs::{
+    BlameLine, CommitInfo, DiffOptions, FileVersion, VcsBackend, VcsInfo, VcsType, detect_vcs,
+};
 
 const VISIBLE_COMMIT_COUNT: usize = 10;
 const COMMIT_PAGE_SIZE: usize = 10;
@@ -39,6 +59,29 @@ pub struct GapId {
     pub hunk_idx: usize,
 }
 
+/// A file shown in full: one hunk covering a whole side is shown in place of
+/// its diff hunks, which stay in `diff_files` for everything but rendering
+#[derive(Debug, Clone)]
+pub struct FullFileView {
+    pub side: LineSide,
+    hunk: DiffHunk,
+}
+
+impl FullFileView {
+    /// Hunks `file` is shown with: the whole side when it has a view in
+    /// `views`, its diff hunks otherwise
+    fn shown_hunks<'a>(
+        views: &'a HashMap<usize, FullFileView>,
+        file_idx: usize,
+        file: &'a DiffFile,
+    ) -> &'a [DiffHunk] {
+        match views.get(&file_idx) {
+            Some(view) => std::slice::from_ref(&view.hunk),
+            None => &file.hunks,
+        }
+    }
+}
+
 /// Describes what a rendered line represents - built once and used for O(1) cursor queries
 #[derive(Debug, Clone)]
 pub enum AnnotatedLine {
@@ -76,8 +119,16 @@ pub enum AnnotatedLine {
         side: LineSide,
         comment_idx: usize,
     },
+    /// A lint or compiler diagnostic on a new-side line
+    Diagnostic {
+        file_idx: usize,
+        line: u32,
+        diagnostic_idx: usize,
+    },
     /// Binary or empty file indicator
     BinaryOrEmpty { file_idx: usize },
+    /// Placeholder for a collapsed generated or vendored file
+    CollapsedFile { file_idx: usize },
     /// Spacing between files
     Spacing,
 }
@@ -89,6 +140,13 @@ pub enum InputMode {
     Command,
     Search,
     Help,
+    Stats,
+    /// Snippet picker opened from Comment mode
+    SnippetPicker,
+    /// Browser of stored review sessions
+    Sessions,
+    /// Editing a session name in the session browser
+    SessionRename,
     Confirm,
     CommitSelect,
     VisualSelect,
@@ -111,6 +169,7 @@ pub enum FocusedPanel {
     FileList,
     Diff,
     CommitSelector,
+    Comments,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -152,6 +211,16 @@ pub struct App {
     pub last_search_pattern: Option<String>,
     pub comment_buffer: String,
     pub comment_cursor: usize,
+    pub comment_types: CommentTypes,
+    pub snippets: Vec<Snippet>,
+    pub snippet_filter: String,
+    pub snippet_cursor: usize,
+    /// Stored sessions listed in the session browser
+    pub session_list: Vec<StoredSession>,
+    pub sessions_cursor: usize,
+    pub session_rename_buffer: String,
+    /// `d` was pressed once in the session browser; a second press deletes
+    session_delete_armed: bool,
     pub comment_type: CommentType,
     pub comment_is_file_level: bool,
     pub comment_line: Option<(u32, LineSide)>,
@@ -179,10 +248,21 @@ pub struct App {
     pub should_quit: bool,
     pub dirty: bool,
     pub quit_warned: bool,
+    /// Lock on the session under review; `None` while another instance
+    /// holds it
+    session_lock: Option<SessionLock>,
+    /// Session a lock was last taken for, so a failed attempt isn't
+    /// retried every frame
+    locked_session_id: Option<String>,
+    /// Time between autosaves of unsaved changes, `None` when disabled
+    autosave_interval: Option<Duration>,
+    last_autosave: Instant,
     pub message: Option<Message>,
     pub pending_confirm: Option<ConfirmAction>,
     pub supports_keyboard_enhancement: bool,
     pub show_file_list: bool,
+    pub show_comments_panel: bool,
+    pub comments_panel_cursor: usize,
     pub file_list_area: Option<ratatui::layout::Rect>,
     pub diff_area: Option<ratatui::layout::Rect>,
     pub expanded_dirs: HashSet<String>,
@@ -194,6 +274,8 @@ pub struct App {
     pub line_annotations: Vec<AnnotatedLine>,
     /// Output to stdout instead of clipboard when exporting
     pub output_to_stdout: bool,
+    /// Format `y`, `:clip` and `:wq` export the review in
+    pub export_format: ExportFormat,
     /// Pending output to print to stdout after TUI exits
     pub pending_stdout_output: Option<String>,
     /// Calculated screen position for comment input cursor (col, row) for IME positioning.
@@ -213,6 +295,46 @@ pub struct App {
     pub range_diff_files: Option<Vec<DiffFile>>,
     /// Saved inline selection range when entering full commit select mode via :commits
     pub saved_inline_selection: Option<(usize, usize)>,
+
+    /// Whether the blame gutter is shown in the diff view
+    pub show_blame: bool,
+    /// Lazily loaded old-side blame keyed by (base commit, old path).
+    /// `None` records a failed lookup so it isn't retried every frame.
+    pub blame_cache: HashMap<(Option<String>, PathBuf), Option<Vec<BlameLine>>>,
+    /// Files shown in full instead of as diff hunks, keyed by file index
+    pub full_file_views: HashMap<usize, FullFileView>,
+    /// Whitespace, context and rename options used to generate diffs
+    pub diff_options: DiffOptions,
+    /// `--path`/`--exclude` globs the review is scoped to
+    pub path_patterns: PathPatterns,
+    /// Combined config, `.tuicrignore` and CLI filter applied to every diff
+    pub path_filter: PathFilter,
+    /// Config and `.tuicrignore` excludes added to every review scope
+    default_excludes: Vec<String>,
+    /// Number of files dropped from the last loaded diff by `path_filter`
+    pub excluded_file_count: usize,
+    /// Configured globs for generated and vendored files
+    pub generated_filter: PathFilter,
+    /// Mark generated files as reviewed when they first appear in a session
+    pub mark_generated_reviewed: bool,
+    /// Files detected as generated or vendored; these render collapsed
+    pub generated_files: HashSet<PathBuf>,
+    /// Generated files the user expanded
+    pub expanded_generated: HashSet<PathBuf>,
+    /// Files folded down to their header
+    pub folded_files: HashSet<PathBuf>,
+    /// Reviewed files the user unfolded while `fold_reviewed` is on
+    pub unfolded_files: HashSet<PathBuf>,
+    /// Hunks folded down to their header, keyed by file path and hunk index
+    pub folded_hunks: HashSet<(PathBuf, usize)>,
+    /// Fold files once they are marked reviewed
+    pub fold_reviewed: bool,
+    /// Author recorded on new comments
+    pub reviewer: Option<String>,
+    /// Lint and compiler diagnostics shown on the diff
+    pub diagnostics: Diagnostics,
+    /// Syntax highlighting of the code blocks in comments
+    pub comment_highlights: CodeHighlights,
 }
 
 #[derive(Default)]
@@ -281,6 +403,30 @@ pub struct HelpState {
     pub total_lines: usize, // Set during render
 }
 
+/// Status note about `unplaced` comments an annotated export left out
+fn unplaced_warning(unplaced: usize) -> Option<String> {
+    let plural = if unplaced == 1 { "" } else { "s" };
+    (unplaced > 0)
+        .then(|| format!("{unplaced} comment{plural} on removed lines outside the diff left out"))
+}
+
+/// A review exported by [`App::export`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedReview {
+    pub content: String,
+    /// Comments the export had to leave out, for the status message
+    pub warning: Option<String>,
+}
+
+/// A comment as listed in the comments panel
+#[derive(Debug, Clone)]
+pub struct CommentEntry {
+    pub path: PathBuf,
+    /// `None` for file-level comments
+    pub line: Option<(u32, LineSide)>,
+    pub comment: Comment,
+}
+
 /// Represents a comment location for deletion
 enum CommentLocation {
     FileComment {
@@ -295,24 +441,57 @@ enum CommentLocation {
     },
 }
 
+impl CommentLocation {
+    fn path(&self) -> &PathBuf {
+        match self {
+            Self::FileComment { path, .. } | Self::LineComment { path, .. } => path,
+        }
+    }
+}
+
 impl App {
-    pub fn new(theme: Theme, output_to_stdout: bool, revisions: Option<&str>) -> Result<Self> {
-        let vcs = detect_vcs()?;
+    pub fn new(
+        theme: Theme,
+        output_to_stdout: bool,
+        revisions: Option<&str>,
+        path_patterns: Option<PathPatterns>,
+        config: &AppConfig,
+    ) -> Result<Self> {
+        let mut vcs = detect_vcs()?;
+        let unsupported_diff_options = vcs.set_diff_options(config.diff.to_diff_options()).err();
         let vcs_info = vcs.info().clone();
         let highlighter = theme.syntax_highlighter();
+        let default_excludes = Self::default_excludes(&vcs_info, &config.exclude);
+
+        // CLI globs replace the ones stored with the session; without any, a
+        // resumed session keeps the scope it was reviewed with.
+        let scope_session = |session: &mut ReviewSession| {
+            if let Some(patterns) = &path_patterns {
+                session.path_filter = patterns.clone();
+            }
+            let filter = PathFilter::new(&session.path_filter, &default_excludes);
+            (session.path_filter.clone(), filter)
+        };
 
         // Determine the diff source, files, and session based on input.
         // Three paths: CLI revisions, working tree changes, or commit selection fallback.
-        if let Some(revisions) = revisions {
+        let mut app = if let Some(revisions) = revisions {
             // Resolve the revisions to commits and diff as a commit range
             let commit_ids = vcs.resolve_revisions(revisions)?;
-            let diff_files = vcs.get_commit_range_diff(&commit_ids, highlighter)?;
+            let mut diff_files = vcs.get_commit_range_diff(&commit_ids, highlighter)?;
+            if diff_files.is_empty() {
+                return Err(TuicrError::NoChanges);
+            }
+            let mut session = Self::load_or_create_commit_range_session(&vcs_info, &commit_ids);
+            let (path_patterns, path_filter) = scope_session(&mut session);
+            let excluded_file_count = path_filter.apply(&mut diff_files);
+            // Commit messages are added below, so only real files count here
             if diff_files.is_empty() {
                 return Err(TuicrError::NoChanges);
             }
-            let session = Self::load_or_create_commit_range_session(&vcs_info, &commit_ids);
             // Get commit info for the inline commit selector
             let review_commits = vcs.get_commits_info(&commit_ids)?;
+            Self::prepend_commit_messages(&mut diff_files, &review_commits);
             // Reverse to newest-first display order
             let review_commits: Vec<CommitInfo> = review_commits.into_iter().rev().collect();
 
@@ -321,12 +500,16 @@ impl App {
                 vcs_info,
                 theme,
                 output_to_stdout,
+                config,
                 diff_files,
                 session,
                 DiffSource::CommitRange(commit_ids),
                 InputMode::Normal,
                 Vec::new(),
             )?;
+            app.path_patterns = path_patterns;
+            app.path_filter = path_filter;
+            app.excluded_file_count = excluded_file_count;
 
             // Set up inline commit selector for multi-commit reviews
             if review_commits.len() > 1 {
@@ -341,8 +524,7 @@ impl App {
                 app.commit_diff_cache.clear();
             }
             app.review_commits = review_commits;
-
-            Ok(app)
+            app
         } else {
             let working_tree_diff = match vcs.get_working_tree_diff(highlighter) {
                 Ok(diff_files) => Some(diff_files),
@@ -360,23 +542,35 @@ impl App {
                 commit_list.insert(0, Self::working_tree_commit_entry());
             }
 
-            let session = Self::load_or_create_session(&vcs_info);
+            let mut session = Self::load_or_create_session(&vcs_info);
+            let (path_patterns, path_filter) = scope_session(&mut session);
+            let mut diff_files = working_tree_diff.unwrap_or_default();
+            let excluded_file_count = path_filter.apply(&mut diff_files);
             let mut app = Self::build(
                 vcs,
                 vcs_info,
                 theme,
                 output_to_stdout,
-                working_tree_diff.unwrap_or_default(),
+                config,
+                diff_files,
                 session,
                 DiffSource::WorkingTree,
                 InputMode::CommitSelect,
                 commit_list,
             )?;
+            app.path_patterns = path_patterns;
+            app.path_filter = path_filter;
+            app.excluded_file_count = excluded_file_count;
 
             app.has_more_commit = commits.len() >= VISIBLE_COMMIT_COUNT;
             app.visible_commit_count = app.commit_list.len();
-            Ok(app)
+            app
+        };
+
+        if let Some(e) = unsupported_diff_options {
+            app.set_warning(e.to_string());
         }
+        Ok(app)
     }
 
     /// Shared constructor: all `App::new` paths converge here.
@@ -386,17 +580,13 @@ impl App {
         vcs_info: VcsInfo,
         theme: Theme,
         output_to_stdout: bool,
+        config: &AppConfig,
         diff_files: Vec<DiffFile>,
-        mut session: ReviewSession,
+        session: ReviewSession,
         diff_source: DiffSource,
         input_mode: InputMode,
         commit_list: Vec<CommitInfo>,
     ) -> Result<Self> {
-        // Ensure all diff files are registered in the session
-        for file in &diff_files {
-            session.add_file(file.display_path().clone(), file.status);
-        }
-
         let has_more_commit = commit_list.len() >= VISIBLE_COMMIT_COUNT;
         let visible_commit_count = if commit_list.is_empty() {
             VISIBLE_COMMIT_COUNT
@@ -404,6 +594,14 @@ impl App {
             commit_list.len()
         };
 
+        let comment_types = config.comment_types();
+        let default_excludes = Self::default_excludes(&vcs_info, &config.exclude);
+        let reviewer = config
+            .reviewer
+            .clone()
+            .or_else(|| vcs.user_name().ok())
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
         let mut app = Self {
             theme,
             vcs,
@@ -422,7 +620,15 @@ impl App {
             last_search_pattern: None,
             comment_buffer: String::new(),
             comment_cursor: 0,
-            comment_type: CommentType::Note,
+            comment_type: comment_types.default_type(),
+            comment_types,
+            snippets: config.snippets.clone(),
+            snippet_filter: String::new(),
+            snippet_cursor: 0,
+            session_list: Vec::new(),
+            sessions_cursor: 0,
+            session_rename_buffer: String::new(),
+            session_delete_armed: false,
             comment_is_file_level: true,
             comment_line: None,
             editing_comment_id: None,
@@ -439,10 +645,16 @@ impl App {
             should_quit: false,
             dirty: false,
             quit_warned: false,
+            session_lock: None,
+            locked_session_id: None,
+            autosave_interval: config.sessions.autosave_interval(),
+            last_autosave: Instant::now(),
             message: None,
             pending_confirm: None,
             supports_keyboard_enhancement: false,
             show_file_list: true,
+            show_comments_panel: false,
+            comments_panel_cursor: 0,
             file_list_area: None,
             diff_area: None,
             expanded_dirs: HashSet::new(),
@@ -450,6 +662,7 @@ impl App {
             expanded_content: HashMap::new(),
             line_annotations: Vec::new(),
             output_to_stdout,
+            export_format: ExportFormat::default(),
             pending_stdout_output: None,
             comment_cursor_screen_pos: None,
             update_info: None,
@@ -458,13 +671,82 @@ impl App {
             commit_diff_cache: HashMap::new(),
             range_diff_files: None,
             saved_inline_selection: None,
+            show_blame: false,
+            blame_cache: HashMap::new(),
+            full_file_views: HashMap::new(),
+            diff_options: config.diff.to_diff_options(),
+            path_patterns: PathPatterns::default(),
+            path_filter: PathFilter::default(),
+            default_excludes,
+            excluded_file_count: 0,
+            generated_filter: PathFilter::from_globs(&config.generated.patterns()),
+            mark_generated_reviewed: config.generated.mark_reviewed,
+            generated_files: HashSet::new(),
+            expanded_generated: HashSet::new(),
+            folded_files: HashSet::new(),
+            unfolded_files: HashSet::new(),
+            folded_hunks: HashSet::new(),
+            fold_reviewed: config.fold_reviewed.unwrap_or(true),
+            reviewer,
+            diagnostics: Diagnostics::default(),
+            comment_highlights: CodeHighlights::default(),
         };
-        app.sort_files_by_directory(true);
+        // Ensure all diff files are registered in the session
+        let diff_files = std::mem::take(&mut app.diff_files);
+        app.register_files(&diff_files);
+        app.diff_files = diff_files;
+        app.sort_files(true);
         app.expand_all_dirs();
         app.rebuild_annotations();
         Ok(app)
     }
 
+    /// Excludes that apply to every review of this repository: sessions
+    /// stored in the repository, the config's `exclude` list and the
+    /// repository's `.tuicrignore`.
+    fn default_excludes(vcs_info: &VcsInfo, config_excludes: &[String]) -> Vec<String> {
+        let mut excludes = vec![format!("{REPO_REVIEWS_DIR}/")];
+        excludes.extend_from_slice(config_excludes);
+        if let Ok(contents) = std::fs::read_to_string(vcs_info.root_path.join(IGNORE_FILE_NAME)) {
+            excludes.extend(PathFilter::parse_ignore_file(&contents));
+        }
+        excludes
+    }
+
+    /// Add files to the session and detect generated ones, marking newly
+    /// seen generated files as reviewed when configured.
+    fn register_files(&mut self, files: &[DiffFile]) {
+        for file in files {
+            let path = file.display_path();
+            let is_new = !self.session.files.contains_key(path);
+            self.session.add_file(path.clone(), file.status);
+
+            let is_generated = !file.is_commit_message()
+                && (self.generated_filter.is_excluded(path)
+                    || self.vcs.is_generated(path).unwrap_or(false));
+            if !is_generated {
+                continue;
+            }
+            self.generated_files.insert(path.clone());
+            if is_new
+                && self.mark_generated_reviewed
+                && let Some(review) = self.session.get_file_mut(path)
+            {
+                review.reviewed = true;
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Drop files outside the review scope and record the scope on the
+    /// current session so it is restored when the session is resumed.
+    fn filter_diff_files(&mut self, diff_files: &mut Vec<DiffFile>) {
+        self.excluded_file_count = self.path_filter.apply(diff_files);
+        if self.session.path_filter != self.path_patterns {
+            self.session.path_filter = self.path_patterns.clone();
+        }
+    }
+
     /// Load or create a session for a commit range (used by revisions and commit selection).
     fn load_or_create_commit_range_session(
         vcs_info: &VcsInfo,
@@ -547,11 +829,26 @@ impl App {
             short_id: "WORKTREE".to_string(),
             branch_name: None,
             summary: "Uncommitted changes".to_string(),
+            message: String::new(),
             author: String::new(),
             time: Utc::now(),
         }
     }
 
+    /// Insert a commit message pseudo-file per commit (oldest first) ahead of
+    /// the changed files, so messages are reviewed before the code.
+    fn prepend_commit_messages<'a>(
+        diff_files: &mut Vec<DiffFile>,
+        commits: impl IntoIterator<Item = &'a CommitInfo>,
+    ) {
+        let message_files: Vec<DiffFile> = commits
+            .into_iter()
+            .filter(|c| !Self::is_working_tree_commit(c))
+            .map(|c| DiffFile::commit_message(&c.short_id, &c.message_lines()))
+            .collect();
+        diff_files.splice(0..0, message_files);
+    }
+
     fn is_working_tree_commit(commit: &CommitInfo) -> bool {
         commit.id == WORKING_TREE_SELECTION_ID
     }
@@ -569,9 +866,11 @@ impl App {
             .saturating_sub(usize::from(self.has_working_tree_option()))
     }
 
-    fn load_working_tree_selection(&mut self) -> Result<()> {
+    /// Review uncommitted changes with `session`, or the latest stored
+    /// session for them
+    fn load_working_tree_selection(&mut self, session: Option<ReviewSession>) -> Result<()> {
         let highlighter = self.theme.syntax_highlighter();
-        let diff_files = match self.vcs.get_working_tree_diff(highlighter) {
+        let mut diff_files = match self.vcs.get_working_tree_diff(highlighter) {
             Ok(diff_files) => diff_files,
             Err(TuicrError::NoChanges) => {
                 self.set_message("No uncommitted changes");
@@ -580,11 +879,9 @@ impl App {
             Err(e) => return Err(e),
         };
 
-        self.session = Self::load_or_create_session(&self.vcs_info);
-        for file in &diff_files {
-            let path = file.display_path().clone();
-            self.session.add_file(path, file.status);
-        }
+        self.session = session.unwrap_or_else(|| Self::load_or_create_session(&self.vcs_info));
+        self.filter_diff_files(&mut diff_files);
+        self.register_files(&diff_files);
 
         self.diff_files = diff_files;
         self.diff_source = DiffSource::WorkingTree;
@@ -592,7 +889,7 @@ impl App {
         self.diff_state = DiffState::default();
         self.file_list_state = FileListState::default();
         self.clear_expanded_gaps();
-        self.sort_files_by_directory(true);
+        self.sort_files(true);
         self.expand_all_dirs();
         self.rebuild_annotations();
 
@@ -615,24 +912,47 @@ impl App {
         };
 
         let highlighter = self.theme.syntax_highlighter();
-        let diff_files = match &self.diff_source {
+        let mut diff_files = match &self.diff_source {
             DiffSource::WorkingTreeAndCommits(commit_ids) => {
                 let ids = commit_ids.clone();
                 self.vcs
                     .get_working_tree_with_commits_diff(&ids, highlighter)?
             }
-            _ => self.vcs.get_working_tree_diff(highlighter)?,
+            DiffSource::CommitRange(commit_ids) => {
+                // Regenerate only the commits selected in the inline selector
+                let ids: Vec<String> = match self.commit_selection_range {
+                    Some((start, end)) => (start..=end)
+                        .rev()
+                        .filter_map(|i| self.review_commits.get(i))
+                        .map(|c| c.id.clone())
+                        .collect(),
+                    None => commit_ids.clone(),
+                };
+                let mut diff_files = match self.vcs.get_commit_range_diff(&ids, highlighter) {
+                    Ok(files) => files,
+                    Err(TuicrError::NoChanges) => Vec::new(),
+                    Err(e) => return Err(e),
+                };
+                Self::prepend_commit_messages(
+                    &mut diff_files,
+                    self.review_commits
+                        .iter()
+                        .rev()
+                        .filter(|c| ids.contains(&c.id)),
+                );
+                diff_files
+            }
+            DiffSource::WorkingTree => self.vcs.get_working_tree_diff(highlighter)?,
         };
-
-        for file in &diff_files {
-            let path = file.display_path().clone();
-            self.session.add_file(path, file.status);
-        }
+        self.filter_diff_files(&mut diff_files);
+        self.register_files(&diff_files);
 
         self.diff_files = diff_files;
         self.clear_expanded_gaps();
+        // HEAD may have moved since the last load
+        self.blame_cache.clear();
 
-        self.sort_files_by_directory(false);
+        self.sort_files(false);
         self.expand_all_dirs();
 
         if self.diff_files.is_empty() {
@@ -702,9 +1022,19 @@ impl App {
             return;
         };
 
+        let before = self.session.snapshot([&path]);
         if let Some(review) = self.session.get_file_mut(&path) {
             review.reviewed = !review.reviewed;
+            let reviewed = review.reviewed;
+            let state = if reviewed { "reviewed" } else { "unreviewed" };
+            self.session
+                .record_change(format!("Mark {} {state}", path.display()), before);
             self.dirty = true;
+            // Marking reviewed re-applies auto-folding; unmarking shows the file again
+            self.unfolded_files.remove(&path);
+            if !reviewed {
+                self.folded_files.remove(&path);
+            }
             self.rebuild_annotations();
 
             if adjust_cursor {
@@ -815,6 +1145,132 @@ impl App {
         self.set_message(format!("Diff wrapping: {status}"));
     }
 
+    /// Apply a diff option from `:set` (e.g. `context=5`) and regenerate the diff.
+    pub fn set_diff_option(&mut self, setting: &str) {
+        let mut options = self.diff_options.clone();
+        if let Err(e) = options.apply_setting(setting) {
+            self.set_error(e);
+            return;
+        }
+
+        self.diff_options = options.clone();
+        let unsupported = self.vcs.set_diff_options(options).err();
+        // Cached commit diffs were generated with the old options
+        self.commit_diff_cache.clear();
+        self.range_diff_files = None;
+
+        match (self.reload_diff_files(), unsupported) {
+            (Err(e), _) => self.set_error(format!("Reload failed: {e}")),
+            (Ok(_), Some(e)) => self.set_warning(e.to_string()),
+            (Ok(_), None) => {
+                self.set_message(format!("Diff options: {}", self.diff_options.summary()))
+            }
+        }
+    }
+
+    pub fn toggle_blame(&mut self) {
+        let enabled = !self.show_blame;
+        self.set_blame(enabled);
+    }
+
+    pub fn set_blame(&mut self, enabled: bool) {
+        self.show_blame = enabled;
+        let status = if enabled { "on" } else { "off" };
+        self.set_message(format!("Blame: {status}"));
+    }
+
+    /// The commits GitLab positions of the current diff are relative to: the
+    /// parent of the oldest reviewed commit and the newest one. `None` when
+    /// the working tree is reviewed, which is not part of a merge request.
+    pub fn gitlab_diff_refs(&self) -> Result<Option<DiffRefs>> {
+        let (Some(oldest), Some(head_sha)) = (self.base_commit(), self.tip_commit()) else {
+            return Ok(None);
+        };
+        let parent = match self.vcs_info.vcs_type {
+            VcsType::Git => format!("{oldest}^"),
+            VcsType::Jujutsu => format!("{oldest}-"),
+            VcsType::Mercurial => format!("p1({oldest})"),
+        };
+        let Some(base_sha) = self.vcs.resolve_revisions(&parent)?.into_iter().next() else {
+            return Ok(None);
+        };
+        Ok(Some(DiffRefs {
+            // The merge request is assumed to target the commit the reviewed
+            // ones are based on
+            start_sha: base_sha.clone(),
+            base_sha,
+            head_sha,
+        }))
+    }
+
+    /// Commit whose parent is the old side of the current diff, if any.
+    /// `None` means the old side is the working copy's parent.
+    fn base_commit(&self) -> Option<String> {
+        match &self.diff_source {
+            DiffSource::WorkingTree => None,
+            DiffSource::WorkingTreeAndCommits(ids) => ids.first().cloned(),
+            DiffSource::CommitRange(ids) => {
+                // The inline selector may narrow the range; review_commits is newest-first
+                if let Some((_, end)) = self.commit_selection_range
+                    && let Some(commit) = self.review_commits.get(end)
+                {
+                    return Some(commit.id.clone());
+                }
+                ids.first().cloned()
+            }
+        }
+    }
+
+    fn blame_key(&self, file: &DiffFile) -> Option<(Option<String>, PathBuf)> {
+        // Added files and commit messages have no old side to blame
+        if file.status == FileStatus::Added || file.is_binary {
+            return None;
+        }
+        let path = file.old_path.as_ref().unwrap_or(file.display_path());
+        Some((self.base_commit(), path.clone()))
+    }
+
+    /// Old-side blame for a file, if it has been loaded.
+    pub fn blame_for_file(&self, file_idx: usize) -> Option<&[BlameLine]> {
+        let file = self.diff_files.get(file_idx)?;
+        let key = self.blame_key(file)?;
+        self.blame_cache.get(&key)?.as_deref()
+    }
+
+    /// Load blame for the files currently in the diff viewport.
+    /// Blame is fetched lazily, one file at a time, the first time a file is shown.
+    pub fn load_visible_blame(&mut self) {
+        if !self.show_blame {
+            return;
+        }
+
+        let start = self.diff_state.scroll_offset;
+        let end = start + self.diff_state.viewport_height.max(1);
+        let mut file_indices: Vec<usize> = self
+            .line_annotations
+            .iter()
+            .take(end)
+            .skip(start)
+            .filter_map(|annotation| match annotation {
+                AnnotatedLine::DiffLine { file_idx, .. }
+                | AnnotatedLine::SideBySideLine { file_idx, .. } => Some(*file_idx),
+                _ => None,
+            })
+            .collect();
+        file_indices.dedup();
+
+        for file_idx in file_indices {
+            let Some(key) = self.blame_key(&self.diff_files[file_idx]) else {
+                continue;
+            };
+            if self.blame_cache.contains_key(&key) {
+                continue;
+            }
+            let blame = self.vcs.blame(&key.1, key.0.as_deref()).ok();
+            self.blame_cache.insert(key, blame);
+        }
+    }
+
     fn ensure_cursor_visible(&mut self) {
         // Use visible_line_count which is computed during render based on actual line widths.
         // Fall back to viewport_height if not yet set (before first render).
@@ -882,10 +1338,7 @@ impl App {
                 if let Some(text) = self.line_text_for_search(line_idx)
                     && text.contains(pattern)
                 {
-                    self.diff_state.cursor_line = line_idx;
-                    self.ensure_cursor_visible();
-                    self.center_cursor();
-                    self.update_current_file_from_cursor();
+                    self.move_cursor_to(line_idx);
                     return true;
                 }
             }
@@ -899,10 +1352,7 @@ impl App {
                 if let Some(text) = self.line_text_for_search(line_idx)
                     && text.contains(pattern)
                 {
-                    self.diff_state.cursor_line = line_idx;
-                    self.ensure_cursor_visible();
-                    self.center_cursor();
-                    self.update_current_file_from_cursor();
+                    self.move_cursor_to(line_idx);
                     return true;
                 }
                 if line_idx == 0 {
@@ -916,6 +1366,14 @@ impl App {
         false
     }
 
+    /// Put the cursor on an annotated line and center it in the view
+    fn move_cursor_to(&mut self, line_idx: usize) {
+        self.diff_state.cursor_line = line_idx;
+        self.ensure_cursor_visible();
+        self.center_cursor();
+        self.update_current_file_from_cursor();
+    }
+
     fn line_text_for_search(&self, line_idx: usize) -> Option<String> {
         match self.line_annotations.get(line_idx)? {
             AnnotatedLine::FileHeader { file_idx } => {
@@ -947,6 +1405,15 @@ impl App {
                 let comment = comments.get(*comment_idx)?;
                 Some(comment.content.clone())
             }
+            AnnotatedLine::Diagnostic {
+                file_idx,
+                line,
+                diagnostic_idx,
+            } => {
+                let path = self.diff_files.get(*file_idx)?.display_path();
+                let diagnostic = self.diagnostics.on_line(path, *line).get(*diagnostic_idx)?;
+                Some(diagnostic.message.clone())
+            }
             AnnotatedLine::Expander { gap_id } => {
                 let gap = self.gap_size(gap_id)?;
                 Some(format!("... expand ({gap} lines) ..."))
@@ -959,8 +1426,7 @@ impl App {
                 Some(content.content.clone())
             }
             AnnotatedLine::HunkHeader { file_idx, hunk_idx } => {
-                let file = self.diff_files.get(*file_idx)?;
-                let hunk = file.hunks.get(*hunk_idx)?;
+                let hunk = self.shown_hunks(*file_idx).get(*hunk_idx)?;
                 Some(hunk.header.clone())
             }
             AnnotatedLine::DiffLine {
@@ -969,11 +1435,11 @@ impl App {
                 line_idx: diff_idx,
                 ..
             } => {
-                let file = self.diff_files.get(*file_idx)?;
-                let hunk = file.hunks.get(*hunk_idx)?;
+                let hunk = self.shown_hunks(*file_idx).get(*hunk_idx)?;
                 let line = hunk.lines.get(*diff_idx)?;
                 Some(line.content.clone())
             }
+            AnnotatedLine::CollapsedFile { .. } => Some("(generated file)".to_string()),
             AnnotatedLine::BinaryOrEmpty { file_idx } => {
                 let file = self.diff_files.get(*file_idx)?;
                 if file.is_binary {
@@ -989,8 +1455,7 @@ impl App {
                 add_line_idx,
                 ..
             } => {
-                let file = self.diff_files.get(*file_idx)?;
-                let hunk = file.hunks.get(*hunk_idx)?;
+                let hunk = self.shown_hunks(*file_idx).get(*hunk_idx)?;
 
                 let del_content = del_line_idx
                     .and_then(|idx| hunk.lines.get(idx))
@@ -1007,10 +1472,10 @@ impl App {
     }
 
     fn gap_size(&self, gap_id: &GapId) -> Option<u32> {
-        let file = self.diff_files.get(gap_id.file_idx)?;
-        let hunk = file.hunks.get(gap_id.hunk_idx)?;
+        let hunks = self.shown_hunks(gap_id.file_idx);
+        let hunk = hunks.get(gap_id.hunk_idx)?;
         let prev_hunk = if gap_id.hunk_idx > 0 {
-            file.hunks.get(gap_id.hunk_idx - 1)
+            hunks.get(gap_id.hunk_idx - 1)
         } else {
             None
         };
@@ -1108,87 +1573,30 @@ impl App {
         None
     }
 
-    pub fn next_hunk(&mut self) {
-        // Find the next hunk header position after current cursor
-        let mut cumulative = 0;
-        for file in &self.diff_files {
-            let path = file.display_path();
-
-            // File header
-            cumulative += 1;
-
-            // If file is reviewed, skip all content
-            if self.session.is_file_reviewed(path) {
-                continue;
-            }
-
-            // File comments
-            if let Some(review) = self.session.files.get(path) {
-                cumulative += review.file_comments.len();
-            }
+    /// Line positions of all visible hunk headers
+    fn hunk_header_positions(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.line_annotations
+            .iter()
+            .enumerate()
+            .filter(|(_, annotation)| matches!(annotation, AnnotatedLine::HunkHeader { .. }))
+            .map(|(pos, _)| pos)
+    }
 
-            if file.is_binary || file.hunks.is_empty() {
-                cumulative += 1; // "(binary file)" or "(no changes)"
-            } else {
-                for hunk in &file.hunks {
-                    // This is a hunk header position
-                    if cumulative > self.diff_state.cursor_line {
-                        self.diff_state.cursor_line = cumulative;
-                        self.ensure_cursor_visible();
-                        self.update_current_file_from_cursor();
-                        return;
-                    }
-                    cumulative += 1; // hunk header
-                    cumulative += hunk.lines.len(); // diff lines
-                }
-            }
-            cumulative += 1; // spacing
+    pub fn next_hunk(&mut self) {
+        let cursor = self.diff_state.cursor_line;
+        let next = self.hunk_header_positions().find(|&pos| pos > cursor);
+        if let Some(pos) = next {
+            self.diff_state.cursor_line = pos;
+            self.ensure_cursor_visible();
+            self.update_current_file_from_cursor();
         }
     }
 
     pub fn prev_hunk(&mut self) {
-        // Find the previous hunk header position before current cursor
-        let mut hunk_positions: Vec<usize> = Vec::new();
-        let mut cumulative = 0;
-
-        for file in &self.diff_files {
-            let path = file.display_path();
-
-            cumulative += 1; // File header
-
-            // If file is reviewed, skip all content
-            if self.session.is_file_reviewed(path) {
-                continue;
-            }
-
-            if let Some(review) = self.session.files.get(path) {
-                cumulative += review.file_comments.len();
-            }
-
-            if file.is_binary || file.hunks.is_empty() {
-                cumulative += 1;
-            } else {
-                for hunk in &file.hunks {
-                    hunk_positions.push(cumulative);
-                    cumulative += 1;
-                    cumulative += hunk.lines.len();
-                }
-            }
-            cumulative += 1;
-        }
-
-        // Find the last hunk position before current cursor
-        for &pos in hunk_positions.iter().rev() {
-            if pos < self.diff_state.cursor_line {
-                self.diff_state.cursor_line = pos;
-                self.ensure_cursor_visible();
-                self.update_current_file_from_cursor();
-                return;
-            }
-        }
-
+        let cursor = self.diff_state.cursor_line;
+        let prev = self.hunk_header_positions().rev().find(|&pos| pos < cursor);
         // If no previous hunk, go to start
-        self.diff_state.cursor_line = 0;
+        self.diff_state.cursor_line = prev.unwrap_or(0);
         self.ensure_cursor_visible();
         self.update_current_file_from_cursor();
     }
@@ -1206,9 +1614,11 @@ impl App {
 
     fn file_render_height(&self, file_idx: usize, file: &DiffFile) -> usize {
         let path = file.display_path();
+        let hunks = FullFileView::shown_hunks(&self.full_file_views, file_idx, file);
+        let comment_width = self.comment_text_width();
 
-        // If reviewed, only show header (1 line total)
-        if self.session.is_file_reviewed(path) {
+        // If folded, only show header (1 line total)
+        if self.is_file_folded(path) {
             return 1;
         }
 
@@ -1219,19 +1629,19 @@ impl App {
 
         if let Some(review) = self.session.files.get(path) {
             for comment in &review.file_comments {
-                comment_lines += Self::comment_display_lines(comment);
+                comment_lines += Self::comment_display_lines(comment, comment_width);
             }
         }
 
-        if file.is_binary || file.hunks.is_empty() {
+        if self.is_file_collapsed(file) || file.is_binary || hunks.is_empty() {
             content_lines = 1;
         } else {
             let line_comments = self.session.files.get(path).map(|r| &r.line_comments);
 
-            for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+            for (hunk_idx, hunk) in hunks.iter().enumerate() {
                 // Calculate gap before this hunk
                 let prev_hunk = if hunk_idx > 0 {
-                    file.hunks.get(hunk_idx - 1)
+                    hunks.get(hunk_idx - 1)
                 } else {
                     None
                 };
@@ -1256,6 +1666,9 @@ impl App {
 
                 // Hunk header + diff lines
                 content_lines += 1; // Hunk header
+                if self.is_hunk_folded(path, hunk_idx) {
+                    continue;
+                }
 
                 // Count diff lines based on view mode
                 match self.diff_view_mode {
@@ -1269,7 +1682,8 @@ impl App {
                                 {
                                     for comment in comments {
                                         if comment.side == Some(LineSide::Old) {
-                                            comment_lines += Self::comment_display_lines(comment);
+                                            comment_lines +=
+                                                Self::comment_display_lines(comment, comment_width);
                                         }
                                     }
                                 }
@@ -1279,7 +1693,8 @@ impl App {
                                 {
                                     for comment in comments {
                                         if comment.side != Some(LineSide::Old) {
-                                            comment_lines += Self::comment_display_lines(comment);
+                                            comment_lines +=
+                                                Self::comment_display_lines(comment, comment_width);
                                         }
                                     }
                                 }
@@ -1305,8 +1720,10 @@ impl App {
                                     {
                                         for comment in comments {
                                             if comment.side != Some(LineSide::Old) {
-                                                comment_lines +=
-                                                    Self::comment_display_lines(comment);
+                                                comment_lines += Self::comment_display_lines(
+                                                    comment,
+                                                    comment_width,
+                                                );
                                             }
                                         }
                                     }
@@ -1345,7 +1762,10 @@ impl App {
                                                 for comment in comments {
                                                     if comment.side == Some(LineSide::Old) {
                                                         comment_lines +=
-                                                            Self::comment_display_lines(comment);
+                                                            Self::comment_display_lines(
+                                                                comment,
+                                                                comment_width,
+                                                            );
                                                     }
                                                 }
                                             }
@@ -1358,7 +1778,10 @@ impl App {
                                                 for comment in comments {
                                                     if comment.side != Some(LineSide::Old) {
                                                         comment_lines +=
-                                                            Self::comment_display_lines(comment);
+                                                            Self::comment_display_lines(
+                                                                comment,
+                                                                comment_width,
+                                                            );
                                                     }
                                                 }
                                             }
@@ -1377,8 +1800,10 @@ impl App {
                                     {
                                         for comment in comments {
                                             if comment.side != Some(LineSide::Old) {
-                                                comment_lines +=
-                                                    Self::comment_display_lines(comment);
+                                                comment_lines += Self::comment_display_lines(
+                                                    comment,
+                                                    comment_width,
+                                                );
                                             }
                                         }
                                     }
@@ -1390,6 +1815,14 @@ impl App {
                     }
                 }
             }
+
+            let tail_gap = GapId {
+                file_idx,
+                hunk_idx: hunks.len(),
+            };
+            if let Some(expanded) = self.expanded_content.get(&tail_gap) {
+                content_lines += expanded.len();
+            }
         }
 
         header_lines + comment_lines + content_lines + spacing_lines
@@ -1441,11 +1874,38 @@ impl App {
     }
 
     /// Calculate the number of display lines a comment takes (header + content + footer)
-    fn comment_display_lines(comment: &Comment) -> usize {
-        let content_lines = comment.content.split('\n').count();
+    fn comment_display_lines(comment: &Comment, width: usize) -> usize {
+        let content_lines = comment_text::line_count(&comment.content, width);
         2 + content_lines // header + content lines + footer
     }
 
+    /// Record the diff view's width. Comments wrap to it, so their rows are
+    /// recounted when it changes.
+    pub fn set_diff_viewport_width(&mut self, width: usize) {
+        if self.diff_state.viewport_width != width {
+            self.diff_state.viewport_width = width;
+            self.rebuild_annotations();
+        }
+    }
+
+    /// Columns comment text is wrapped to in the diff view
+    pub fn comment_text_width(&self) -> usize {
+        comment_text::text_width(self.diff_state.viewport_width)
+    }
+
+    /// Highlight the code blocks of comments added or edited since the last
+    /// call, for drawing them
+    pub fn highlight_comment_code(&mut self) {
+        let comments = self.session.files.values().flat_map(|review| {
+            review
+                .file_comments
+                .iter()
+                .chain(review.line_comments.values().flatten())
+        });
+        self.comment_highlights
+            .update(comments, self.theme.syntax_highlighter());
+    }
+
     /// Returns the source line number and side at the current cursor position, if on a diff line
     pub fn get_line_at_cursor(&self) -> Option<(u32, LineSide)> {
         let target = self.diff_state.cursor_line;
@@ -1465,6 +1925,7 @@ impl App {
                     .map(|ln| (ln, LineSide::New))
                     .or_else(|| old_lineno.map(|ln| (ln, LineSide::Old)))
             }
+            Some(AnnotatedLine::Diagnostic { line, .. }) => Some((*line, LineSide::New)),
             _ => None,
         }
     }
@@ -1505,11 +1966,15 @@ impl App {
     /// Returns true if a comment was deleted
     pub fn delete_comment_at_cursor(&mut self) -> bool {
         let location = self.find_comment_at_cursor();
+        let before = self
+            .session
+            .snapshot(location.as_ref().map(CommentLocation::path));
 
         match location {
             Some(CommentLocation::FileComment { path, index }) => {
                 if let Some(review) = self.session.get_file_mut(&path) {
                     review.file_comments.remove(index);
+                    self.session.record_change("Delete file comment", before);
                     self.dirty = true;
                     self.set_message("Comment deleted");
                     self.rebuild_annotations();
@@ -1543,6 +2008,8 @@ impl App {
                         if comments.is_empty() {
                             review.line_comments.remove(&line);
                         }
+                        self.session
+                            .record_change(format!("Delete comment on line {line}"), before);
                         self.dirty = true;
                         self.set_message(format!("Comment on line {line} deleted"));
                         self.rebuild_annotations();
@@ -1557,75 +2024,330 @@ impl App {
     }
 
     pub fn clear_all_comments(&mut self) {
+        let commented: Vec<PathBuf> = self
+            .session
+            .files
+            .values()
+            .filter(|review| review.comment_count() > 0)
+            .map(|review| review.path.clone())
+            .collect();
+        let before = self.session.snapshot(&commented);
         let cleared = self.session.clear_comments();
         if cleared == 0 {
             self.set_message("No comments to clear");
             return;
         }
+        self.session
+            .record_change(format!("Clear {cleared} comments"), before);
 
         self.dirty = true;
         self.rebuild_annotations();
         self.set_message(format!("Cleared {cleared} comments"));
     }
 
-    /// Enter edit mode for the comment at the current cursor position
-    /// Returns true if a comment was found and edit mode entered
-    pub fn enter_edit_mode(&mut self) -> bool {
-        let location = self.find_comment_at_cursor();
-
-        match location {
-            Some(CommentLocation::FileComment { path, index }) => {
-                if let Some(review) = self.session.files.get(&path)
-                    && let Some(comment) = review.file_comments.get(index)
-                {
-                    self.input_mode = InputMode::Comment;
-                    self.comment_buffer = comment.content.clone();
-                    self.comment_cursor = self.comment_buffer.len();
-                    self.comment_type = comment.comment_type;
-                    self.comment_is_file_level = true;
-                    self.comment_line = None;
-                    self.editing_comment_id = Some(comment.id.clone());
-                    return true;
-                }
+    /// The comment rendered on an annotated line, if any
+    fn comment_at_line(&self, line_idx: usize) -> Option<&Comment> {
+        match self.line_annotations.get(line_idx)? {
+            AnnotatedLine::FileComment {
+                file_idx,
+                comment_idx,
+            } => {
+                let path = self.diff_files.get(*file_idx)?.display_path();
+                self.session
+                    .files
+                    .get(path)?
+                    .file_comments
+                    .get(*comment_idx)
             }
-            Some(CommentLocation::LineComment {
-                path,
+            AnnotatedLine::LineComment {
+                file_idx,
                 line,
                 side,
-                index,
-            }) => {
-                if let Some(review) = self.session.files.get(&path)
-                    && let Some(comments) = review.line_comments.get(&line)
-                {
-                    // Find the actual comment by counting comments with matching side
-                    let mut side_idx = 0;
-                    for comment in comments.iter() {
-                        let comment_side = comment.side.unwrap_or(LineSide::New);
-                        if comment_side == side {
-                            if side_idx == index {
-                                self.input_mode = InputMode::Comment;
-                                self.comment_buffer = comment.content.clone();
-                                self.comment_cursor = self.comment_buffer.len();
-                                self.comment_type = comment.comment_type;
-                                self.comment_is_file_level = false;
-                                self.comment_line = Some((line, side));
-                                self.editing_comment_id = Some(comment.id.clone());
-                                return true;
-                            }
-                            side_idx += 1;
-                        }
-                    }
-                }
+                comment_idx,
+            } => {
+                let path = self.diff_files.get(*file_idx)?.display_path();
+                // Line comment indices count only comments on the same side
+                self.session
+                    .files
+                    .get(path)?
+                    .line_comments
+                    .get(line)?
+                    .iter()
+                    .filter(|c| c.side.unwrap_or(LineSide::New) == *side)
+                    .nth(*comment_idx)
             }
-            None => {}
+            _ => None,
         }
-
-        false
-    }
-
-    pub fn enter_command_mode(&mut self) {
-        self.input_mode = InputMode::Command;
-        self.command_buffer.clear();
+    }
+
+    /// First and last annotated line of every rendered comment box, in
+    /// display order
+    fn comment_boxes(&self) -> Vec<(usize, usize, &Comment)> {
+        let mut boxes: Vec<(usize, usize, &Comment)> = Vec::new();
+        for line_idx in 0..self.line_annotations.len() {
+            let Some(comment) = self.comment_at_line(line_idx) else {
+                continue;
+            };
+            match boxes.last_mut() {
+                Some((_, end, prev)) if *end + 1 == line_idx && prev.id == comment.id => {
+                    *end = line_idx;
+                }
+                _ => boxes.push((line_idx, line_idx, comment)),
+            }
+        }
+        boxes
+    }
+
+    /// Move to the next (or previous) comment, optionally only of one type,
+    /// wrapping around at the end (or start) of the diff.
+    pub fn jump_to_comment(&mut self, forward: bool, comment_type: Option<CommentType>) {
+        let cursor = self.diff_state.cursor_line;
+        let boxes: Vec<(usize, usize)> = self
+            .comment_boxes()
+            .into_iter()
+            .filter(|(_, _, comment)| {
+                comment_type
+                    .as_ref()
+                    .is_none_or(|wanted| comment.comment_type == *wanted)
+            })
+            .map(|(start, end, _)| (start, end))
+            .collect();
+        let target = if forward {
+            boxes.iter().find(|(start, _)| *start > cursor)
+        } else {
+            boxes.iter().rev().find(|(_, end)| *end < cursor)
+        };
+        let wrapped = if forward { boxes.first() } else { boxes.last() };
+
+        match (target, wrapped) {
+            (Some((start, _)), _) => self.move_cursor_to(*start),
+            (None, Some(&(start, end))) => {
+                if !(start..=end).contains(&cursor) {
+                    self.move_cursor_to(start);
+                }
+                let edge = if forward { "top" } else { "bottom" };
+                self.set_message(format!("Wrapped around to the {edge}"));
+            }
+            (None, None) => {
+                let kind = comment_type
+                    .map(|t| format!("{} comments", self.comment_types.resolve(&t).name))
+                    .unwrap_or_else(|| "comments".to_string());
+                self.set_message(format!("No {kind}"));
+            }
+        }
+    }
+
+    /// `:cnext [type]` / `:cprev [type]`
+    pub fn jump_to_comment_named(&mut self, forward: bool, type_name: &str) {
+        if type_name.trim().is_empty() {
+            self.jump_to_comment(forward, None);
+        } else if let Some(comment_type) = self.comment_types.find(type_name) {
+            self.jump_to_comment(forward, Some(comment_type));
+        } else {
+            self.set_warning(format!("Unknown comment type: {}", type_name.trim()));
+        }
+    }
+
+    /// Every comment in the session shown by the file filter: files in diff
+    /// order, then files that are no longer part of the diff.
+    pub fn comment_entries(&self) -> Vec<CommentEntry> {
+        let filter = &self.session.file_list.filter;
+        let mut paths: Vec<&PathBuf> = self
+            .diff_files
+            .iter()
+            .map(|file| file.display_path())
+            .collect();
+        let mut other_paths: Vec<&PathBuf> = self
+            .session
+            .files
+            .keys()
+            .filter(|path| !paths.contains(path))
+            .collect();
+        other_paths.sort();
+        paths.extend(other_paths);
+
+        let mut entries = Vec::new();
+        for path in paths {
+            let Some(review) = self.session.files.get(path) else {
+                continue;
+            };
+            entries.extend(
+                review
+                    .file_comments
+                    .iter()
+                    .filter(|comment| filter.shows_comment(comment))
+                    .map(|comment| CommentEntry {
+                        path: path.clone(),
+                        line: None,
+                        comment: comment.clone(),
+                    }),
+            );
+
+            let mut line_comments: Vec<(u32, LineSide, &Comment)> = review
+                .line_comments
+                .iter()
+                .flat_map(|(line, comments)| {
+                    comments
+                        .iter()
+                        .filter(|c| filter.shows_comment(c))
+                        .map(|c| (*line, c.side.unwrap_or(LineSide::New), c))
+                })
+                .collect();
+            line_comments
+                .sort_by_key(|(line, side, c)| (*line, *side == LineSide::New, c.created_at));
+            entries.extend(
+                line_comments
+                    .into_iter()
+                    .map(|(line, side, comment)| CommentEntry {
+                        path: path.clone(),
+                        line: Some((line, side)),
+                        comment: comment.clone(),
+                    }),
+            );
+        }
+        entries
+    }
+
+    pub fn toggle_comments_panel(&mut self) {
+        self.show_comments_panel = !self.show_comments_panel;
+        if self.show_comments_panel {
+            self.focused_panel = FocusedPanel::Comments;
+        } else if self.focused_panel == FocusedPanel::Comments {
+            self.focused_panel = FocusedPanel::Diff;
+        }
+    }
+
+    pub fn comments_panel_down(&mut self, n: usize) {
+        let max_idx = self.comment_entries().len().saturating_sub(1);
+        self.comments_panel_cursor = (self.comments_panel_cursor + n).min(max_idx);
+    }
+
+    pub fn comments_panel_up(&mut self, n: usize) {
+        self.comments_panel_cursor = self.comments_panel_cursor.saturating_sub(n);
+    }
+
+    /// Move the diff cursor to the comment selected in the comments panel,
+    /// unfolding its file if needed. Returns false if it can't be shown.
+    pub fn jump_to_selected_comment(&mut self) -> bool {
+        let Some(entry) = self
+            .comment_entries()
+            .into_iter()
+            .nth(self.comments_panel_cursor)
+        else {
+            return false;
+        };
+        let Some(file_idx) = self
+            .diff_files
+            .iter()
+            .position(|file| file.display_path() == &entry.path)
+        else {
+            self.set_warning(format!(
+                "{} is not part of the current diff",
+                entry.path.display()
+            ));
+            return false;
+        };
+
+        self.folded_files.remove(&entry.path);
+        if self.is_file_folded(&entry.path) {
+            self.unfolded_files.insert(entry.path.clone());
+        }
+        if self.generated_files.contains(&entry.path) {
+            self.expanded_generated.insert(entry.path.clone());
+        }
+        self.folded_hunks.retain(|(path, _)| *path != entry.path);
+        self.rebuild_annotations();
+
+        let position = (0..self.line_annotations.len()).find(|&idx| {
+            self.comment_at_line(idx)
+                .is_some_and(|comment| comment.id == entry.comment.id)
+        });
+        match position {
+            Some(line_idx) => {
+                self.move_cursor_to(line_idx);
+                true
+            }
+            None => {
+                // The commented line is outside the diff's hunks
+                self.jump_to_file(file_idx);
+                self.set_warning("Commented line is not shown in the current diff");
+                false
+            }
+        }
+    }
+
+    pub fn edit_selected_comment(&mut self) {
+        if self.jump_to_selected_comment() {
+            self.focused_panel = FocusedPanel::Diff;
+            self.enter_edit_mode();
+        }
+    }
+
+    pub fn delete_selected_comment(&mut self) {
+        if self.jump_to_selected_comment() && self.delete_comment_at_cursor() {
+            let max_idx = self.comment_entries().len().saturating_sub(1);
+            self.comments_panel_cursor = self.comments_panel_cursor.min(max_idx);
+        }
+    }
+
+    /// Enter edit mode for the comment at the current cursor position
+    /// Returns true if a comment was found and edit mode entered
+    pub fn enter_edit_mode(&mut self) -> bool {
+        let location = self.find_comment_at_cursor();
+
+        match location {
+            Some(CommentLocation::FileComment { path, index }) => {
+                if let Some(review) = self.session.files.get(&path)
+                    && let Some(comment) = review.file_comments.get(index)
+                {
+                    self.input_mode = InputMode::Comment;
+                    self.comment_buffer = comment.content.clone();
+                    self.comment_cursor = self.comment_buffer.len();
+                    self.comment_type = comment.comment_type.clone();
+                    self.comment_is_file_level = true;
+                    self.comment_line = None;
+                    self.editing_comment_id = Some(comment.id.clone());
+                    return true;
+                }
+            }
+            Some(CommentLocation::LineComment {
+                path,
+                line,
+                side,
+                index,
+            }) => {
+                if let Some(review) = self.session.files.get(&path)
+                    && let Some(comments) = review.line_comments.get(&line)
+                {
+                    // Find the actual comment by counting comments with matching side
+                    let mut side_idx = 0;
+                    for comment in comments.iter() {
+                        let comment_side = comment.side.unwrap_or(LineSide::New);
+                        if comment_side == side {
+                            if side_idx == index {
+                                self.input_mode = InputMode::Comment;
+                                self.comment_buffer = comment.content.clone();
+                                self.comment_cursor = self.comment_buffer.len();
+                                self.comment_type = comment.comment_type.clone();
+                                self.comment_is_file_level = false;
+                                self.comment_line = Some((line, side));
+                                self.editing_comment_id = Some(comment.id.clone());
+                                return true;
+                            }
+                            side_idx += 1;
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
+
+        false
+    }
+
+    pub fn enter_command_mode(&mut self) {
+        self.input_mode = InputMode::Command;
+        self.command_buffer.clear();
     }
 
     pub fn exit_command_mode(&mut self) {
@@ -1647,7 +2369,7 @@ impl App {
         self.input_mode = InputMode::Comment;
         self.comment_buffer.clear();
         self.comment_cursor = 0;
-        self.comment_type = CommentType::Note;
+        self.comment_type = self.comment_types.default_type();
         self.comment_is_file_level = file_level;
         self.comment_line = line;
     }
@@ -1660,6 +2382,727 @@ impl App {
         self.comment_line_range = None;
     }
 
+    /// Comment mode, or a popup on top of the comment being written
+    pub fn is_writing_comment(&self) -> bool {
+        matches!(
+            self.input_mode,
+            InputMode::Comment | InputMode::SnippetPicker
+        )
+    }
+
+    pub fn open_snippet_picker(&mut self) {
+        if self.snippets.is_empty() {
+            self.set_warning("No snippets configured (add [[snippets]] to config.toml)");
+            return;
+        }
+        self.input_mode = InputMode::SnippetPicker;
+        self.snippet_filter.clear();
+        self.snippet_cursor = 0;
+    }
+
+    pub fn close_snippet_picker(&mut self) {
+        self.input_mode = InputMode::Comment;
+    }
+
+    /// Snippets whose name contains the picker's filter text
+    pub fn filtered_snippets(&self) -> Vec<&Snippet> {
+        self.snippets
+            .iter()
+            .filter(|snippet| snippet.matches(&self.snippet_filter))
+            .collect()
+    }
+
+    pub fn set_snippet_filter(&mut self, filter: String) {
+        self.snippet_filter = filter;
+        self.snippet_cursor = 0;
+    }
+
+    pub fn snippet_picker_down(&mut self) {
+        let count = self.filtered_snippets().len();
+        if self.snippet_cursor + 1 < count {
+            self.snippet_cursor += 1;
+        }
+    }
+
+    pub fn snippet_picker_up(&mut self) {
+        self.snippet_cursor = self.snippet_cursor.saturating_sub(1);
+    }
+
+    /// Expand the selected snippet into the comment at the text cursor
+    pub fn insert_selected_snippet(&mut self) {
+        let Some(snippet) = self.filtered_snippets().get(self.snippet_cursor).copied() else {
+            return;
+        };
+        let text = snippet.expand(&self.snippet_context());
+        self.comment_buffer.insert_str(self.comment_cursor, &text);
+        self.comment_cursor += text.len();
+        self.close_snippet_picker();
+    }
+
+    /// File, lines and code the comment being written is attached to
+    fn snippet_context(&self) -> SnippetContext {
+        let file = self
+            .current_file_path()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        let (lines, side) = match (self.comment_line_range, self.comment_line) {
+            _ if self.comment_is_file_level => (None, LineSide::New),
+            (Some((range, side)), _) => (Some(range), side),
+            (None, Some((line, side))) => (Some(LineRange::single(line)), side),
+            (None, None) => (None, LineSide::New),
+        };
+        let Some(range) = lines else {
+            return SnippetContext {
+                file,
+                lines,
+                code: String::new(),
+            };
+        };
+        let code_in = |hunks: &[DiffHunk]| {
+            hunks
+                .iter()
+                .flat_map(|hunk| &hunk.lines)
+                .filter(|line| {
+                    let lineno = match side {
+                        LineSide::Old => line
+                            .old_lineno
+                            .filter(|_| line.origin != LineOrigin::Addition),
+                        LineSide::New => line
+                            .new_lineno
+                            .filter(|_| line.origin != LineOrigin::Deletion),
+                    };
+                    lineno.is_some_and(|n| range.contains(n))
+                })
+                .map(|line| line.content.trim_end_matches('\n'))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        // Lines outside the diff can be commented on in the full-file view
+        let code = match self.current_file() {
+            Some(file) => match code_in(&file.hunks) {
+                code if code.is_empty() => {
+                    code_in(self.shown_hunks(self.diff_state.current_file_idx))
+                }
+                code => code,
+            },
+            None => String::new(),
+        };
+        SnippetContext { file, lines, code }
+    }
+
+    /// Review the diff `session` was made against, continuing that session.
+    /// The session must belong to the open repository.
+    pub fn open_session(&mut self, session: ReviewSession) -> Result<()> {
+        let id = session.id.clone();
+        let diff_source = session.diff_source;
+        let commit_ids = session.commit_range.clone().unwrap_or_default();
+        if diff_source != SessionDiffSource::WorkingTree && commit_ids.is_empty() {
+            return Err(TuicrError::CorruptedSession(
+                "commit range is missing".to_string(),
+            ));
+        }
+        let commits = if diff_source == SessionDiffSource::CommitRange {
+            let commits = self.vcs.get_commits_info(&commit_ids)?;
+            if commits.len() != commit_ids.len() {
+                return Err(TuicrError::VcsCommand(
+                    "the session's commits no longer exist".to_string(),
+                ));
+            }
+            commits
+        } else {
+            Vec::new()
+        };
+
+        let previous = (
+            self.path_patterns.clone(),
+            self.path_filter.clone(),
+            self.dirty,
+        );
+        self.path_patterns = session.path_filter.clone();
+        self.path_filter = PathFilter::new(&self.path_patterns, &self.default_excludes);
+        self.dirty = false;
+        let result = match diff_source {
+            SessionDiffSource::WorkingTree => self.load_working_tree_selection(Some(session)),
+            SessionDiffSource::CommitRange => {
+                self.load_commit_range_selection(commits, Some(session))
+            }
+            SessionDiffSource::WorkingTreeAndCommits => {
+                self.load_working_tree_and_commits_selection(commit_ids, Some(session))
+            }
+        };
+        if result.is_err() || self.session.id != id {
+            (self.path_patterns, self.path_filter, self.dirty) = previous;
+            return result.and(Err(TuicrError::NoChanges));
+        }
+
+        if diff_source != SessionDiffSource::CommitRange {
+            self.review_commits.clear();
+            self.range_diff_files = None;
+            self.show_commit_selector = false;
+            self.commit_diff_cache.clear();
+        }
+        Ok(())
+    }
+
+    /// Save the session under review. Fails while another instance has it
+    /// open unless `force` is set.
+    pub fn save_session(&mut self, force: bool) -> Result<PathBuf> {
+        if !self.holds_session_lock() {
+            match lock_session(&self.session.id) {
+                Ok(lock) => self.session_lock = Some(lock),
+                Err(e) if !force => return Err(e),
+                Err(_) => {}
+            }
+        }
+        let path = save_session(&self.session)?;
+        self.dirty = false;
+        if self.holds_session_lock() {
+            discard_autosave(&self.session.id)?;
+        }
+        Ok(path)
+    }
+
+    /// Quit without saving, dropping the autosave of the unsaved changes
+    pub fn quit_discarding_changes(&mut self) {
+        if self.holds_session_lock() {
+            let _ = discard_autosave(&self.session.id);
+        }
+        self.dirty = false;
+        self.should_quit = true;
+    }
+
+    /// Periodic work of the main loop: lock a newly opened session and
+    /// autosave unsaved changes
+    pub fn tick(&mut self) {
+        if self.locked_session_id.as_deref() != Some(self.session.id.as_str()) {
+            self.lock_session_under_review();
+        }
+        if self.dirty
+            && self
+                .autosave_interval
+                .is_some_and(|interval| self.last_autosave.elapsed() >= interval)
+        {
+            self.autosave();
+        }
+    }
+
+    /// Autosave unsaved changes now, e.g. when exiting without saving
+    pub fn autosave(&mut self) {
+        self.last_autosave = Instant::now();
+        // The other instance's autosave of this session is the live one
+        if !self.dirty || self.autosave_interval.is_none() || !self.holds_session_lock() {
+            return;
+        }
+        if let Err(e) = autosave_session(&self.session) {
+            self.set_error(format!("Autosave failed: {e}"));
+        }
+    }
+
+    fn holds_session_lock(&self) -> bool {
+        self.session_lock
+            .as_ref()
+            .is_some_and(|lock| lock.session_id == self.session.id)
+    }
+
+    /// Lock the session under review and recover unsaved changes a previous
+    /// instance left behind, or warn when another instance has it open
+    fn lock_session_under_review(&mut self) {
+        self.session_lock = None;
+        match lock_session(&self.session.id) {
+            Ok(lock) => {
+                self.session_lock = Some(lock);
+                match find_autosave(&self.session) {
+                    Ok(Some(session)) => self.recover_session(session),
+                    Ok(None) => {}
+                    Err(e) => self.set_error(format!("Failed to check for autosaves: {e}")),
+                }
+                if let Ok(Some((path, e))) = find_newer_session(&self.session) {
+                    let filename = path.file_name().unwrap_or_default().to_string_lossy();
+                    self.set_warning(format!("Skipped {filename}: {e}"));
+                }
+            }
+            Err(e @ TuicrError::SessionLocked(_)) => {
+                self.set_warning(format!("{e}; :w here would overwrite its changes"));
+            }
+            Err(e) => self.set_error(format!("Failed to lock session: {e}")),
+        }
+        self.locked_session_id = Some(self.session.id.clone());
+    }
+
+    /// Continue from an autosave of the session under review, or of an
+    /// unsaved session of the same review, in the current scope
+    fn recover_session(&mut self, mut session: ReviewSession) {
+        if session.id != self.session.id {
+            self.session_lock = lock_session(&session.id).ok();
+        }
+        session.path_filter = self.path_patterns.clone();
+        self.session = session;
+        let diff_files = std::mem::take(&mut self.diff_files);
+        self.register_files(&diff_files);
+        self.diff_files = diff_files;
+        self.rebuild_annotations();
+        self.dirty = true;
+        self.set_warning("Recovered unsaved changes from an earlier run (:w to keep them)");
+    }
+
+    pub fn open_sessions_browser(&mut self) {
+        match list_sessions() {
+            Ok(sessions) => {
+                self.sessions_cursor = sessions
+                    .iter()
+                    .position(|stored| stored.session.id == self.session.id)
+                    .unwrap_or(0);
+                self.session_list = sessions;
+                self.session_delete_armed = false;
+                self.input_mode = InputMode::Sessions;
+            }
+            Err(e) => self.set_error(format!("Failed to list sessions: {e}")),
+        }
+    }
+
+    pub fn close_sessions_browser(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.session_list.clear();
+    }
+
+    pub fn sessions_down(&mut self) {
+        self.session_delete_armed = false;
+        if self.sessions_cursor + 1 < self.session_list.len() {
+            self.sessions_cursor += 1;
+        }
+    }
+
+    pub fn sessions_up(&mut self) {
+        self.session_delete_armed = false;
+        self.sessions_cursor = self.sessions_cursor.saturating_sub(1);
+    }
+
+    /// Switch the review to the session under the browser's cursor
+    pub fn open_selected_session(&mut self) {
+        let Some(stored) = self.session_list.get(self.sessions_cursor) else {
+            return;
+        };
+        if stored.session.id == self.session.id {
+            self.close_sessions_browser();
+            return;
+        }
+        if !is_same_repo(&stored.session.repo_path, &self.vcs_info.root_path) {
+            self.set_warning(format!(
+                "Session belongs to {}; open it with `tuicr sessions open` there",
+                stored.session.repo_path.display()
+            ));
+            return;
+        }
+        if self.dirty {
+            self.set_error("No write since last change (:w first)");
+            return;
+        }
+
+        let session = stored.session.clone();
+        let label = stored.label().to_string();
+        self.close_sessions_browser();
+        match self.open_session(session) {
+            Ok(()) => self.set_message(format!("Opened session {label}")),
+            Err(e) => self.set_error(format!("Failed to open session: {e}")),
+        }
+    }
+
+    pub fn start_session_rename(&mut self) {
+        self.session_delete_armed = false;
+        let Some(stored) = self.session_list.get(self.sessions_cursor) else {
+            return;
+        };
+        self.session_rename_buffer = stored.session.name.clone().unwrap_or_default();
+        self.input_mode = InputMode::SessionRename;
+    }
+
+    pub fn cancel_session_rename(&mut self) {
+        self.input_mode = InputMode::Sessions;
+    }
+
+    pub fn finish_session_rename(&mut self) {
+        let name = Self::session_name(&self.session_rename_buffer);
+        self.input_mode = InputMode::Sessions;
+        self.update_selected_session(|session| session.name = name.clone());
+    }
+
+    pub fn toggle_selected_session_pin(&mut self) {
+        self.session_delete_armed = false;
+        let Some(stored) = self.session_list.get(self.sessions_cursor) else {
+            return;
+        };
+        let pinned = !stored.session.pinned;
+        self.update_selected_session(|session| session.pinned = pinned);
+    }
+
+    /// Delete the selected session on the second consecutive press
+    pub fn delete_selected_session(&mut self) {
+        let Some(stored) = self.session_list.get(self.sessions_cursor) else {
+            return;
+        };
+        if stored.session.id == self.session.id {
+            self.set_warning("Can't delete the session under review");
+            return;
+        }
+        if !self.session_delete_armed {
+            self.session_delete_armed = true;
+            self.set_warning(format!("Press d again to delete {}", stored.label()));
+            return;
+        }
+
+        self.session_delete_armed = false;
+        let label = stored.label().to_string();
+        match delete_session(&stored.path) {
+            Ok(()) => {
+                self.session_list.remove(self.sessions_cursor);
+                self.sessions_cursor = self
+                    .sessions_cursor
+                    .min(self.session_list.len().saturating_sub(1));
+                self.set_message(format!("Deleted session {label}"));
+            }
+            Err(e) => self.set_error(format!("Failed to delete session: {e}")),
+        }
+    }
+
+    /// Show the diagnostics in the file at `path` on the diff, in addition to
+    /// those already loaded; no path clears them
+    pub fn load_diagnostics(&mut self, path: &str) {
+        let path = path.trim();
+        if path.is_empty() {
+            if self.diagnostics.is_empty() {
+                self.set_message("Usage: :diagnostics <file> (no file clears them)");
+                return;
+            }
+            let cleared = self.diagnostics.len();
+            self.diagnostics.clear();
+            self.rebuild_annotations();
+            self.set_message(format!("Cleared {cleared} diagnostics"));
+            return;
+        }
+        match diagnostics::load(Path::new(path), &self.vcs_info.root_path) {
+            Ok(loaded) => {
+                let added = self.diagnostics.extend(loaded);
+                self.rebuild_annotations();
+                let on_diff: usize = self
+                    .diff_files
+                    .iter()
+                    .flat_map(|file| {
+                        file.hunks
+                            .iter()
+                            .flat_map(|hunk| &hunk.lines)
+                            .filter_map(|line| line.new_lineno)
+                            .map(|line| self.diagnostics.on_line(file.display_path(), line).len())
+                    })
+                    .sum();
+                self.set_message(format!(
+                    "Loaded {added} diagnostics from {path}, {on_diff} on diff lines"
+                ));
+            }
+            Err(e) => self.set_error(format!("Can't load diagnostics from {path}: {e}")),
+        }
+    }
+
+    /// The diagnostic at the cursor, or the first one under the diff line at
+    /// the cursor
+    fn diagnostic_at_cursor(&self) -> Option<(PathBuf, &Diagnostic)> {
+        let (file_idx, line, diagnostic_idx) =
+            match self.line_annotations.get(self.diff_state.cursor_line)? {
+                AnnotatedLine::Diagnostic {
+                    file_idx,
+                    line,
+                    diagnostic_idx,
+                } => (*file_idx, *line, *diagnostic_idx),
+                AnnotatedLine::DiffLine {
+                    file_idx,
+                    new_lineno: Some(line),
+                    ..
+                }
+                | AnnotatedLine::SideBySideLine {
+                    file_idx,
+                    new_lineno: Some(line),
+                    ..
+                } => {
+                    let path = self.diff_files.get(*file_idx)?.display_path();
+                    let comments = self
+                        .session
+                        .files
+                        .get(path)
+                        .and_then(|review| review.line_comments.get(line))
+                        .map_or(&[][..], Vec::as_slice);
+                    let (idx, _) = self
+                        .diagnostics
+                        .shown_on_line(path, *line, comments)
+                        .next()?;
+                    (*file_idx, *line, idx)
+                }
+                _ => return None,
+            };
+        let path = self.diff_files.get(file_idx)?.display_path();
+        let diagnostic = self.diagnostics.on_line(path, line).get(diagnostic_idx)?;
+        Some((path.clone(), diagnostic))
+    }
+
+    /// Turn the diagnostic at the cursor into a line comment attributed to
+    /// the tool that reported it
+    pub fn promote_diagnostic(&mut self) {
+        let Some((path, diagnostic)) = self.diagnostic_at_cursor() else {
+            self.set_message("No diagnostic at cursor");
+            return;
+        };
+        let preferred = match diagnostic.severity {
+            Severity::Error | Severity::Warning => "issue",
+            Severity::Note => "note",
+        };
+        let comment_type = self
+            .comment_types
+            .find(preferred)
+            .unwrap_or_else(|| self.comment_types.default_type());
+        let line = diagnostic.line;
+        let severity = diagnostic.severity.label();
+        let comment = Comment::new(diagnostic.comment_text(), comment_type, Some(LineSide::New))
+            .with_author(diagnostic.tool.clone().or_else(|| self.reviewer.clone()));
+
+        let before = self.session.snapshot([&path]);
+        if let Some(review) = self.session.get_file_mut(&path) {
+            review.add_line_comment(line, comment);
+            self.session.record_change(
+                format!("Promote {severity} on {}:{line}", path.display()),
+                before,
+            );
+            self.dirty = true;
+            self.rebuild_annotations();
+            self.set_message(format!(
+                "Added {severity} on {}:{line} as a comment",
+                path.display()
+            ));
+        }
+    }
+
+    /// The reviewed files with comments, as reviewed (the new side of the
+    /// diff), with their comments inserted as code comments, and the number
+    /// of comments on removed lines that have no place in them. Files whose
+    /// language has no known comment syntax are left out.
+    pub fn annotated_files(&self) -> Result<(Vec<AnnotatedFile>, usize)> {
+        if !self.session.has_comments() {
+            return Err(TuicrError::NoComments);
+        }
+        let highlighter = self.theme.syntax_highlighter();
+        let tip_commit = self.tip_commit();
+        let version = match tip_commit.as_deref() {
+            Some(id) => FileVersion::Commit(id),
+            None => FileVersion::WorkingTree,
+        };
+
+        let mut files = Vec::new();
+        let mut unplaced = 0;
+        for file in &self.diff_files {
+            if file.is_binary || file.is_commit_message() || file.status == FileStatus::Deleted {
+                continue;
+            }
+            let path = file.display_path();
+            let Some(review) = self.session.files.get(path) else {
+                continue;
+            };
+            let Some(syntax) = highlighter
+                .get_syntax(path)
+                .and_then(|syntax| CommentSyntax::for_syntax(&syntax.name))
+            else {
+                continue;
+            };
+            let annotations = file_annotations(review, &file.hunks, &self.comment_types, syntax);
+            unplaced += annotations.unplaced;
+            if annotations.markers.is_empty() {
+                continue;
+            }
+            let content = self.vcs.file_content(path, version)?;
+            files.push(AnnotatedFile::new(path, &content, annotations.markers));
+        }
+        if files.is_empty() {
+            return Err(TuicrError::UnsupportedOperation(
+                "no comments on files with a known comment syntax".to_string(),
+            ));
+        }
+        Ok((files, unplaced))
+    }
+
+    /// The review exported in `format`. The patch and GitLab formats place
+    /// comments in the reviewed files, which are read from the VCS first.
+    pub fn export(&self, format: ExportFormat) -> Result<ExportedReview> {
+        let mut warning = None;
+        let content = match format {
+            ExportFormat::Markdown => {
+                generate_export_content(&self.session, &self.diff_source, &self.comment_types)?
+            }
+            ExportFormat::Sarif => generate_sarif(&self.session, &self.comment_types)?,
+            ExportFormat::GitHub => generate_github_review(
+                &self.session,
+                self.tip_commit().as_deref(),
+                &self.comment_types,
+            )?,
+            ExportFormat::GitLab => generate_gitlab_discussions(
+                &self.session,
+                &self.diff_files,
+                self.gitlab_diff_refs()?.as_ref(),
+                &self.comment_types,
+            )?,
+            ExportFormat::Patch => {
+                let (files, unplaced) = self.annotated_files()?;
+                warning = unplaced_warning(unplaced);
+                generate_patch(&files)
+            }
+        };
+        Ok(ExportedReview { content, warning })
+    }
+
+    /// Insert the comments into the working tree files as code comments
+    pub fn annotate_working_tree(&mut self) {
+        if self.tip_commit().is_some() {
+            self.set_warning(
+                "Only working tree reviews can be annotated in place; use :export patch",
+            );
+            return;
+        }
+        let (files, unplaced) = match self.annotated_files() {
+            Ok(annotated) => annotated,
+            Err(e) => {
+                self.set_warning(format!("Annotate failed: {e}"));
+                return;
+            }
+        };
+        let mut markers = 0;
+        for file in &files {
+            let path = self.vcs_info.root_path.join(&file.path);
+            if let Err(e) = write_atomic(&path, file.content().as_bytes()) {
+                self.set_error(format!("Failed to write {}: {e}", file.path.display()));
+                return;
+            }
+            markers += file.marker_count();
+        }
+        let summary = format!("Added {markers} review markers to {} files", files.len());
+        match unplaced_warning(unplaced) {
+            Some(warning) => self.set_warning(format!("{summary}; {warning}")),
+            None => self.set_message(summary),
+        }
+    }
+
+    /// Remove the markers `:annotate` inserted from the reviewed working
+    /// tree files
+    pub fn strip_review_markers(&mut self) {
+        let highlighter = self.theme.syntax_highlighter();
+        let mut stripped = 0;
+        for file in &self.diff_files {
+            if file.is_binary || file.is_commit_message() || file.status == FileStatus::Deleted {
+                continue;
+            }
+            let Some(syntax) = highlighter
+                .get_syntax(file.display_path())
+                .and_then(|syntax| CommentSyntax::for_syntax(&syntax.name))
+            else {
+                continue;
+            };
+            let path = self.vcs_info.root_path.join(file.display_path());
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Some(content) = strip_markers(&content, syntax) {
+                if let Err(e) = write_atomic(&path, content.as_bytes()) {
+                    let message = format!("Failed to write {}: {e}", file.display_path().display());
+                    self.set_error(message);
+                    return;
+                }
+                stripped += 1;
+            }
+        }
+        match stripped {
+            0 => self.set_message("No review markers found"),
+            n => self.set_message(format!("Removed review markers from {n} files")),
+        }
+    }
+
+    /// `:merge [either|both] <file>`: add the comments of the session saved
+    /// at `file`, as one change that can be undone. Files are reviewed if
+    /// either session reviewed them, or only if both did.
+    pub fn merge_session_file(&mut self, args: &str) {
+        let args = args.trim();
+        let (reviewed, path) = match args.split_once(char::is_whitespace) {
+            Some((policy, path)) => match policy.parse::<ReviewedPolicy>() {
+                Ok(policy) => (policy, path.trim()),
+                Err(_) => (ReviewedPolicy::default(), args),
+            },
+            None => (ReviewedPolicy::default(), args),
+        };
+        if path.is_empty() {
+            self.set_warning("Usage: :merge [either|both] <session file>");
+            return;
+        }
+        let other = match load_session(&PathBuf::from(path)) {
+            Ok(other) => other,
+            Err(e) => {
+                self.set_error(format!("Can't merge {path}: {e}"));
+                return;
+            }
+        };
+
+        // Files only the other session has get an empty review first, so
+        // undoing the merge empties them again
+        for (file, review) in &other.files {
+            self.session.add_file(file.clone(), review.status);
+        }
+        let before = self.session.snapshot(other.files.keys());
+        let notes_before = self.session.session_notes.clone();
+        let options = MergeOptions {
+            same_repo: is_same_repo(&self.session.repo_path, &other.repo_path),
+            reviewed,
+        };
+        let report = self.session.merge(&other, options);
+        self.session
+            .record_change_with_notes(format!("Merge {path}"), before, notes_before);
+        self.dirty = true;
+        self.rebuild_annotations();
+
+        let summary = format!("{} from {path}", report.summary());
+        match report.conflicts.as_slice() {
+            [] => self.set_message(summary),
+            [conflict] => self.set_warning(format!("{summary}; {conflict}")),
+            [first, rest @ ..] => self.set_warning(format!(
+                "{summary}; {first} (+{} more conflict{})",
+                rest.len(),
+                if rest.len() == 1 { "" } else { "s" }
+            )),
+        }
+    }
+
+    /// Name the session under review, saved with the next `:w`
+    pub fn set_session_name(&mut self, name: &str) {
+        self.session.name = Self::session_name(name);
+        self.dirty = true;
+        match &self.session.name {
+            Some(name) => self.set_message(format!("Session named {name}")),
+            None => self.set_message("Session name cleared"),
+        }
+    }
+
+    fn session_name(input: &str) -> Option<String> {
+        Some(input.trim().to_string()).filter(|name| !name.is_empty())
+    }
+
+    /// Write a change to the selected stored session, mirroring it into the
+    /// session under review when that is the one selected
+    fn update_selected_session(&mut self, update: impl Fn(&mut ReviewSession)) {
+        let Some(stored) = self.session_list.get_mut(self.sessions_cursor) else {
+            return;
+        };
+        match update_session(&stored.path, &update) {
+            Ok(session) => {
+                stored.session = session;
+                if stored.session.id == self.session.id {
+                    update(&mut self.session);
+                }
+            }
+            Err(e) => self.set_error(format!("Failed to update session: {e}")),
+        }
+    }
+
     /// Enter visual selection mode, anchoring at the current cursor position
     pub fn enter_visual_mode(&mut self, line: u32, side: LineSide) {
         self.input_mode = InputMode::VisualSelect;
@@ -1708,7 +3151,7 @@ impl App {
             self.input_mode = InputMode::Comment;
             self.comment_buffer.clear();
             self.comment_cursor = 0;
-            self.comment_type = CommentType::Note;
+            self.comment_type = self.comment_types.default_type();
             self.comment_is_file_level = false;
             self.visual_anchor = None;
         } else {
@@ -1724,6 +3167,11 @@ impl App {
         }
 
         let content = self.comment_buffer.trim().to_string();
+        let author = self.reviewer.clone();
+        let before = self
+            .current_file_path()
+            .map(|path| self.session.snapshot([path]))
+            .unwrap_or_default();
 
         if let Some(path) = self.current_file_path().cloned()
             && let Some(review) = self.session.get_file_mut(&path)
@@ -1740,7 +3188,7 @@ impl App {
                     .find(|c| &c.id == editing_id)
                 {
                     comment.content = content.clone();
-                    comment.comment_type = self.comment_type;
+                    comment.comment_type = self.comment_type.clone();
                     message = "Comment updated".to_string();
                 } else {
                     // If not found in file comments, search in line comments
@@ -1754,7 +3202,7 @@ impl App {
 
                     if let Some(comment) = found_comment {
                         comment.content = content.clone();
-                        comment.comment_type = self.comment_type;
+                        comment.comment_type = self.comment_type.clone();
                         message = if let Some((line, _)) = self.comment_line {
                             format!("Comment on line {line} updated")
                         } else {
@@ -1767,13 +3215,19 @@ impl App {
             } else {
                 // Create new comment
                 if self.comment_is_file_level {
-                    let comment = Comment::new(content, self.comment_type, None);
+                    let comment =
+                        Comment::new(content, self.comment_type.clone(), None).with_author(author);
                     review.add_file_comment(comment);
                     message = "File comment added".to_string();
                 } else if let Some((range, side)) = self.comment_line_range {
                     // Range comment from visual selection
-                    let comment =
-                        Comment::new_with_range(content, self.comment_type, Some(side), range);
+                    let comment = Comment::new_with_range(
+                        content,
+                        self.comment_type.clone(),
+                        Some(side),
+                        range,
+                    )
+                    .with_author(author);
                     // Store by end line of the range
                     review.add_line_comment(range.end, comment);
                     if range.is_single() {
@@ -1782,17 +3236,20 @@ impl App {
                         message = format!("Comment added to lines {}-{}", range.start, range.end);
                     }
                 } else if let Some((line, side)) = self.comment_line {
-                    let comment = Comment::new(content, self.comment_type, Some(side));
+                    let comment = Comment::new(content, self.comment_type.clone(), Some(side))
+                        .with_author(author);
                     review.add_line_comment(line, comment);
                     message = format!("Comment added to line {line}");
                 } else {
                     // Fallback to file comment if no line specified
-                    let comment = Comment::new(content, self.comment_type, None);
+                    let comment =
+                        Comment::new(content, self.comment_type.clone(), None).with_author(author);
                     review.add_file_comment(comment);
                     message = "File comment added".to_string();
                 }
             }
 
+            self.session.record_change(message.clone(), before);
             self.dirty = true;
             self.set_message(message);
             self.rebuild_annotations();
@@ -1801,13 +3258,30 @@ impl App {
         self.exit_comment_mode();
     }
 
+    pub fn undo(&mut self) {
+        match self.session.undo() {
+            Some(description) => {
+                self.dirty = true;
+                self.rebuild_annotations();
+                self.set_message(format!("Undo: {description}"));
+            }
+            None => self.set_message("Already at oldest change"),
+        }
+    }
+
+    pub fn redo(&mut self) {
+        match self.session.redo() {
+            Some(description) => {
+                self.dirty = true;
+                self.rebuild_annotations();
+                self.set_message(format!("Redo: {description}"));
+            }
+            None => self.set_message("Already at newest change"),
+        }
+    }
+
     pub fn cycle_comment_type(&mut self) {
-        self.comment_type = match self.comment_type {
-            CommentType::Note => CommentType::Suggestion,
-            CommentType::Suggestion => CommentType::Issue,
-            CommentType::Issue => CommentType::Praise,
-            CommentType::Praise => CommentType::Note,
-        };
+        self.comment_type = self.comment_types.next(&self.comment_type);
     }
 
     pub fn toggle_help(&mut self) {
@@ -1819,6 +3293,14 @@ impl App {
         }
     }
 
+    pub fn toggle_stats(&mut self) {
+        self.input_mode = if self.input_mode == InputMode::Stats {
+            InputMode::Normal
+        } else {
+            InputMode::Stats
+        };
+    }
+
     pub fn help_scroll_down(&mut self, lines: usize) {
         let max_offset = self
             .help_state
@@ -1914,17 +3396,14 @@ impl App {
         ) {
             let highlighter = self.theme.syntax_highlighter();
             match self.vcs.get_working_tree_diff(highlighter) {
-                Ok(diff_files) => {
+                Ok(mut diff_files) => {
+                    self.filter_diff_files(&mut diff_files);
+                    self.register_files(&diff_files);
                     self.diff_files = diff_files;
                     self.diff_source = DiffSource::WorkingTree;
+                    self.clear_expanded_gaps();
 
-                    // Update session for new files
-                    for file in &self.diff_files {
-                        let path = file.display_path().clone();
-                        self.session.add_file(path, file.status);
-                    }
-
-                    self.sort_files_by_directory(true);
+                    self.sort_files(true);
                     self.expand_all_dirs();
                 }
                 Err(_) => {
@@ -2165,9 +3644,10 @@ impl App {
         };
 
         // Collect selected entries in order from oldest to newest (end..start).
-        let selected_commits: Vec<&CommitInfo> = (start..=end)
+        let selected_commits: Vec<CommitInfo> = (start..=end)
             .rev()
             .filter_map(|i| self.commit_list.get(i))
+            .cloned()
             .collect();
 
         if selected_commits.is_empty() {
@@ -2175,9 +3655,7 @@ impl App {
             return Ok(());
         }
 
-        let selected_working_tree = selected_commits
-            .iter()
-            .any(|c| Self::is_working_tree_commit(c));
+        let selected_working_tree = selected_commits.iter().any(Self::is_working_tree_commit);
         let selected_ids: Vec<String> = selected_commits
             .iter()
             .filter(|c| !Self::is_working_tree_commit(c))
@@ -2185,33 +3663,48 @@ impl App {
             .collect();
 
         if selected_working_tree && !selected_ids.is_empty() {
-            return self.load_working_tree_and_commits_selection(selected_ids);
+            return self.load_working_tree_and_commits_selection(selected_ids, None);
         }
 
         if selected_working_tree {
-            return self.load_working_tree_selection();
+            return self.load_working_tree_selection(None);
         }
 
+        self.load_commit_range_selection(selected_commits, None)
+    }
+
+    /// Review `selected_commits` (oldest first) with `session`, or the latest
+    /// stored session for them
+    fn load_commit_range_selection(
+        &mut self,
+        selected_commits: Vec<CommitInfo>,
+        session: Option<ReviewSession>,
+    ) -> Result<()> {
+        let selected_ids: Vec<String> = selected_commits.iter().map(|c| c.id.clone()).collect();
+
         // Get the diff for the selected commits
         let highlighter = self.theme.syntax_highlighter();
-        let diff_files = self.vcs.get_commit_range_diff(&selected_ids, highlighter)?;
+        let mut diff_files = self.vcs.get_commit_range_diff(&selected_ids, highlighter)?;
 
         if diff_files.is_empty() {
             self.set_message("No changes in selected commits");
             return Ok(());
         }
+        Self::prepend_commit_messages(&mut diff_files, &selected_commits);
 
         // Update session with the newest commit as base
         let newest_commit_id = selected_ids.last().unwrap().clone();
-        let loaded_session = load_latest_session_for_context(
-            &self.vcs_info.root_path,
-            self.vcs_info.branch_name.as_deref(),
-            &newest_commit_id,
-            SessionDiffSource::CommitRange,
-            Some(selected_ids.as_slice()),
-        )
-        .ok()
-        .and_then(|found| found.map(|(_path, session)| session));
+        let loaded_session = session.or_else(|| {
+            load_latest_session_for_context(
+                &self.vcs_info.root_path,
+                self.vcs_info.branch_name.as_deref(),
+                &newest_commit_id,
+                SessionDiffSource::CommitRange,
+                Some(selected_ids.as_slice()),
+            )
+            .ok()
+            .and_then(|found| found.map(|(_path, session)| session))
+        });
 
         let mut session = loaded_session.unwrap_or_else(|| {
             let mut session = ReviewSession::new(
@@ -2230,12 +3723,8 @@ impl App {
         }
 
         self.session = session;
-
-        // Add files to session
-        for file in &diff_files {
-            let path = file.display_path().clone();
-            self.session.add_file(path, file.status);
-        }
+        self.filter_diff_files(&mut diff_files);
+        self.register_files(&diff_files);
 
         // Update app state
         self.diff_files = diff_files;
@@ -2247,11 +3736,7 @@ impl App {
         self.file_list_state = FileListState::default();
 
         // Set up inline commit selector for multi-commit reviews (newest-first display order)
-        self.review_commits = selected_commits
-            .iter()
-            .rev()
-            .map(|c| (*c).clone())
-            .collect();
+        self.review_commits = selected_commits.into_iter().rev().collect();
         self.range_diff_files = Some(self.diff_files.clone());
         self.commit_list = self.review_commits.clone();
         self.commit_list_cursor = 0;
@@ -2266,8 +3751,9 @@ impl App {
         self.show_commit_selector = self.review_commits.len() > 1;
         self.commit_diff_cache.clear();
         self.saved_inline_selection = None;
+        self.clear_expanded_gaps();
 
-        self.sort_files_by_directory(true);
+        self.sort_files(true);
         self.expand_all_dirs();
         self.rebuild_annotations();
 
@@ -2291,9 +3777,8 @@ impl App {
             self.diff_state = DiffState::default();
             self.diff_state.wrap_lines = wrap;
             self.file_list_state = FileListState::default();
-            self.expanded_gaps.clear();
-            self.expanded_content.clear();
-            self.sort_files_by_directory(true);
+            self.clear_expanded_gaps();
+            self.sort_files(true);
             self.expand_all_dirs();
             self.rebuild_annotations();
             return Ok(());
@@ -2306,9 +3791,8 @@ impl App {
             self.diff_state = DiffState::default();
             self.diff_state.wrap_lines = wrap;
             self.file_list_state = FileListState::default();
-            self.expanded_gaps.clear();
-            self.expanded_content.clear();
-            self.sort_files_by_directory(true);
+            self.clear_expanded_gaps();
+            self.sort_files(true);
             self.expand_all_dirs();
             self.rebuild_annotations();
             return Ok(());
@@ -2322,11 +3806,18 @@ impl App {
             .collect();
 
         let highlighter = self.theme.syntax_highlighter();
-        let diff_files = match self.vcs.get_commit_range_diff(&selected_ids, highlighter) {
+        let mut diff_files = match self.vcs.get_commit_range_diff(&selected_ids, highlighter) {
             Ok(files) => files,
             Err(TuicrError::NoChanges) => Vec::new(),
             Err(e) => return Err(e),
         };
+        self.filter_diff_files(&mut diff_files);
+        Self::prepend_commit_messages(
+            &mut diff_files,
+            (start..=end)
+                .rev()
+                .filter_map(|i| self.review_commits.get(i)),
+        );
         self.commit_diff_cache
             .insert((start, end), diff_files.clone());
         self.diff_files = diff_files;
@@ -2336,18 +3827,21 @@ impl App {
         self.diff_state = DiffState::default();
         self.diff_state.wrap_lines = wrap;
         self.file_list_state = FileListState::default();
-        self.expanded_gaps.clear();
-        self.expanded_content.clear();
-        self.sort_files_by_directory(true);
+        self.clear_expanded_gaps();
+        self.sort_files(true);
         self.expand_all_dirs();
         self.rebuild_annotations();
 
         Ok(())
     }
 
-    fn load_working_tree_and_commits_selection(&mut self, selected_ids: Vec<String>) -> Result<()> {
+    fn load_working_tree_and_commits_selection(
+        &mut self,
+        selected_ids: Vec<String>,
+        session: Option<ReviewSession>,
+    ) -> Result<()> {
         let highlighter = self.theme.syntax_highlighter();
-        let diff_files = match self
+        let mut diff_files = match self
             .vcs
             .get_working_tree_with_commits_diff(&selected_ids, highlighter)
         {
@@ -2360,15 +3854,17 @@ impl App {
         };
 
         let newest_commit_id = selected_ids.last().unwrap().clone();
-        let loaded_session = load_latest_session_for_context(
-            &self.vcs_info.root_path,
-            self.vcs_info.branch_name.as_deref(),
-            &newest_commit_id,
-            SessionDiffSource::WorkingTreeAndCommits,
-            Some(selected_ids.as_slice()),
-        )
-        .ok()
-        .and_then(|found| found.map(|(_path, session)| session));
+        let loaded_session = session.or_else(|| {
+            load_latest_session_for_context(
+                &self.vcs_info.root_path,
+                self.vcs_info.branch_name.as_deref(),
+                &newest_commit_id,
+                SessionDiffSource::WorkingTreeAndCommits,
+                Some(selected_ids.as_slice()),
+            )
+            .ok()
+            .and_then(|found| found.map(|(_path, session)| session))
+        });
 
         let mut session = loaded_session.unwrap_or_else(|| {
             let mut session = ReviewSession::new(
@@ -2387,25 +3883,23 @@ impl App {
         }
 
         self.session = session;
-
-        for file in &diff_files {
-            let path = file.display_path().clone();
-            self.session.add_file(path, file.status);
-        }
+        self.filter_diff_files(&mut diff_files);
+        self.register_files(&diff_files);
 
         self.diff_files = diff_files;
         self.diff_source = DiffSource::WorkingTreeAndCommits(selected_ids);
+        self.clear_expanded_gaps();
         self.input_mode = InputMode::Normal;
         self.diff_state = DiffState::default();
         self.file_list_state = FileListState::default();
 
-        self.sort_files_by_directory(true);
+        self.sort_files(true);
         self.expand_all_dirs();
         self.rebuild_annotations();
         Ok(())
     }
 
-    fn sort_files_by_directory(&mut self, reset_position: bool) {
+    fn sort_files(&mut self, reset_position: bool) {
         use std::collections::BTreeMap;
         use std::path::Path;
 
@@ -2415,11 +3909,21 @@ impl App {
             None
         };
 
+        let sort = self.session.file_list.sort;
         let mut dir_map: BTreeMap<String, Vec<DiffFile>> = BTreeMap::new();
+        // Commit message pseudo-files always stay at the top, in commit order
+        let mut commit_messages: Vec<DiffFile> = Vec::new();
 
         for file in self.diff_files.drain(..) {
+            if file.is_commit_message() {
+                commit_messages.push(file);
+                continue;
+            }
+            // The flat view orders all files together instead of per directory
             let path = file.display_path();
-            let dir = if let Some(parent) = path.parent() {
+            let dir = if self.session.file_list.flat {
+                String::new()
+            } else if let Some(parent) = path.parent() {
                 if parent == Path::new("") {
                     ".".to_string()
                 } else {
@@ -2432,6 +3936,11 @@ impl App {
             dir_map.entry(dir).or_default().push(file);
         }
 
+        for files in dir_map.values_mut() {
+            files.sort_by(|a, b| sort.compare(a, b));
+        }
+
+        self.diff_files.extend(commit_messages);
         for (_dir, files) in dir_map {
             self.diff_files.extend(files);
         }
@@ -2495,24 +4004,38 @@ impl App {
         let file = self.diff_files.get(gap_id.file_idx).ok_or_else(|| {
             TuicrError::CorruptedSession(format!("Invalid file index: {}", gap_id.file_idx))
         })?;
+        let hunks = self.shown_hunks(gap_id.file_idx);
 
-        let hunk = file.hunks.get(gap_id.hunk_idx).ok_or_else(|| {
-            TuicrError::CorruptedSession(format!("Invalid hunk index: {}", gap_id.hunk_idx))
-        })?;
-
-        // Get previous hunk to calculate gap boundaries
-        let prev_hunk = if gap_id.hunk_idx > 0 {
-            file.hunks.get(gap_id.hunk_idx - 1)
+        // Calculate line range to fetch
+        let (start_line, end_line) = if gap_id.hunk_idx == hunks.len() {
+            // Trailing gap after the last hunk runs to the end of the file
+            let last = hunks.last().ok_or_else(|| {
+                TuicrError::CorruptedSession(format!("Invalid hunk index: {}", gap_id.hunk_idx))
+            })?;
+            if !Self::has_trailing_gap(file, hunks) {
+                return Ok(());
+            }
+            // A hunk with no new-side lines starts at the line before it
+            let last_end = last.new_start + last.new_count.max(1);
+            (last_end, u32::MAX)
         } else {
-            None
-        };
+            let hunk = hunks.get(gap_id.hunk_idx).ok_or_else(|| {
+                TuicrError::CorruptedSession(format!("Invalid hunk index: {}", gap_id.hunk_idx))
+            })?;
 
-        // Calculate line range to fetch
-        let (start_line, end_line) = match prev_hunk {
-            None => (1, hunk.new_start.saturating_sub(1)),
-            Some(prev) => {
-                let prev_end = prev.new_start + prev.new_count;
-                (prev_end, hunk.new_start.saturating_sub(1))
+            // Get previous hunk to calculate gap boundaries
+            let prev_hunk = if gap_id.hunk_idx > 0 {
+                hunks.get(gap_id.hunk_idx - 1)
+            } else {
+                None
+            };
+
+            match prev_hunk {
+                None => (1, hunk.new_start.saturating_sub(1)),
+                Some(prev) => {
+                    let prev_end = prev.new_start + prev.new_count;
+                    (prev_end, hunk.new_start.saturating_sub(1))
+                }
             }
         };
 
@@ -2520,13 +4043,33 @@ impl App {
             return Ok(()); // No gap to expand
         }
 
+        // Unchanged lines sit at a fixed distance between the two versions
+        // of the file; blame is looked up by the old line number
+        let old_offset = match hunks.get(gap_id.hunk_idx) {
+            Some(hunk) => {
+                i64::from(hunk.old_start)
+                    - i64::from(hunk.old_count.min(1))
+                    - (i64::from(hunk.new_start) - i64::from(hunk.new_count.min(1)))
+            }
+            None => hunks.last().map_or(0, |last| {
+                i64::from(last.old_start + last.old_count.max(1))
+                    - i64::from(last.new_start + last.new_count.max(1))
+            }),
+        };
+
         let file_path = file.display_path().clone();
         let file_status = file.status;
 
         // Fetch the context lines
-        let lines = self
-            .vcs
-            .fetch_context_lines(&file_path, file_status, start_line, end_line)?;
+        let mut lines =
+            self.vcs
+                .fetch_context_lines(&file_path, file_status, start_line, end_line)?;
+        for line in &mut lines {
+            line.old_lineno = line
+                .new_lineno
+                .and_then(|n| u32::try_from(i64::from(n) + old_offset).ok())
+                .filter(|n| *n > 0);
+        }
 
         self.expanded_content.insert(gap_id.clone(), lines);
         self.expanded_gaps.insert(gap_id);
@@ -2535,6 +4078,480 @@ impl App {
         Ok(())
     }
 
+    /// Whether the file may have unchanged lines after its last hunk.
+    /// Added and deleted files are covered by their hunk entirely; binaries
+    /// and commit messages have no file content to read.
+    fn has_trailing_gap(file: &DiffFile, hunks: &[DiffHunk]) -> bool {
+        !hunks.is_empty()
+            && !file.is_binary
+            && !matches!(file.status, FileStatus::Added | FileStatus::Deleted)
+            && !file.is_commit_message()
+    }
+
+    /// All gaps in a file that can be expanded, including the trailing one
+    fn file_gap_ids(&self, file_idx: usize) -> Vec<GapId> {
+        let Some(file) = self.diff_files.get(file_idx) else {
+            return Vec::new();
+        };
+        let hunks = self.shown_hunks(file_idx);
+        let mut gaps: Vec<GapId> = (0..hunks.len())
+            .map(|hunk_idx| GapId { file_idx, hunk_idx })
+            .filter(|gap_id| self.gap_size(gap_id).is_some_and(|gap| gap > 0))
+            .collect();
+        if Self::has_trailing_gap(file, hunks) {
+            gaps.push(GapId {
+                file_idx,
+                hunk_idx: hunks.len(),
+            });
+        }
+        gaps
+    }
+
+    /// Expand every gap in the current file, including the lines after the
+    /// last hunk. Collapses them again if they are all expanded already.
+    pub fn toggle_file_context(&mut self) {
+        let file_idx = self.diff_state.current_file_idx;
+        if self.full_file_views.contains_key(&file_idx) {
+            self.set_message("File is already shown in full");
+            return;
+        }
+
+        let gaps = self.file_gap_ids(file_idx);
+        if gaps.is_empty() {
+            self.set_message("No hidden context in this file");
+            return;
+        }
+
+        if gaps
+            .iter()
+            .all(|gap_id| self.expanded_gaps.contains(gap_id))
+        {
+            for gap_id in &gaps {
+                self.expanded_gaps.remove(gap_id);
+                self.expanded_content.remove(gap_id);
+            }
+            self.rebuild_annotations();
+            self.set_message("Collapsed file context");
+            return;
+        }
+
+        for gap_id in gaps {
+            if let Err(e) = self.expand_gap(gap_id) {
+                self.set_error(format!("Failed to expand: {e}"));
+                return;
+            }
+        }
+        self.set_message("Expanded file context");
+    }
+
+    /// Whether a generated or vendored file is shown as a one-line placeholder.
+    pub fn is_file_collapsed(&self, file: &DiffFile) -> bool {
+        let path = file.display_path();
+        self.generated_files.contains(path) && !self.expanded_generated.contains(path)
+    }
+
+    pub fn is_file_folded(&self, path: &PathBuf) -> bool {
+        self.folded_files.contains(path)
+            || (self.fold_reviewed
+                && self.session.is_file_reviewed(path)
+                && !self.unfolded_files.contains(path))
+    }
+
+    pub fn is_hunk_folded(&self, path: &Path, hunk_idx: usize) -> bool {
+        self.folded_hunks.contains(&(path.to_path_buf(), hunk_idx))
+    }
+
+    /// The file and, if the cursor is inside one, the hunk that fold
+    /// commands act on.
+    fn fold_target_at_cursor(&self) -> Option<(usize, Option<usize>)> {
+        let last = self.line_annotations.len().checked_sub(1)?;
+        let cursor = self.diff_state.cursor_line.min(last);
+        // Spacing, line comments and diagnostics belong to whatever precedes
+        // them
+        let mut file_only = false;
+        for annotation in self.line_annotations[..=cursor].iter().rev() {
+            let (file_idx, hunk_idx) = match annotation {
+                AnnotatedLine::Spacing => {
+                    file_only = true;
+                    continue;
+                }
+                AnnotatedLine::LineComment { .. } | AnnotatedLine::Diagnostic { .. } => continue,
+                AnnotatedLine::HunkHeader { file_idx, hunk_idx }
+                | AnnotatedLine::DiffLine {
+                    file_idx, hunk_idx, ..
+                }
+                | AnnotatedLine::SideBySideLine {
+                    file_idx, hunk_idx, ..
+                } => (*file_idx, Some(*hunk_idx)),
+                AnnotatedLine::FileHeader { file_idx }
+                | AnnotatedLine::FileComment { file_idx, .. }
+                | AnnotatedLine::BinaryOrEmpty { file_idx }
+                | AnnotatedLine::CollapsedFile { file_idx } => (*file_idx, None),
+                AnnotatedLine::Expander { gap_id }
+                | AnnotatedLine::ExpandedContext { gap_id, .. } => (gap_id.file_idx, None),
+            };
+            return Some((file_idx, hunk_idx.filter(|_| !file_only)));
+        }
+        None
+    }
+
+    /// Apply a fold change, then put the cursor on the folded header.
+    fn set_fold(&mut self, file_idx: usize, hunk_idx: Option<usize>, folded: bool) {
+        let Some(path) = self
+            .diff_files
+            .get(file_idx)
+            .map(|file| file.display_path().clone())
+        else {
+            return;
+        };
+
+        match hunk_idx {
+            Some(hunk_idx) if folded => {
+                self.folded_hunks.insert((path, hunk_idx));
+            }
+            Some(hunk_idx) => {
+                self.folded_hunks.remove(&(path, hunk_idx));
+            }
+            None if folded => {
+                self.unfolded_files.remove(&path);
+                self.folded_files.insert(path);
+            }
+            None => {
+                self.folded_files.remove(&path);
+                if self.session.is_file_reviewed(&path) {
+                    self.unfolded_files.insert(path);
+                }
+            }
+        }
+        self.rebuild_annotations();
+
+        let header =
+            self.line_annotations
+                .iter()
+                .position(|annotation| match (annotation, hunk_idx) {
+                    (
+                        AnnotatedLine::HunkHeader {
+                            file_idx: f,
+                            hunk_idx: h,
+                        },
+                        Some(hunk_idx),
+                    ) => *f == file_idx && *h == hunk_idx,
+                    (AnnotatedLine::FileHeader { file_idx: f }, None) => *f == file_idx,
+                    _ => false,
+                });
+        if let Some(line) = header {
+            self.diff_state.cursor_line = line;
+            self.ensure_cursor_visible();
+            self.update_current_file_from_cursor();
+        }
+    }
+
+    /// `za`: toggle the fold under the cursor.
+    pub fn toggle_fold(&mut self) {
+        let Some((file_idx, hunk_idx)) = self.fold_target_at_cursor() else {
+            return;
+        };
+        let path = self.diff_files[file_idx].display_path();
+        let folded = match hunk_idx {
+            Some(hunk_idx) => self.is_hunk_folded(path, hunk_idx),
+            None => self.is_file_folded(path),
+        };
+        self.set_fold(file_idx, hunk_idx, !folded);
+    }
+
+    /// `zo`: open the fold under the cursor.
+    pub fn open_fold(&mut self) {
+        if let Some((file_idx, hunk_idx)) = self.fold_target_at_cursor() {
+            self.set_fold(file_idx, hunk_idx, false);
+        }
+    }
+
+    /// `zc`: close the fold under the cursor; on a folded hunk, fold its file.
+    pub fn close_fold(&mut self) {
+        let Some((file_idx, hunk_idx)) = self.fold_target_at_cursor() else {
+            return;
+        };
+        let path = self.diff_files[file_idx].display_path();
+        let hunk_idx = hunk_idx.filter(|&hunk_idx| !self.is_hunk_folded(path, hunk_idx));
+        self.set_fold(file_idx, hunk_idx, true);
+    }
+
+    /// `zM`: fold every file.
+    pub fn fold_all(&mut self) {
+        self.unfolded_files.clear();
+        self.folded_files = self
+            .diff_files
+            .iter()
+            .map(|file| file.display_path().clone())
+            .collect();
+        self.rebuild_annotations();
+        self.jump_to_file(self.diff_state.current_file_idx);
+    }
+
+    /// `zR`: open every file and hunk fold, including reviewed files.
+    pub fn unfold_all(&mut self) {
+        self.folded_files.clear();
+        self.folded_hunks.clear();
+        self.unfolded_files = self
+            .diff_files
+            .iter()
+            .map(|file| file.display_path().clone())
+            .collect();
+        self.rebuild_annotations();
+        self.jump_to_file(self.diff_state.current_file_idx);
+    }
+
+    pub fn toggle_fold_reviewed(&mut self) {
+        let enabled = !self.fold_reviewed;
+        self.set_fold_reviewed(enabled);
+    }
+
+    pub fn set_fold_reviewed(&mut self, enabled: bool) {
+        self.fold_reviewed = enabled;
+        self.unfolded_files.clear();
+        self.rebuild_annotations();
+        self.jump_to_file(self.diff_state.current_file_idx);
+        let status = if enabled { "on" } else { "off" };
+        self.set_message(format!("Fold reviewed files: {status}"));
+    }
+
+    pub fn set_file_filter(&mut self, arg: &str) {
+        match arg.parse() {
+            Ok(filter) => {
+                self.session.file_list.filter = filter;
+                self.dirty = true;
+                self.ensure_valid_tree_selection();
+                self.set_message(format!("File filter: {}", self.session.file_list.filter));
+            }
+            Err(e) => self.set_warning(e),
+        }
+    }
+
+    pub fn set_file_sort(&mut self, arg: &str) {
+        match arg.parse() {
+            Ok(sort) => {
+                self.session.file_list.sort = sort;
+                self.resort_files();
+                self.set_message(format!("Sort files by {sort}"));
+            }
+            Err(e) => self.set_warning(e),
+        }
+    }
+
+    pub fn toggle_flat_file_list(&mut self) {
+        self.set_flat_file_list(!self.session.file_list.flat);
+    }
+
+    pub fn set_flat_file_list(&mut self, flat: bool) {
+        self.session.file_list.flat = flat;
+        self.resort_files();
+        let layout = if flat { "flat" } else { "tree" };
+        self.set_message(format!("File list: {layout}"));
+    }
+
+    /// Reorder files after the sort or layout changed, keeping the current file.
+    fn resort_files(&mut self) {
+        let old_paths: Vec<PathBuf> = self
+            .diff_files
+            .iter()
+            .map(|file| file.display_path().clone())
+            .collect();
+        self.dirty = true;
+        self.sort_files(false);
+        self.remap_file_indices(&old_paths);
+        self.rebuild_annotations();
+        self.jump_to_file(self.diff_state.current_file_idx);
+        self.ensure_valid_tree_selection();
+    }
+
+    /// Move expansions and full-file views, which are keyed by file index, to
+    /// where their files went in the new order. `old_paths` are the files'
+    /// paths in the old order. Hunk folds are keyed by path and stay as they are.
+    fn remap_file_indices(&mut self, old_paths: &[PathBuf]) {
+        let new_idx: HashMap<&PathBuf, usize> = self
+            .diff_files
+            .iter()
+            .enumerate()
+            .map(|(idx, file)| (file.display_path(), idx))
+            .collect();
+        let remap = |file_idx: usize| {
+            old_paths
+                .get(file_idx)
+                .and_then(|path| new_idx.get(path))
+                .copied()
+        };
+        let remap_gap = |gap_id: GapId| {
+            remap(gap_id.file_idx).map(|file_idx| GapId {
+                file_idx,
+                hunk_idx: gap_id.hunk_idx,
+            })
+        };
+
+        self.expanded_gaps = std::mem::take(&mut self.expanded_gaps)
+            .into_iter()
+            .filter_map(remap_gap)
+            .collect();
+        self.expanded_content = std::mem::take(&mut self.expanded_content)
+            .into_iter()
+            .filter_map(|(gap_id, lines)| Some((remap_gap(gap_id)?, lines)))
+            .collect();
+        self.full_file_views = std::mem::take(&mut self.full_file_views)
+            .into_iter()
+            .filter_map(|(file_idx, view)| Some((remap(file_idx)?, view)))
+            .collect();
+    }
+
+    /// File index of the collapsed-file placeholder under the cursor, if any
+    pub fn collapsed_file_at_cursor(&self) -> Option<usize> {
+        match self.line_annotations.get(self.diff_state.cursor_line) {
+            Some(AnnotatedLine::CollapsedFile { file_idx }) => Some(*file_idx),
+            _ => None,
+        }
+    }
+
+    /// Expand a generated file, or collapse it again.
+    pub fn toggle_generated_file(&mut self, file_idx: usize) {
+        let Some(path) = self
+            .diff_files
+            .get(file_idx)
+            .map(|file| file.display_path().clone())
+        else {
+            return;
+        };
+        if !self.generated_files.contains(&path) {
+            self.set_message("Not a generated file");
+            return;
+        }
+
+        if self.expanded_generated.remove(&path) {
+            self.set_message("Collapsed generated file");
+        } else {
+            self.expanded_generated.insert(path);
+            self.set_message("Expanded generated file");
+        }
+        self.rebuild_annotations();
+        self.jump_to_file(file_idx);
+    }
+
+    /// Hunks the file at `file_idx` is shown with, which are not its diff
+    /// hunks while it is shown in full
+    pub fn shown_hunks(&self, file_idx: usize) -> &[DiffHunk] {
+        match self.diff_files.get(file_idx) {
+            Some(file) => FullFileView::shown_hunks(&self.full_file_views, file_idx, file),
+            None => &[],
+        }
+    }
+
+    /// Cycle the current file between its diff, the full new side and the full old side.
+    pub fn cycle_full_file_view(&mut self) {
+        let file_idx = self.diff_state.current_file_idx;
+        let Some(file) = self.diff_files.get(file_idx) else {
+            return;
+        };
+        if file.is_binary || file.is_commit_message() {
+            self.set_message("Full-file view is not available for this file");
+            return;
+        }
+
+        let current = self.full_file_views.get(&file_idx).map(|view| view.side);
+        let next = match current {
+            None if file.status != FileStatus::Deleted => Some(LineSide::New),
+            None | Some(LineSide::New) if file.status != FileStatus::Added => Some(LineSide::Old),
+            _ => None,
+        };
+
+        let result = match next {
+            Some(side) => self.show_full_file(file_idx, side),
+            None => {
+                self.full_file_views.remove(&file_idx);
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            self.set_error(format!("Failed to load full file: {e}"));
+            return;
+        }
+
+        self.rebuild_annotations();
+        self.jump_to_file(file_idx);
+        let status = match next {
+            Some(LineSide::New) => "new side",
+            Some(LineSide::Old) => "old side",
+            None => "off",
+        };
+        self.set_message(format!("Full-file view: {status}"));
+    }
+
+    /// Commit whose tree is the new side of the current diff.
+    /// `None` means the new side is the working tree.
+    fn tip_commit(&self) -> Option<String> {
+        match &self.diff_source {
+            DiffSource::WorkingTree | DiffSource::WorkingTreeAndCommits(_) => None,
+            DiffSource::CommitRange(ids) => {
+                // review_commits is newest-first, so the range start is the tip
+                if let Some((start, _)) = self.commit_selection_range
+                    && let Some(commit) = self.review_commits.get(start)
+                {
+                    return Some(commit.id.clone());
+                }
+                ids.last().cloned()
+            }
+        }
+    }
+
+    fn show_full_file(&mut self, file_idx: usize, side: LineSide) -> Result<()> {
+        let file = &self.diff_files[file_idx];
+        let base_commit = self.base_commit();
+        let tip_commit = self.tip_commit();
+        let (path, version) = match side {
+            LineSide::New => (
+                file.display_path().clone(),
+                match tip_commit.as_deref() {
+                    Some(id) => FileVersion::Commit(id),
+                    None => FileVersion::WorkingTree,
+                },
+            ),
+            LineSide::Old => (
+                file.old_path
+                    .as_ref()
+                    .unwrap_or(file.display_path())
+                    .clone(),
+                FileVersion::ParentOf(base_commit.as_deref()),
+            ),
+        };
+        let content = self.vcs.file_content(&path, version)?;
+        let file_lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+        let file = &self.diff_files[file_idx];
+        let mut hunk = full_file_hunk(&file.hunks, &file_lines, side);
+
+        let highlighter = self.theme.syntax_highlighter();
+        if let Some(highlighted) = highlighter.highlight_file_lines(&path, &file_lines) {
+            for line in &mut hunk.lines {
+                let lineno = match side {
+                    LineSide::New => line.new_lineno,
+                    LineSide::Old => line.old_lineno,
+                };
+                let spans = lineno
+                    .and_then(|ln| highlighted.get(ln as usize - 1))
+                    .cloned()
+                    .flatten();
+                line.highlighted_spans =
+                    spans.map(|spans| highlighter.apply_diff_background(spans, line.origin));
+            }
+        }
+
+        // Gaps refer to the diff hunks, which are hidden while the full file is shown
+        self.expanded_gaps
+            .retain(|gap_id| gap_id.file_idx != file_idx);
+        self.expanded_content
+            .retain(|gap_id, _| gap_id.file_idx != file_idx);
+
+        self.full_file_views
+            .insert(file_idx, FullFileView { side, hunk });
+        Ok(())
+    }
+
     /// Collapse an expanded gap
     pub fn collapse_gap(&mut self, gap_id: GapId) {
         self.expanded_gaps.remove(&gap_id);
@@ -2542,10 +4559,13 @@ impl App {
         self.rebuild_annotations();
     }
 
-    /// Clear all expanded gaps (called when reloading diffs)
+    /// Clear all expanded gaps and full-file views (called when reloading diffs)
     pub fn clear_expanded_gaps(&mut self) {
         self.expanded_gaps.clear();
         self.expanded_content.clear();
+        self.full_file_views.clear();
+        // Hunk indices are only meaningful for the diff they were folded in
+        self.folded_hunks.clear();
     }
 
     /// Rebuild the line annotations cache. Call this when:
@@ -2555,23 +4575,25 @@ impl App {
     /// - Diff view mode changes
     pub fn rebuild_annotations(&mut self) {
         self.line_annotations.clear();
+        let comment_width = self.comment_text_width();
 
         for (file_idx, file) in self.diff_files.iter().enumerate() {
             let path = file.display_path();
+            let hunks = FullFileView::shown_hunks(&self.full_file_views, file_idx, file);
 
             // File header
             self.line_annotations
                 .push(AnnotatedLine::FileHeader { file_idx });
 
-            // If reviewed, skip all content for this file
-            if self.session.is_file_reviewed(path) {
+            // If folded, skip all content for this file
+            if self.is_file_folded(path) {
                 continue;
             }
 
             // File comments
             if let Some(review) = self.session.files.get(path) {
                 for (comment_idx, comment) in review.file_comments.iter().enumerate() {
-                    let comment_lines = Self::comment_display_lines(comment);
+                    let comment_lines = Self::comment_display_lines(comment, comment_width);
                     for _ in 0..comment_lines {
                         self.line_annotations.push(AnnotatedLine::FileComment {
                             file_idx,
@@ -2581,7 +4603,10 @@ impl App {
                 }
             }
 
-            if file.is_binary || file.hunks.is_empty() {
+            if self.is_file_collapsed(file) {
+                self.line_annotations
+                    .push(AnnotatedLine::CollapsedFile { file_idx });
+            } else if file.is_binary || hunks.is_empty() {
                 self.line_annotations
                     .push(AnnotatedLine::BinaryOrEmpty { file_idx });
             } else {
@@ -2594,10 +4619,10 @@ impl App {
                     .cloned()
                     .unwrap_or_default();
 
-                for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+                for (hunk_idx, hunk) in hunks.iter().enumerate() {
                     // Calculate gap before this hunk
                     let prev_hunk = if hunk_idx > 0 {
-                        file.hunks.get(hunk_idx - 1)
+                        hunks.get(hunk_idx - 1)
                     } else {
                         None
                     };
@@ -2630,6 +4655,9 @@ impl App {
                     // Hunk header
                     self.line_annotations
                         .push(AnnotatedLine::HunkHeader { file_idx, hunk_idx });
+                    if self.is_hunk_folded(path, hunk_idx) {
+                        continue;
+                    }
 
                     // Diff lines - handle differently based on view mode
                     match self.diff_view_mode {
@@ -2640,6 +4668,8 @@ impl App {
                                 hunk_idx,
                                 &hunk.lines,
                                 &line_comments,
+                                (&self.diagnostics, path),
+                                comment_width,
                             );
                         }
                         DiffViewMode::SideBySide => {
@@ -2649,10 +4679,26 @@ impl App {
                                 hunk_idx,
                                 &hunk.lines,
                                 &line_comments,
+                                (&self.diagnostics, path),
+                                comment_width,
                             );
                         }
                     }
                 }
+
+                // Trailing context after the last hunk, once expanded
+                let tail_gap = GapId {
+                    file_idx,
+                    hunk_idx: hunks.len(),
+                };
+                if let Some(content) = self.expanded_content.get(&tail_gap) {
+                    for content_idx in 0..content.len() {
+                        self.line_annotations.push(AnnotatedLine::ExpandedContext {
+                            gap_id: tail_gap.clone(),
+                            line_idx: content_idx,
+                        });
+                    }
+                }
             }
 
             // Spacing line
@@ -2666,6 +4712,7 @@ impl App {
         line_no: Option<u32>,
         line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
         side: LineSide,
+        comment_width: usize,
     ) {
         let Some(ln) = line_no else {
             return;
@@ -2683,7 +4730,7 @@ impl App {
                 continue;
             }
 
-            let comment_lines = Self::comment_display_lines(comment);
+            let comment_lines = Self::comment_display_lines(comment, comment_width);
             for _ in 0..comment_lines {
                 annotations.push(AnnotatedLine::LineComment {
                     file_idx,
@@ -2695,6 +4742,27 @@ impl App {
         }
     }
 
+    /// Diagnostics shown under new-side line `line_no`, before its comments
+    fn push_diagnostics(
+        annotations: &mut Vec<AnnotatedLine>,
+        file_idx: usize,
+        line_no: Option<u32>,
+        line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
+        (diagnostics, path): (&Diagnostics, &Path),
+    ) {
+        let Some(line) = line_no else {
+            return;
+        };
+        let comments = line_comments.get(&line).map_or(&[][..], Vec::as_slice);
+        for (diagnostic_idx, _) in diagnostics.shown_on_line(path, line, comments) {
+            annotations.push(AnnotatedLine::Diagnostic {
+                file_idx,
+                line,
+                diagnostic_idx,
+            });
+        }
+    }
+
     /// Build annotations for unified diff mode (one annotation per diff line)
     fn build_unified_diff_annotations(
         annotations: &mut Vec<AnnotatedLine>,
@@ -2702,6 +4770,8 @@ impl App {
         hunk_idx: usize,
         lines: &[crate::model::DiffLine],
         line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
+        diagnostics: (&Diagnostics, &Path),
+        comment_width: usize,
     ) {
         for (line_idx, diff_line) in lines.iter().enumerate() {
             annotations.push(AnnotatedLine::DiffLine {
@@ -2711,6 +4781,13 @@ impl App {
                 old_lineno: diff_line.old_lineno,
                 new_lineno: diff_line.new_lineno,
             });
+            Self::push_diagnostics(
+                annotations,
+                file_idx,
+                diff_line.new_lineno,
+                line_comments,
+                diagnostics,
+            );
 
             // Line comments on old side (delete lines)
             if let Some(old_ln) = diff_line.old_lineno {
@@ -2720,6 +4797,7 @@ impl App {
                     Some(old_ln),
                     line_comments,
                     LineSide::Old,
+                    comment_width,
                 );
             }
 
@@ -2731,6 +4809,7 @@ impl App {
                     Some(new_ln),
                     line_comments,
                     LineSide::New,
+                    comment_width,
                 );
             }
         }
@@ -2743,6 +4822,8 @@ impl App {
         hunk_idx: usize,
         lines: &[crate::model::DiffLine],
         line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
+        diagnostics: (&Diagnostics, &Path),
+        comment_width: usize,
     ) {
         let mut i = 0;
         while i < lines.len() {
@@ -2758,6 +4839,13 @@ impl App {
                         old_lineno: diff_line.old_lineno,
                         new_lineno: diff_line.new_lineno,
                     });
+                    Self::push_diagnostics(
+                        annotations,
+                        file_idx,
+                        diff_line.new_lineno,
+                        line_comments,
+                        diagnostics,
+                    );
 
                     Self::push_comments(
                         annotations,
@@ -2765,6 +4853,7 @@ impl App {
                         diff_line.new_lineno,
                         line_comments,
                         LineSide::New,
+                        comment_width,
                     );
 
                     i += 1
@@ -2812,6 +4901,13 @@ impl App {
                             old_lineno,
                             new_lineno,
                         });
+                        Self::push_diagnostics(
+                            annotations,
+                            file_idx,
+                            new_lineno,
+                            line_comments,
+                            diagnostics,
+                        );
 
                         Self::push_comments(
                             annotations,
@@ -2819,6 +4915,7 @@ impl App {
                             old_lineno,
                             line_comments,
                             LineSide::Old,
+                            comment_width,
                         );
                         Self::push_comments(
                             annotations,
@@ -2826,6 +4923,7 @@ impl App {
                             new_lineno,
                             line_comments,
                             LineSide::New,
+                            comment_width,
                         );
                     }
 
@@ -2840,6 +4938,13 @@ impl App {
                         old_lineno: None,
                         new_lineno: diff_line.new_lineno,
                     });
+                    Self::push_diagnostics(
+                        annotations,
+                        file_idx,
+                        diff_line.new_lineno,
+                        line_comments,
+                        diagnostics,
+                    );
 
                     Self::push_comments(
                         annotations,
@@ -2847,6 +4952,7 @@ impl App {
                         diff_line.new_lineno,
                         line_comments,
                         LineSide::New,
+                        comment_width,
                     );
 
                     i += 1;
@@ -2913,8 +5019,16 @@ impl App {
         let mut items = Vec::new();
         let mut seen_dirs: HashSet<String> = HashSet::new();
 
+        let view = &self.session.file_list;
         for (file_idx, file) in self.diff_files.iter().enumerate() {
             let path = file.display_path();
+            if !view.filter.matches(file, self.session.files.get(path)) {
+                continue;
+            }
+            if view.flat {
+                items.push(FileTreeItem::File { file_idx, depth: 0 });
+                continue;
+            }
 
             let mut ancestors: Vec<String> = Vec::new();
             let mut current = path.parent();
@@ -3297,3 +5411,886 @@ mod scroll_tests {
         assert_eq!(diff_state_wrap.viewport_height, 20);
     }
 }
+
+#[cfg(test)]
+mod test_support {
+    use super::*;
+    use crate::syntax::SyntaxHighlighter;
+    use crate::vcs::VcsType;
+
+    /// Backend for an `App` built from a fixed diff, serving `files` as the
+    /// content of both sides
+    pub(super) struct StubVcs {
+        info: VcsInfo,
+        files: HashMap<PathBuf, String>,
+    }
+
+    impl VcsBackend for StubVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
+        }
+
+        fn set_diff_options(&mut self, _options: DiffOptions) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
+        }
+
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
+        }
+
+        fn file_content(&self, file_path: &Path, _version: FileVersion<'_>) -> Result<String> {
+            self.files
+                .get(file_path)
+                .cloned()
+                .ok_or_else(|| TuicrError::VcsCommand(format!("no {}", file_path.display())))
+        }
+    }
+
+    pub(super) fn diff_line(
+        origin: LineOrigin,
+        old: Option<u32>,
+        new: Option<u32>,
+        content: &str,
+    ) -> DiffLine {
+        DiffLine {
+            origin,
+            content: content.to_string(),
+            old_lineno: old,
+            new_lineno: new,
+            highlighted_spans: None,
+        }
+    }
+
+    /// App reviewing `diff_files` of the working tree of /tmp/test-repo,
+    /// whose files have the content in `files`
+    pub(super) fn build_test_app(
+        config: &AppConfig,
+        diff_files: Vec<DiffFile>,
+        files: &[(&str, &str)],
+    ) -> App {
+        let info = VcsInfo {
+            root_path: PathBuf::from("/tmp/test-repo"),
+            head_commit: "abc1234def".to_string(),
+            branch_name: Some("main".to_string()),
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            info.root_path.clone(),
+            info.head_commit.clone(),
+            info.branch_name.clone(),
+            SessionDiffSource::WorkingTree,
+        );
+        let files = files
+            .iter()
+            .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+            .collect();
+        App::build(
+            Box::new(StubVcs {
+                info: info.clone(),
+                files,
+            }),
+            info,
+            Theme::default(),
+            false,
+            config,
+            diff_files,
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod snippet_tests {
+    use super::test_support::{build_test_app, diff_line};
+    use super::*;
+
+    /// App reviewing `src/lib.rs`, whose second and third lines were replaced
+    fn create_test_app() -> App {
+        let file = DiffFile {
+            old_path: Some(PathBuf::from("src/lib.rs")),
+            new_path: Some(PathBuf::from("src/lib.rs")),
+            status: FileStatus::Modified,
+            hunks: vec![DiffHunk {
+                header: "@@ -1,4 +1,4 @@".to_string(),
+                lines: vec![
+                    diff_line(LineOrigin::Context, Some(1), Some(1), "fn run() {"),
+                    diff_line(LineOrigin::Deletion, Some(2), None, "    old();"),
+                    diff_line(LineOrigin::Deletion, Some(3), None, "    older();"),
+                    diff_line(LineOrigin::Addition, None, Some(2), "    new();"),
+                    diff_line(LineOrigin::Addition, None, Some(3), "    newer();"),
+                    diff_line(LineOrigin::Context, Some(4), Some(4), "}"),
+                ],
+                old_start: 1,
+                old_count: 4,
+                new_start: 1,
+                new_count: 4,
+            }],
+            is_binary: false,
+        };
+        let config = AppConfig {
+            snippets: vec![Snippet {
+                name: "quote".to_string(),
+                text: "[{file}:{lines}]".to_string(),
+            }],
+            ..AppConfig::default()
+        };
+        build_test_app(&config, vec![file], &[])
+    }
+
+    /// Put the cursor on a diff line
+    fn move_to(app: &mut App, line: u32, side: LineSide) {
+        app.diff_state.cursor_line = app
+            .line_annotations
+            .iter()
+            .position(|annotation| {
+                let AnnotatedLine::DiffLine {
+                    old_lineno,
+                    new_lineno,
+                    ..
+                } = annotation
+                else {
+                    return false;
+                };
+                match side {
+                    LineSide::Old => new_lineno.is_none() && *old_lineno == Some(line),
+                    LineSide::New => *new_lineno == Some(line),
+                }
+            })
+            .unwrap();
+    }
+
+    fn select(app: &mut App, side: LineSide, start: u32, end: u32) {
+        move_to(app, start, side);
+        app.enter_visual_mode(start, side);
+        move_to(app, end, side);
+        app.enter_comment_from_visual();
+    }
+
+    #[test]
+    fn should_take_snippet_context_from_old_side_selection() {
+        // given
+        let mut app = create_test_app();
+
+        // when
+        select(&mut app, LineSide::Old, 2, 3);
+
+        // then
+        assert_eq!(
+            app.snippet_context(),
+            SnippetContext {
+                file: "src/lib.rs".to_string(),
+                lines: Some(LineRange::new(2, 3)),
+                code: "    old();\n    older();".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_take_snippet_context_from_new_side_selection() {
+        // given
+        let mut app = create_test_app();
+
+        // when
+        select(&mut app, LineSide::New, 3, 4);
+
+        // then
+        assert_eq!(
+            app.snippet_context(),
+            SnippetContext {
+                file: "src/lib.rs".to_string(),
+                lines: Some(LineRange::new(3, 4)),
+                code: "    newer();\n}".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_take_only_the_file_for_file_comments() {
+        // given
+        let mut app = create_test_app();
+
+        // when
+        app.enter_comment_mode(true, None);
+
+        // then
+        assert_eq!(
+            app.snippet_context(),
+            SnippetContext {
+                file: "src/lib.rs".to_string(),
+                lines: None,
+                code: String::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_insert_snippet_at_a_cursor_after_multibyte_text() {
+        // given
+        let mut app = create_test_app();
+        move_to(&mut app, 2, LineSide::New);
+        app.enter_comment_mode(false, Some((2, LineSide::New)));
+        app.comment_buffer = "héllo wörld".to_string();
+        app.comment_cursor = "héllo ".len();
+
+        // when
+        app.open_snippet_picker();
+        app.insert_selected_snippet();
+
+        // then
+        assert_eq!(app.comment_buffer, "héllo [src/lib.rs:2]wörld");
+        assert_eq!(app.comment_cursor, "héllo [src/lib.rs:2]".len());
+        assert_eq!(app.input_mode, InputMode::Comment);
+    }
+}
+
+#[cfg(test)]
+mod full_file_tests {
+    use super::test_support::{build_test_app, diff_line};
+    use super::*;
+
+    const LIB: &str = "fn run() {\n    new();\n    newer();\n}\n";
+
+    /// App reviewing `src/lib.rs`, whose second line was replaced, with a
+    /// comment on each side of the change
+    fn create_test_app() -> App {
+        let file = DiffFile {
+            old_path: Some(PathBuf::from("src/lib.rs")),
+            new_path: Some(PathBuf::from("src/lib.rs")),
+            status: FileStatus::Modified,
+            hunks: vec![DiffHunk {
+                header: "@@ -1,3 +1,3 @@".to_string(),
+                lines: vec![
+                    diff_line(LineOrigin::Context, Some(1), Some(1), "fn run() {"),
+                    diff_line(LineOrigin::Deletion, Some(2), None, "    old();"),
+                    diff_line(LineOrigin::Addition, None, Some(2), "    new();"),
+                    diff_line(LineOrigin::Context, Some(3), Some(3), "    newer();"),
+                ],
+                old_start: 1,
+                old_count: 3,
+                new_start: 1,
+                new_count: 3,
+            }],
+            is_binary: false,
+        };
+        let mut app = build_test_app(&AppConfig::default(), vec![file], &[("src/lib.rs", LIB)]);
+        let review = app
+            .session
+            .get_file_mut(&PathBuf::from("src/lib.rs"))
+            .unwrap();
+        review.add_line_comment(
+            2,
+            Comment::new(
+                "Why remove this?".to_string(),
+                CommentType::new("question"),
+                Some(LineSide::Old),
+            ),
+        );
+        review.add_line_comment(
+            3,
+            Comment::new(
+                "Name this better".to_string(),
+                CommentType::new("suggestion"),
+                Some(LineSide::New),
+            ),
+        );
+        app
+    }
+
+    #[test]
+    fn should_export_the_same_with_the_full_file_shown() {
+        // given
+        let mut app = create_test_app();
+        let formats = [
+            ExportFormat::Markdown,
+            ExportFormat::GitLab,
+            ExportFormat::Patch,
+        ];
+        let diff_view: Vec<String> = formats
+            .iter()
+            .map(|format| app.export(*format).unwrap().content)
+            .collect();
+
+        // when
+        app.cycle_full_file_view();
+
+        // then
+        assert!(app.full_file_views.contains_key(&0));
+        assert_eq!(app.diff_files[0].hunks[0].lines.len(), 4);
+        let full_view: Vec<String> = formats
+            .iter()
+            .map(|format| app.export(*format).unwrap().content)
+            .collect();
+        assert_eq!(full_view, diff_view);
+        assert!(full_view[2].contains("removed line 2: Why remove this?"));
+    }
+
+    #[test]
+    fn should_show_the_whole_file_without_changing_the_diff() {
+        // given
+        let mut app = create_test_app();
+
+        // when
+        app.cycle_full_file_view();
+
+        // then
+        let shown = app.shown_hunks(0);
+        assert_eq!(shown.len(), 1);
+        assert_eq!(
+            shown[0]
+                .lines
+                .iter()
+                .filter(|line| line.new_lineno == Some(4))
+                .count(),
+            1
+        );
+        assert_eq!(app.diff_files[0].hunks[0].header, "@@ -1,3 +1,3 @@");
+    }
+}
+
+#[cfg(test)]
+mod sort_tests {
+    use super::test_support::{build_test_app, diff_line};
+    use super::*;
+
+    fn added_file(path: &str, lines: u32) -> DiffFile {
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Added,
+            hunks: vec![DiffHunk {
+                header: format!("@@ -0,0 +1,{lines} @@"),
+                lines: (1..=lines)
+                    .map(|n| diff_line(LineOrigin::Addition, None, Some(n), "x"))
+                    .collect(),
+                old_start: 0,
+                old_count: 0,
+                new_start: 1,
+                new_count: lines,
+            }],
+            is_binary: false,
+        }
+    }
+
+    #[test]
+    fn should_keep_folds_and_full_file_views_when_resorting() {
+        // given
+        let mut app = build_test_app(
+            &AppConfig::default(),
+            vec![added_file("a.rs", 1), added_file("b.rs", 3)],
+            &[("a.rs", "x\n")],
+        );
+        app.set_fold(1, Some(0), true);
+        app.diff_state.current_file_idx = 0;
+        app.cycle_full_file_view();
+
+        // when
+        app.set_file_sort("churn");
+
+        // then
+        let paths: Vec<&Path> = app
+            .diff_files
+            .iter()
+            .map(|file| file.display_path().as_path())
+            .collect();
+        assert_eq!(paths, vec![Path::new("b.rs"), Path::new("a.rs")]);
+        assert!(app.is_hunk_folded(Path::new("b.rs"), 0));
+        assert_eq!(
+            app.full_file_views.keys().copied().collect::<Vec<_>>(),
+            vec![1]
+        );
+    }
+}
+
+#[cfg(test)]
+mod comment_list_tests {
+    use super::test_support::{build_test_app, diff_line};
+    use super::*;
+
+    fn added_file(path: &str, lines: u32) -> DiffFile {
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Added,
+            hunks: vec![DiffHunk {
+                header: format!("@@ -0,0 +1,{lines} @@"),
+                lines: (1..=lines)
+                    .map(|n| diff_line(LineOrigin::Addition, None, Some(n), "x"))
+                    .collect(),
+                old_start: 0,
+                old_count: 0,
+                new_start: 1,
+                new_count: lines,
+            }],
+            is_binary: false,
+        }
+    }
+
+    fn add_comment(app: &mut App, path: &str, line: u32, content: &str, comment_type: &str) {
+        app.session
+            .get_file_mut(&PathBuf::from(path))
+            .unwrap()
+            .add_line_comment(
+                line,
+                Comment::new(
+                    content.to_string(),
+                    CommentType::new(comment_type),
+                    Some(LineSide::New),
+                ),
+            );
+    }
+
+    /// App reviewing two added files, with an issue on a.rs:2 and a note
+    /// on b.rs:3
+    fn create_test_app() -> App {
+        let mut app = build_test_app(
+            &AppConfig::default(),
+            vec![added_file("a.rs", 3), added_file("b.rs", 3)],
+            &[],
+        );
+        add_comment(&mut app, "a.rs", 2, "Fix this", "issue");
+        add_comment(&mut app, "b.rs", 3, "Keep in mind", "note");
+        app.rebuild_annotations();
+        app
+    }
+
+    fn comment_under_cursor(app: &App) -> Option<String> {
+        app.comment_at_line(app.diff_state.cursor_line)
+            .map(|comment| comment.content.clone())
+    }
+
+    #[test]
+    fn should_wrap_around_when_jumping_to_the_next_comment() {
+        // given
+        let mut app = create_test_app();
+        app.jump_to_comment(true, None);
+        app.jump_to_comment(true, None);
+        assert_eq!(comment_under_cursor(&app).as_deref(), Some("Keep in mind"));
+
+        // when
+        app.jump_to_comment(true, None);
+
+        // then
+        assert_eq!(comment_under_cursor(&app).as_deref(), Some("Fix this"));
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Wrapped around to the top")
+        );
+    }
+
+    #[test]
+    fn should_wrap_around_when_jumping_to_the_previous_comment() {
+        // given
+        let mut app = create_test_app();
+
+        // when
+        app.jump_to_comment(false, None);
+
+        // then
+        assert_eq!(comment_under_cursor(&app).as_deref(), Some("Keep in mind"));
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Wrapped around to the bottom")
+        );
+    }
+
+    #[test]
+    fn should_only_jump_to_comments_of_the_given_type() {
+        // given
+        let mut app = create_test_app();
+
+        // when
+        app.jump_to_comment_named(true, " note");
+
+        // then
+        assert_eq!(comment_under_cursor(&app).as_deref(), Some("Keep in mind"));
+    }
+
+    #[test]
+    fn should_stay_put_when_there_are_no_comments() {
+        // given
+        let mut app = build_test_app(&AppConfig::default(), vec![added_file("a.rs", 3)], &[]);
+        let cursor = app.diff_state.cursor_line;
+
+        // when
+        app.jump_to_comment(true, None);
+
+        // then
+        assert_eq!(app.diff_state.cursor_line, cursor);
+        assert!(app.comment_entries().is_empty());
+        assert!(!app.jump_to_selected_comment());
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("No comments")
+        );
+    }
+
+    #[test]
+    fn should_list_comments_in_diff_order() {
+        // given
+        let app = create_test_app();
+
+        // when
+        let entries = app.comment_entries();
+
+        // then
+        let listed: Vec<(&Path, Option<(u32, LineSide)>)> = entries
+            .iter()
+            .map(|entry| (entry.path.as_path(), entry.line))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (Path::new("a.rs"), Some((2, LineSide::New))),
+                (Path::new("b.rs"), Some((3, LineSide::New))),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_jump_to_the_comment_selected_in_the_list() {
+        // given
+        let mut app = create_test_app();
+        app.set_fold(1, None, true);
+        app.comments_panel_down(1);
+
+        // when
+        let jumped = app.jump_to_selected_comment();
+
+        // then
+        assert!(jumped);
+        assert_eq!(comment_under_cursor(&app).as_deref(), Some("Keep in mind"));
+    }
+
+    #[test]
+    fn should_delete_the_comment_selected_in_the_list() {
+        // given
+        let mut app = create_test_app();
+        app.comments_panel_down(1);
+
+        // when
+        app.delete_selected_comment();
+
+        // then
+        let entries = app.comment_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].comment.content, "Fix this");
+        assert_eq!(app.comments_panel_cursor, 0);
+    }
+}
+
+#[cfg(test)]
+mod merge_tests {
+    use super::test_support::{build_test_app, diff_line};
+    use super::*;
+
+    fn added_file(path: &str) -> DiffFile {
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Added,
+            hunks: vec![DiffHunk {
+                header: "@@ -0,0 +1,2 @@".to_string(),
+                lines: vec![
+                    diff_line(LineOrigin::Addition, None, Some(1), "x"),
+                    diff_line(LineOrigin::Addition, None, Some(2), "y"),
+                ],
+                old_start: 0,
+                old_count: 0,
+                new_start: 1,
+                new_count: 2,
+            }],
+            is_binary: false,
+        }
+    }
+
+    fn create_test_app() -> App {
+        build_test_app(
+            &AppConfig::default(),
+            vec![added_file("a.rs"), added_file("b.rs")],
+            &[],
+        )
+    }
+
+    /// Session of the same review as `app`'s with a comment on a.rs:2,
+    /// b.rs reviewed and notes, saved in `dir`
+    fn save_other_session(app: &App, dir: &Path) -> String {
+        let mut other = app.session.clone();
+        other.id = uuid::Uuid::new_v4().to_string();
+        let a = other.get_file_mut(&PathBuf::from("a.rs")).unwrap();
+        a.add_line_comment(
+            2,
+            Comment::new(
+                "Rename y".to_string(),
+                CommentType::new("suggestion"),
+                Some(LineSide::New),
+            ),
+        );
+        other.get_file_mut(&PathBuf::from("b.rs")).unwrap().reviewed = true;
+        other.session_notes = Some("Check the tests".to_string());
+        let path = dir.join("other.json");
+        std::fs::write(&path, serde_json::to_string(&other).unwrap()).unwrap();
+        path.display().to_string()
+    }
+
+    fn message(app: &App) -> &str {
+        app.message.as_ref().map_or("", |m| m.content.as_str())
+    }
+
+    #[test]
+    fn should_merge_the_comments_of_a_session_file() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = create_test_app();
+        let path = save_other_session(&app, dir.path());
+
+        // when
+        app.merge_session_file(&format!(" {path}"));
+
+        // then
+        assert_eq!(app.session.comment_count(), 1);
+        assert!(app.session.is_file_reviewed(&PathBuf::from("b.rs")));
+        assert_eq!(
+            app.session.session_notes.as_deref(),
+            Some("Check the tests")
+        );
+        assert_eq!(
+            message(&app),
+            format!("Merged 1 comment; 1 file reviewed in only one session from {path}")
+        );
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn should_undo_a_merge_including_the_notes() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = create_test_app();
+        let path = save_other_session(&app, dir.path());
+        app.merge_session_file(&path);
+
+        // when
+        app.undo();
+
+        // then
+        assert_eq!(app.session.comment_count(), 0);
+        assert!(!app.session.is_file_reviewed(&PathBuf::from("b.rs")));
+        assert_eq!(app.session.session_notes, None);
+
+        // when
+        app.redo();
+
+        // then
+        assert_eq!(app.session.comment_count(), 1);
+        assert_eq!(
+            app.session.session_notes.as_deref(),
+            Some("Check the tests")
+        );
+    }
+
+    #[test]
+    fn should_keep_files_reviewed_in_one_session_unreviewed_with_both() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = create_test_app();
+        let path = save_other_session(&app, dir.path());
+
+        // when
+        app.merge_session_file(&format!("both {path}"));
+
+        // then
+        assert_eq!(app.session.comment_count(), 1);
+        assert!(!app.session.is_file_reviewed(&PathBuf::from("b.rs")));
+    }
+
+    #[test]
+    fn should_count_the_conflicts_of_a_merge() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = create_test_app();
+        let path = save_other_session(&app, dir.path());
+        let mut other = load_session(&PathBuf::from(&path)).unwrap();
+        other.repo_path = PathBuf::from("/tmp/other-repo");
+        other.base_commit = "def5678".to_string();
+        std::fs::write(&path, serde_json::to_string(&other).unwrap()).unwrap();
+
+        // when
+        app.merge_session_file(&path);
+
+        // then
+        let message = app.message.as_ref().unwrap();
+        assert_eq!(message.message_type, MessageType::Warning);
+        assert!(
+            message
+                .content
+                .contains("Sessions belong to different repositories")
+        );
+        assert!(message.content.ends_with("(+1 more conflict)"));
+    }
+
+    #[test]
+    fn should_report_a_session_file_that_cannot_be_loaded() {
+        // given
+        let mut app = create_test_app();
+
+        // when
+        app.merge_session_file("/nonexistent/session.json");
+
+        // then
+        assert!(message(&app).starts_with("Can't merge /nonexistent/session.json"));
+        assert_eq!(app.session.comment_count(), 0);
+        assert!(app.session.undo().is_none());
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::test_support::{build_test_app, diff_line};
+    use super::*;
+
+    const FORMATS: [ExportFormat; 5] = [
+        ExportFormat::Markdown,
+        ExportFormat::Sarif,
+        ExportFormat::GitHub,
+        ExportFormat::GitLab,
+        ExportFormat::Patch,
+    ];
+
+    /// App reviewing `src/lib.rs`, whose second of three lines was deleted
+    fn create_test_app() -> App {
+        let file = DiffFile {
+            old_path: Some(PathBuf::from("src/lib.rs")),
+            new_path: Some(PathBuf::from("src/lib.rs")),
+            status: FileStatus::Modified,
+            hunks: vec![DiffHunk {
+                header: "@@ -1,3 +1,2 @@".to_string(),
+                lines: vec![
+                    diff_line(LineOrigin::Context, Some(1), Some(1), "a();"),
+                    diff_line(LineOrigin::Deletion, Some(2), None, "b();"),
+                    diff_line(LineOrigin::Context, Some(3), Some(2), "c();"),
+                ],
+                old_start: 1,
+                old_count: 3,
+                new_start: 1,
+                new_count: 2,
+            }],
+            is_binary: false,
+        };
+        build_test_app(
+            &AppConfig::default(),
+            vec![file],
+            &[("src/lib.rs", "a();\nc();\n")],
+        )
+    }
+
+    fn add_old_side_comment(app: &mut App, line: u32, content: &str) {
+        app.session
+            .get_file_mut(&PathBuf::from("src/lib.rs"))
+            .unwrap()
+            .add_line_comment(
+                line,
+                Comment::new(
+                    content.to_string(),
+                    CommentType::new("note"),
+                    Some(LineSide::Old),
+                ),
+            );
+    }
+
+    #[test]
+    fn should_fail_export_when_no_comments() {
+        // given
+        let app = create_test_app();
+
+        for format in FORMATS {
+            // when
+            let result = app.export(format);
+
+            // then
+            assert!(matches!(result, Err(TuicrError::NoComments)), "{format}");
+        }
+    }
+
+    #[test]
+    fn should_report_comments_the_patch_cannot_place() {
+        // given
+        let mut app = create_test_app();
+        add_old_side_comment(&mut app, 2, "Why remove this?");
+        add_old_side_comment(&mut app, 40, "Outside the diff");
+
+        // when
+        let export = app.export(ExportFormat::Patch).unwrap();
+
+        // then
+        assert!(
+            export
+                .content
+                .contains("+// REVIEW[tuicr](NOTE) on removed line 2: Why remove this?")
+        );
+        assert!(!export.content.contains("Outside the diff"));
+        assert_eq!(
+            export.warning.as_deref(),
+            Some("1 comment on removed lines outside the diff left out")
+        );
+    }
+
+    #[test]
+    fn should_place_the_github_review_on_the_newest_selected_commit() {
+        // given
+        let mut app = create_test_app();
+        add_old_side_comment(&mut app, 2, "Why remove this?");
+        let ids = ["aaa111", "bbb222", "ccc333"];
+        app.diff_source = DiffSource::CommitRange(ids.iter().map(|id| id.to_string()).collect());
+        // Newest first, as the commit selector lists them
+        app.review_commits = ids
+            .iter()
+            .rev()
+            .map(|id| CommitInfo {
+                id: id.to_string(),
+                short_id: id.to_string(),
+                branch_name: None,
+                summary: String::new(),
+                message: String::new(),
+                author: String::new(),
+                time: Utc::now(),
+            })
+            .collect();
+        app.commit_selection_range = Some((1, 2));
+
+        // when
+        let export = app.export(ExportFormat::GitHub).unwrap();
+
+        // then
+        let review: serde_json::Value = serde_json::from_str(&export.content).unwrap();
+        assert_eq!(review["commit_id"], "bbb222");
+    }
+
+    #[test]
+    fn should_export_other_formats_without_a_warning() {
+        // given
+        let mut app = create_test_app();
+        add_old_side_comment(&mut app, 2, "Why remove this?");
+
+        for format in FORMATS {
+            // when
+            let export = app.export(format).unwrap();
+
+            // then
+            assert!(export.content.contains("Why remove this?"), "{format}");
+            assert_eq!(export.warning, None, "{format}");
+        }
+    }
+}
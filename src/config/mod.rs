@@ -1,14 +1,205 @@
 use std::fs;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Result, anyhow};
-use serde::Deserialize;
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+
+use crate::model::snippet::Snippet;
+use crate::model::{CommentTypeDef, CommentTypes, DEFAULT_GENERATED_PATTERNS};
+use crate::persistence::Retention;
+use crate::vcs::{DiffOptions, WhitespaceMode};
+
+const DEFAULT_AUTOSAVE_SECS: u64 = 30;
 
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
 #[serde(default, deny_unknown_fields)]
 pub struct AppConfig {
     pub theme: Option<String>,
+    /// Globs excluded from every review, before `.tuicrignore` and `--exclude`
+    pub exclude: Vec<String>,
+    /// Fold files once they are marked reviewed (default true)
+    pub fold_reviewed: Option<bool>,
+    /// Name recorded as the author of your comments (default: the VCS user)
+    pub reviewer: Option<String>,
+    pub diff: DiffConfig,
+    pub generated: GeneratedConfig,
+    /// `[[comment_types]]` entries; replace the built-in types when set
+    pub comment_types: Vec<CommentTypeConfig>,
+    /// `[[snippets]]` entries offered by the comment snippet picker
+    pub snippets: Vec<Snippet>,
+    pub sessions: SessionsConfig,
+}
+
+impl AppConfig {
+    pub fn comment_types(&self) -> CommentTypes {
+        let defs = self
+            .comment_types
+            .iter()
+            .enumerate()
+            .map(|(idx, config)| CommentTypeDef {
+                color: config.color,
+                priority: config.priority.unwrap_or(idx as u32),
+                blocking: config.blocking,
+                ..CommentTypeDef::new(&config.name, &config.description, 0)
+            })
+            .collect();
+        CommentTypes::new(defs)
+    }
+}
+
+/// One `[[comment_types]]` entry
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CommentTypeConfig {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Color name or `#rrggbb`; built-in names default to the theme color
+    #[serde(default, deserialize_with = "deserialize_color")]
+    pub color: Option<Color>,
+    /// Export order, lowest first (default: position in the list)
+    pub priority: Option<u32>,
+    #[serde(default)]
+    pub blocking: bool,
+}
+
+fn deserialize_color<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Color>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("invalid color: {value}")))
+}
+
+/// `[diff]` table: defaults for how diffs are generated
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiffConfig {
+    pub ignore_whitespace: Option<WhitespaceMode>,
+    pub context_lines: Option<u32>,
+    pub detect_renames: Option<bool>,
+    pub rename_similarity: Option<u16>,
+    pub ignore_blank_lines: Option<bool>,
+}
+
+impl DiffConfig {
+    pub fn to_diff_options(&self) -> DiffOptions {
+        let defaults = DiffOptions::default();
+        DiffOptions {
+            whitespace: self.ignore_whitespace.unwrap_or(defaults.whitespace),
+            context_lines: self.context_lines.unwrap_or(defaults.context_lines),
+            detect_renames: self.detect_renames.unwrap_or(defaults.detect_renames),
+            rename_similarity: self
+                .rename_similarity
+                .map_or(defaults.rename_similarity, |similarity| similarity.min(100)),
+            ignore_blank_lines: self
+                .ignore_blank_lines
+                .unwrap_or(defaults.ignore_blank_lines),
+        }
+    }
+}
+
+/// `[generated]` table: which files are collapsed as generated or vendored
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneratedConfig {
+    /// Replaces the built-in patterns when set
+    pub patterns: Option<Vec<String>>,
+    /// Mark generated files as reviewed when they first appear in a session
+    pub mark_reviewed: bool,
+}
+
+impl GeneratedConfig {
+    pub fn patterns(&self) -> Vec<String> {
+        match &self.patterns {
+            Some(patterns) => patterns.clone(),
+            None => DEFAULT_GENERATED_PATTERNS
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// `[sessions]` table: where review sessions are stored, how long they are
+/// kept and how often unsaved changes are autosaved
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionsConfig {
+    pub storage: SessionStorage,
+    /// Sessions directory; a leading `~` is the home directory
+    pub dir: Option<PathBuf>,
+    /// Days since the last save before a session is deleted; `Some(None)`
+    /// for `"never"`
+    #[serde(deserialize_with = "deserialize_max_age_days")]
+    pub max_age_days: Option<Option<u64>>,
+    pub max_per_repo: Option<usize>,
+    /// Seconds between autosaves of unsaved changes; 0 disables them
+    pub autosave_secs: Option<u64>,
+    /// Delete sessions outside the retention settings on startup rather
+    /// than only with `tuicr sessions gc`
+    pub auto_gc: bool,
+}
+
+/// Where review sessions are saved
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStorage {
+    /// The user's sessions directory
+    #[default]
+    User,
+    /// `.tuicr/reviews/` inside the repository, to be shared with it
+    Repo,
+}
+
+impl SessionsConfig {
+    pub fn dir(&self) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        let home = std::env::var_os("HOME").filter(|home| !home.is_empty());
+        match (dir.strip_prefix("~"), home) {
+            (Ok(rest), Some(home)) => Some(PathBuf::from(home).join(rest)),
+            _ => Some(dir.clone()),
+        }
+    }
+
+    pub fn retention(&self) -> Retention {
+        let defaults = Retention::default();
+        Retention {
+            max_age_days: self.max_age_days.unwrap_or(defaults.max_age_days),
+            max_per_repo: self.max_per_repo.or(defaults.max_per_repo),
+        }
+    }
+
+    pub fn autosave_interval(&self) -> Option<Duration> {
+        match self.autosave_secs.unwrap_or(DEFAULT_AUTOSAVE_SECS) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+}
+
+fn deserialize_max_age_days<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Option<u64>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MaxAge {
+        Days(u64),
+        Keyword(String),
+    }
+
+    match MaxAge::deserialize(deserializer)? {
+        MaxAge::Days(days) if days > 0 => Ok(Some(Some(days))),
+        MaxAge::Keyword(keyword) if keyword == "never" => Ok(Some(None)),
+        _ => Err(serde::de::Error::custom(
+            "max_age_days must be a positive number of days or \"never\"",
+        )),
+    }
 }
 
 pub fn config_path() -> Result<PathBuf> {
@@ -109,6 +300,192 @@ mod tests {
         assert_eq!(config, AppConfig::default());
     }
 
+    #[test]
+    fn should_load_diff_options_from_diff_table() {
+        let dir = tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "[diff]\nignore_whitespace = \"at-eol\"\ncontext_lines = 8\ndetect_renames = true\n",
+        )
+        .expect("failed to write config");
+
+        let config = load_config_from_path(&path)
+            .expect("valid config should parse")
+            .expect("config should exist");
+        let options = config.diff.to_diff_options();
+        assert_eq!(options.whitespace, WhitespaceMode::AtEol);
+        assert_eq!(options.context_lines, 8);
+        assert!(options.detect_renames);
+        assert_eq!(options.rename_similarity, 50);
+        assert!(!options.ignore_blank_lines);
+    }
+
+    #[test]
+    fn should_load_default_excludes() {
+        let dir = tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "exclude = [\"*.lock\", \"vendor/\"]\n").expect("failed to write config");
+
+        let config = load_config_from_path(&path)
+            .expect("valid config should parse")
+            .expect("config should exist");
+        assert_eq!(config.exclude, vec!["*.lock", "vendor/"]);
+    }
+
+    #[test]
+    fn should_load_fold_reviewed_setting() {
+        let dir = tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "fold_reviewed = false\n").expect("failed to write config");
+
+        let config = load_config_from_path(&path)
+            .expect("valid config should parse")
+            .expect("config should exist");
+        assert_eq!(config.fold_reviewed, Some(false));
+    }
+
+    #[test]
+    fn should_use_default_generated_patterns_unless_configured() {
+        let dir = tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[generated]\nmark_reviewed = true\n").expect("failed to write config");
+        let config = load_config_from_path(&path)
+            .expect("valid config should parse")
+            .expect("config should exist");
+        assert!(config.generated.mark_reviewed);
+        assert!(config.generated.patterns().contains(&"vendor/".to_string()));
+
+        fs::write(&path, "[generated]\npatterns = [\"gen/\"]\n").expect("failed to write config");
+        let config = load_config_from_path(&path)
+            .expect("valid config should parse")
+            .expect("config should exist");
+        assert!(!config.generated.mark_reviewed);
+        assert_eq!(config.generated.patterns(), vec!["gen/"]);
+    }
+
+    #[test]
+    fn should_load_custom_comment_types() {
+        let dir = tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            r##"
+[[comment_types]]
+name = "Question"
+
+[[comment_types]]
+name = "Security"
+description = "vulnerabilities"
+color = "#ff0000"
+priority = 0
+blocking = true
+"##,
+        )
+        .expect("failed to write config");
+        let config = load_config_from_path(&path)
+            .expect("valid config should parse")
+            .expect("config should exist");
+
+        let types = config.comment_types();
+        let question = types.default_type();
+        assert_eq!(types.resolve(&question).label(), "QUESTION");
+        assert_eq!(types.resolve(&question).priority, 0);
+        let security = types.next(&question);
+        assert_eq!(types.resolve(&security).color, Some(Color::Rgb(255, 0, 0)));
+        assert!(types.resolve(&security).blocking);
+        assert_eq!(types.next(&security), question);
+        assert_eq!(
+            AppConfig::default().comment_types(),
+            CommentTypes::default()
+        );
+    }
+
+    #[test]
+    fn should_load_session_storage_settings() {
+        let dir = tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "[sessions]\nstorage = \"repo\"\ndir = \"/sync/reviews\"\nmax_age_days = \"never\"\nmax_per_repo = 20\nautosave_secs = 0\nauto_gc = true\n",
+        )
+        .expect("failed to write config");
+        let config = load_config_from_path(&path)
+            .expect("valid config should parse")
+            .expect("config should exist");
+        assert_eq!(config.sessions.storage, SessionStorage::Repo);
+        assert_eq!(config.sessions.dir(), Some(PathBuf::from("/sync/reviews")));
+        assert_eq!(
+            config.sessions.retention(),
+            Retention {
+                max_age_days: None,
+                max_per_repo: Some(20),
+            }
+        );
+        assert_eq!(config.sessions.autosave_interval(), None);
+        assert!(config.sessions.auto_gc);
+        assert!(!AppConfig::default().sessions.auto_gc);
+        assert_eq!(
+            AppConfig::default().sessions.retention(),
+            Retention::default()
+        );
+        assert_eq!(
+            AppConfig::default().sessions.autosave_interval(),
+            Some(Duration::from_secs(30))
+        );
+
+        fs::write(&path, "[sessions]\nmax_age_days = 0\n").expect("failed to write config");
+        let err = load_config_from_path(&path).expect_err("zero days should fail");
+        assert!(err.to_string().contains("max_age_days"));
+    }
+
+    #[test]
+    fn should_load_snippets() {
+        let dir = tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "[[snippets]]\nname = \"Test\"\ntext = \"Please add a test for {file}:{lines}\"\n",
+        )
+        .expect("failed to write config");
+        let config = load_config_from_path(&path)
+            .expect("valid config should parse")
+            .expect("config should exist");
+        assert_eq!(config.snippets.len(), 1);
+        assert_eq!(config.snippets[0].name, "Test");
+        assert_eq!(
+            config.snippets[0].text,
+            "Please add a test for {file}:{lines}"
+        );
+    }
+
+    #[test]
+    fn should_error_on_invalid_comment_type_color() {
+        let dir = tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "[[comment_types]]\nname = \"Nit\"\ncolor = \"blurple\"\n",
+        )
+        .expect("failed to write config");
+
+        let result = load_config_from_path(&path);
+        assert!(result.is_err(), "unknown color should return error");
+    }
+
+    #[test]
+    fn should_error_on_invalid_whitespace_mode() {
+        let dir = tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[diff]\nignore_whitespace = \"some\"\n").expect("failed to write config");
+
+        let result = load_config_from_path(&path);
+        assert!(
+            result.is_err(),
+            "unknown whitespace mode should return error"
+        );
+    }
+
     #[test]
     fn should_error_on_invalid_toml() {
         let dir = tempdir().expect("failed to create temp dir");
@@ -0,0 +1,74 @@
+//! `cargo --message-format=json` output, and rustc's
+//! `--error-format=json` messages it wraps.
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use super::{Diagnostic, Severity};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Message {
+    /// A cargo message; only `compiler-message`s carry a diagnostic
+    Cargo {
+        reason: String,
+        message: Option<CompilerMessage>,
+    },
+    Rustc(CompilerMessage),
+}
+
+#[derive(Deserialize)]
+struct CompilerMessage {
+    message: String,
+    level: String,
+    code: Option<Code>,
+    spans: Vec<Span>,
+}
+
+#[derive(Deserialize)]
+struct Code {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct Span {
+    file_name: String,
+    line_start: u32,
+    column_start: u32,
+    is_primary: bool,
+}
+
+/// Diagnostics in `text`, one JSON message per line. Other lines are
+/// skipped, so output mixed with cargo's progress still loads.
+pub(super) fn parse(text: &str) -> Vec<Diagnostic> {
+    text.lines()
+        .filter_map(|line| serde_json::from_str::<Message>(line).ok())
+        .filter_map(|message| match message {
+            Message::Cargo { reason, message } if reason == "compiler-message" => message,
+            Message::Cargo { .. } => None,
+            Message::Rustc(message) => Some(message),
+        })
+        .filter_map(diagnostic)
+        .collect()
+}
+
+/// The diagnostic at the primary span; summaries like `2 warnings emitted`
+/// have no span and are skipped
+fn diagnostic(message: CompilerMessage) -> Option<Diagnostic> {
+    let span = message.spans.into_iter().find(|span| span.is_primary)?;
+    let code = message.code.map(|code| code.code);
+    let tool = match &code {
+        Some(code) if code.starts_with("clippy::") => "clippy",
+        _ => "rustc",
+    };
+    Some(Diagnostic {
+        path: PathBuf::from(span.file_name),
+        line: span.line_start,
+        column: Some(span.column_start),
+        severity: Severity::parse(&message.level)?,
+        message: message.message,
+        code,
+        tool: Some(tool.to_string()),
+    })
+}
@@ -0,0 +1,318 @@
+//! Lint and compiler diagnostics shown on the diff.
+//!
+//! Diagnostics are read from cargo's JSON messages, SARIF logs or plain
+//! `file:line[:column]: message` lines and shown under the new-side line
+//! they point at.
+
+mod cargo;
+mod sarif;
+mod text;
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, TuicrError};
+use crate::model::Comment;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    /// Parse a level as written by compilers and linters; `help` and `info`
+    /// are notes
+    fn parse(level: &str) -> Option<Self> {
+        match level.trim().to_lowercase().as_str() {
+            "error" | "fatal" => Some(Self::Error),
+            level if level.starts_with("error:") => Some(Self::Error),
+            "warning" | "warn" => Some(Self::Warning),
+            "note" | "help" | "info" | "none" => Some(Self::Note),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Path relative to the repository root
+    pub path: PathBuf,
+    pub line: u32,
+    pub column: Option<u32>,
+    pub severity: Severity,
+    pub message: String,
+    /// Lint or error code, e.g. `clippy::needless_return`
+    pub code: Option<String>,
+    /// Tool that reported it, e.g. `clippy` or `eslint`
+    pub tool: Option<String>,
+}
+
+impl Diagnostic {
+    /// Body of the comment the diagnostic is promoted to
+    pub fn comment_text(&self) -> String {
+        match &self.code {
+            Some(code) => format!("{} (`{code}`)", self.message),
+            None => self.message.clone(),
+        }
+    }
+}
+
+/// Loaded diagnostics, by file and line
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    by_file: HashMap<PathBuf, BTreeMap<u32, Vec<Diagnostic>>>,
+}
+
+impl Diagnostics {
+    pub fn len(&self) -> usize {
+        self.by_file
+            .values()
+            .flat_map(BTreeMap::values)
+            .map(Vec::len)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_file.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.by_file.clear();
+    }
+
+    /// Add `diagnostics`, skipping ones already loaded (cargo reports a lint
+    /// once per target that compiles the file). Returns how many were added.
+    pub fn extend(&mut self, diagnostics: impl IntoIterator<Item = Diagnostic>) -> usize {
+        let mut added = 0;
+        for diagnostic in diagnostics {
+            let line = self
+                .by_file
+                .entry(diagnostic.path.clone())
+                .or_default()
+                .entry(diagnostic.line)
+                .or_default();
+            if !line.contains(&diagnostic) {
+                line.push(diagnostic);
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Diagnostics reported on `line` of the new version of `path`
+    pub fn on_line(&self, path: &Path, line: u32) -> &[Diagnostic] {
+        self.by_file
+            .get(path)
+            .and_then(|lines| lines.get(&line))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Diagnostics on `line` of `path`, with their index on the line, except
+    /// those already promoted to one of the line's `comments`
+    pub fn shown_on_line<'a>(
+        &'a self,
+        path: &Path,
+        line: u32,
+        comments: &'a [Comment],
+    ) -> impl Iterator<Item = (usize, &'a Diagnostic)> {
+        self.on_line(path, line)
+            .iter()
+            .enumerate()
+            .filter(|(_, diagnostic)| {
+                let text = diagnostic.comment_text();
+                !comments.iter().any(|comment| comment.content == text)
+            })
+    }
+}
+
+/// Read the diagnostics in the file at `path`, detecting its format. Paths
+/// in it are made relative to the repository at `root`.
+pub fn load(path: &Path, root: &Path) -> Result<Vec<Diagnostic>> {
+    let text = std::fs::read_to_string(path)?;
+    parse(&text, root)
+}
+
+fn parse(text: &str, root: &Path) -> Result<Vec<Diagnostic>> {
+    let trimmed = text.trim_start();
+    let diagnostics = if trimmed.starts_with('{') {
+        // A SARIF log is one JSON document, cargo writes one message per line
+        match serde_json::from_str::<serde_json::Value>(trimmed) {
+            Ok(value) if value.get("runs").is_some() => sarif::parse(value)
+                .map_err(|e| TuicrError::InvalidDiagnostics(format!("SARIF: {e}")))?,
+            _ => cargo::parse(text),
+        }
+    } else {
+        text::parse(text)
+    };
+
+    Ok(diagnostics
+        .into_iter()
+        .filter(|diagnostic| diagnostic.line > 0)
+        .map(|diagnostic| Diagnostic {
+            path: relative_path(&diagnostic.path, root),
+            ..diagnostic
+        })
+        .collect())
+}
+
+/// `path` relative to `root`; tools report absolute paths, `file://` URIs
+/// (already resolved and decoded for SARIF) or paths relative to where they ran
+fn relative_path(path: &Path, root: &Path) -> PathBuf {
+    let path_str = path.to_string_lossy();
+    let path = Path::new(path_str.strip_prefix("file://").unwrap_or(&path_str));
+    let path = path.strip_prefix(root).unwrap_or(path);
+    path.strip_prefix("./").unwrap_or(path).to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARGO: &str = r#"{"reason":"compiler-artifact","package_id":"tuicr","target":{"name":"tuicr"}}
+{"reason":"compiler-message","package_id":"tuicr","message":{"message":"unneeded `return` statement","code":{"code":"clippy::needless_return","explanation":null},"level":"warning","spans":[{"file_name":"src/app.rs","line_start":42,"line_end":42,"column_start":9,"column_end":21,"is_primary":true}],"children":[],"rendered":"warning: unneeded `return` statement"}}
+{"reason":"compiler-message","package_id":"tuicr","message":{"message":"2 warnings emitted","code":null,"level":"warning","spans":[],"children":[],"rendered":"warning: 2 warnings emitted"}}
+{"reason":"build-finished","success":true}
+"#;
+
+    const SARIF: &str = r#"{
+  "version": "2.1.0",
+  "runs": [{
+    "tool": { "driver": { "name": "ESLint" } },
+    "results": [{
+      "ruleId": "no-unused-vars",
+      "level": "error",
+      "message": { "text": "'x' is assigned a value but never used." },
+      "locations": [{ "physicalLocation": {
+        "artifactLocation": { "uri": "file:///work/repo/web/app.js" },
+        "region": { "startLine": 3, "startColumn": 7 }
+      } }]
+    }]
+  }]
+}"#;
+
+    #[test]
+    fn should_read_cargo_json_messages() {
+        // when
+        let diagnostics = parse(CARGO, Path::new("/work/repo")).unwrap();
+
+        // then
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic {
+                path: PathBuf::from("src/app.rs"),
+                line: 42,
+                column: Some(9),
+                severity: Severity::Warning,
+                message: "unneeded `return` statement".to_string(),
+                code: Some("clippy::needless_return".to_string()),
+                tool: Some("clippy".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_read_sarif_results_relative_to_the_repository() {
+        // when
+        let diagnostics = parse(SARIF, Path::new("/work/repo")).unwrap();
+
+        // then
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.path, PathBuf::from("web/app.js"));
+        assert_eq!((diagnostic.line, diagnostic.column), (3, Some(7)));
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.code.as_deref(), Some("no-unused-vars"));
+        assert_eq!(diagnostic.tool.as_deref(), Some("ESLint"));
+    }
+
+    #[test]
+    fn should_resolve_sarif_uris_against_their_base_and_decode_them() {
+        // given
+        let sarif = r#"{
+  "version": "2.1.0",
+  "runs": [{
+    "tool": { "driver": { "name": "semgrep" } },
+    "originalUriBaseIds": { "SRCROOT": { "uri": "file:///work/my%20repo/" } },
+    "results": [{
+      "message": { "text": "Avoid eval" },
+      "locations": [{ "physicalLocation": {
+        "artifactLocation": { "uri": "web/my%20app.js", "uriBaseId": "SRCROOT" },
+        "region": { "startLine": 8 }
+      } }]
+    }]
+  }]
+}"#;
+
+        // when
+        let diagnostics = parse(sarif, Path::new("/work/my repo")).unwrap();
+
+        // then
+        assert_eq!(diagnostics[0].path, PathBuf::from("web/my app.js"));
+        assert_eq!(diagnostics[0].line, 8);
+    }
+
+    #[test]
+    fn should_read_file_line_column_lines() {
+        // given
+        let text = "./src/lib.rs:10:5: warning: unused variable: `x`\n\
+                    src/lib.rs:12: missing docs\n\
+                    not a diagnostic\n";
+
+        // when
+        let diagnostics = parse(text, Path::new("/work/repo")).unwrap();
+
+        // then
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].path, PathBuf::from("src/lib.rs"));
+        assert_eq!(diagnostics[0].column, Some(5));
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].message, "unused variable: `x`");
+        assert_eq!((diagnostics[1].line, diagnostics[1].column), (12, None));
+        assert_eq!(diagnostics[1].message, "missing docs");
+    }
+
+    #[test]
+    fn should_skip_diagnostics_already_loaded() {
+        // given
+        let mut diagnostics = Diagnostics::default();
+        let loaded = parse(CARGO, Path::new("/work/repo")).unwrap();
+
+        // when
+        let first = diagnostics.extend(loaded.clone());
+        let second = diagnostics.extend(loaded);
+
+        // then
+        assert_eq!((first, second), (1, 0));
+        assert_eq!(diagnostics.on_line(Path::new("src/app.rs"), 42).len(), 1);
+        assert!(diagnostics.on_line(Path::new("src/app.rs"), 41).is_empty());
+    }
+
+    #[test]
+    fn should_hide_diagnostics_promoted_to_a_comment() {
+        // given
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.extend(parse(CARGO, Path::new("/work/repo")).unwrap());
+        let path = Path::new("src/app.rs");
+        let promoted = Comment::new(
+            diagnostics.on_line(path, 42)[0].comment_text(),
+            crate::model::CommentType::new("issue"),
+            None,
+        );
+
+        // when
+        let shown = diagnostics.shown_on_line(path, 42, std::slice::from_ref(&promoted));
+
+        // then
+        assert_eq!(shown.count(), 0);
+        assert_eq!(diagnostics.shown_on_line(path, 42, &[]).count(), 1);
+    }
+}
@@ -0,0 +1,177 @@
+//! SARIF 2.1 logs, as written by eslint, semgrep, CodeQL and others.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{Diagnostic, Severity};
+
+#[derive(Deserialize)]
+struct Log {
+    runs: Vec<Run>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    #[serde(default)]
+    results: Vec<SarifResult>,
+    /// Base URIs that relative artifact URIs name through `uriBaseId`
+    #[serde(default)]
+    original_uri_base_ids: HashMap<String, ArtifactLocation>,
+}
+
+#[derive(Deserialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Deserialize)]
+struct Driver {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: Option<String>,
+    level: Option<String>,
+    message: Message,
+    #[serde(default)]
+    locations: Vec<Location>,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: Option<PhysicalLocation>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Option<Region>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: Option<String>,
+    uri_base_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: Option<u32>,
+    start_column: Option<u32>,
+}
+
+/// Results of every run in the log that point at a file line
+pub(super) fn parse(log: Value) -> serde_json::Result<Vec<Diagnostic>> {
+    let log: Log = serde_json::from_value(log)?;
+    Ok(log
+        .runs
+        .into_iter()
+        .flat_map(|run| {
+            let tool = run.tool.driver.name;
+            let bases = run.original_uri_base_ids;
+            run.results
+                .into_iter()
+                .filter_map(move |result| diagnostic(result, &tool, &bases))
+        })
+        .collect())
+}
+
+fn diagnostic(
+    result: SarifResult,
+    tool: &str,
+    bases: &HashMap<String, ArtifactLocation>,
+) -> Option<Diagnostic> {
+    let location = result
+        .locations
+        .into_iter()
+        .find_map(|location| location.physical_location)?;
+    let region = location.region?;
+    // SARIF's default level is warning
+    let severity = match result.level {
+        Some(level) => Severity::parse(&level)?,
+        None => Severity::Warning,
+    };
+    Some(Diagnostic {
+        path: PathBuf::from(percent_decode(&resolve(
+            &location.artifact_location,
+            bases,
+        )?)),
+        line: region.start_line?,
+        column: region.start_column,
+        severity,
+        message: result.message.text.or(result.rule_id.clone())?,
+        code: result.rule_id,
+        tool: Some(tool.to_string()),
+    })
+}
+
+/// The URI of `location`, joined onto its `uriBaseId` when the run defines
+/// that base. Bases may themselves be relative to another base; an unknown
+/// base leaves the URI relative, which for most tools means the directory
+/// they ran in.
+fn resolve(
+    location: &ArtifactLocation,
+    bases: &HashMap<String, ArtifactLocation>,
+) -> Option<String> {
+    let mut uri = location.uri.clone()?;
+    let mut base_id = location.uri_base_id.as_deref();
+    // A bound on the chain, in case a log defines bases in a cycle
+    for _ in 0..bases.len() {
+        if uri.starts_with('/') || uri.contains("://") {
+            break;
+        }
+        let Some(base) = base_id.and_then(|id| bases.get(id)) else {
+            break;
+        };
+        let Some(base_uri) = base.uri.as_deref() else {
+            break;
+        };
+        uri = if base_uri.ends_with('/') {
+            format!("{base_uri}{uri}")
+        } else {
+            format!("{base_uri}/{uri}")
+        };
+        base_id = base.uri_base_id.as_deref();
+    }
+    Some(uri)
+}
+
+/// `uri` with its `%XX` escapes decoded, e.g. `my%20file.rs` to `my file.rs`
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,46 @@
+//! `path:line[:column]: [severity:] message` lines, as printed by gcc,
+//! eslint's `unix` formatter, mypy, shellcheck's `gcc` format and others.
+
+use std::path::PathBuf;
+
+use super::{Diagnostic, Severity};
+
+pub(super) fn parse(text: &str) -> Vec<Diagnostic> {
+    text.lines().filter_map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Option<Diagnostic> {
+    let (path, rest) = line.split_once(':')?;
+    let (line_no, rest) = rest.split_once(':')?;
+    let line_no = line_no.trim().parse().ok()?;
+    let (column, rest) = match rest.split_once(':') {
+        Some((column, message)) => match column.trim().parse() {
+            Ok(column) => (Some(column), message),
+            Err(_) => (None, rest),
+        },
+        None => (None, rest),
+    };
+
+    let message = rest.trim();
+    let (severity, message) = match message.split_once(':') {
+        Some((level, text)) => match Severity::parse(level) {
+            Some(severity) => (severity, text.trim()),
+            None => (Severity::Warning, message),
+        },
+        None => (Severity::Warning, message),
+    };
+
+    let path = path.trim();
+    if path.is_empty() || message.is_empty() {
+        return None;
+    }
+    Some(Diagnostic {
+        path: PathBuf::from(path),
+        line: line_no,
+        column,
+        severity,
+        message: message.to_string(),
+        code: None,
+        tool: None,
+    })
+}
@@ -23,12 +23,23 @@ pub enum TuicrError {
     #[error("Review session corrupted: {0}")]
     CorruptedSession(String),
 
+    #[error(
+        "Session format {version} is newer than supported ({supported}); upgrade tuicr to open it"
+    )]
+    UnsupportedSessionVersion { version: String, supported: String },
+
     #[error("Clipboard error: {0}")]
     Clipboard(String),
 
     #[error("VCS command failed: {0}")]
     VcsCommand(String),
 
+    #[error("Session is open in another tuicr instance ({0})")]
+    SessionLocked(String),
+
+    #[error("Invalid diagnostics: {0}")]
+    InvalidDiagnostics(String),
+
     #[error("Unsupported operation: {0}")]
     UnsupportedOperation(String),
 }
@@ -1,27 +1,92 @@
 use crate::app::{self, App, FileTreeItem, FocusedPanel};
+use crate::error::TuicrError;
 use crate::input::Action;
-use crate::output::{export_to_clipboard, generate_export_content};
-use crate::persistence::save_session;
+use crate::output::{ExportFormat, copy_to_clipboard};
 use crate::text_edit::{
     delete_char_before, delete_word_before, next_char_boundary, prev_char_boundary,
 };
 
-/// Export review: either to clipboard or set pending stdout output based on app.output_to_stdout.
-/// When output_to_stdout is true, stores the content and sets should_quit.
-fn handle_export(app: &mut App) {
+/// Export the review in `format`: to the clipboard, or as pending stdout
+/// output when app.output_to_stdout. Returns whether anything was exported.
+fn export_review(app: &mut App, format: ExportFormat) -> bool {
+    let export = match app.export(format) {
+        Ok(export) => export,
+        Err(e) => {
+            app.set_warning(format!("{e}"));
+            return false;
+        }
+    };
     if app.output_to_stdout {
-        match generate_export_content(&app.session, &app.diff_source) {
-            Ok(content) => {
-                app.pending_stdout_output = Some(content);
-                app.should_quit = true;
-            }
-            Err(e) => app.set_warning(format!("{e}")),
+        app.pending_stdout_output = Some(export.content);
+        return true;
+    }
+    match copy_to_clipboard(&export.content) {
+        Ok(msg) => {
+            set_export_message(app, msg, export.warning);
+            true
+        }
+        Err(e) => {
+            app.set_warning(format!("{e}"));
+            false
         }
+    }
+}
+
+/// Export review: either to clipboard or set pending stdout output based on app.output_to_stdout.
+/// When output_to_stdout is true, stores the content and sets should_quit.
+fn handle_export(app: &mut App, format: ExportFormat) {
+    if export_review(app, format) && app.output_to_stdout {
+        app.should_quit = true;
+    }
+}
+
+/// `:export [format] [file]`: export in `format` (the configured one by
+/// default), to `file` when given
+fn handle_export_command(app: &mut App, args: &str) {
+    let args = args.trim();
+    let (format, file) = match args.split_once(char::is_whitespace) {
+        Some((format, file)) => (format, file.trim()),
+        None => (args, ""),
+    };
+    let format = if format.is_empty() {
+        app.export_format
     } else {
-        match export_to_clipboard(&app.session, &app.diff_source) {
-            Ok(msg) => app.set_message(msg),
-            Err(e) => app.set_warning(format!("{e}")),
+        match format.parse() {
+            Ok(format) => format,
+            Err(e) => {
+                app.set_error(e);
+                return;
+            }
         }
+    };
+    if file.is_empty() {
+        handle_export(app, format);
+        return;
+    }
+
+    let result = app.export(format).and_then(|export| {
+        std::fs::write(file, export.content)?;
+        Ok(export.warning)
+    });
+    match result {
+        Ok(warning) => set_export_message(app, format!("Exported {format} to {file}"), warning),
+        Err(TuicrError::NoComments) => app.set_warning("No comments to export"),
+        Err(e) => app.set_error(format!("Export failed: {e}")),
+    }
+}
+
+/// Report a finished export, with what it left out as a warning
+fn set_export_message(app: &mut App, message: String, warning: Option<String>) {
+    match warning {
+        Some(warning) => app.set_warning(format!("{message}; {warning}")),
+        None => app.set_message(message),
+    }
+}
+
+fn set_save_error(app: &mut App, e: TuicrError) {
+    match e {
+        TuicrError::SessionLocked(_) => app.set_error(format!("{e} (add ! to override)")),
+        e => app.set_error(format!("Save failed: {e}")),
     }
 }
 
@@ -127,6 +192,63 @@ pub fn handle_help_action(app: &mut App, action: Action) {
     }
 }
 
+pub fn handle_stats_action(app: &mut App, action: Action) {
+    if action == Action::ExitMode {
+        app.toggle_stats();
+    }
+}
+
+/// Handle actions in the snippet picker (typing filters by name)
+pub fn handle_snippet_picker_action(app: &mut App, action: Action) {
+    match action {
+        Action::InsertChar(c) => {
+            let mut filter = app.snippet_filter.clone();
+            filter.push(c);
+            app.set_snippet_filter(filter);
+        }
+        Action::DeleteChar => {
+            let mut filter = app.snippet_filter.clone();
+            filter.pop();
+            app.set_snippet_filter(filter);
+        }
+        Action::CursorDown(_) => app.snippet_picker_down(),
+        Action::CursorUp(_) => app.snippet_picker_up(),
+        Action::SubmitInput => app.insert_selected_snippet(),
+        Action::ExitMode => app.close_snippet_picker(),
+        _ => {}
+    }
+}
+
+/// Handle actions in the session browser
+pub fn handle_sessions_action(app: &mut App, action: Action) {
+    match action {
+        Action::CursorDown(_) => app.sessions_down(),
+        Action::CursorUp(_) => app.sessions_up(),
+        Action::SubmitInput => app.open_selected_session(),
+        Action::RenameSession => app.start_session_rename(),
+        Action::TogglePinSession => app.toggle_selected_session_pin(),
+        Action::DeleteSession => app.delete_selected_session(),
+        Action::ExitMode => app.close_sessions_browser(),
+        Action::Quit => app.should_quit = true,
+        _ => {}
+    }
+}
+
+/// Handle actions while renaming a session in the session browser
+pub fn handle_session_rename_action(app: &mut App, action: Action) {
+    match action {
+        Action::InsertChar(c) => app.session_rename_buffer.push(c),
+        Action::DeleteChar => {
+            app.session_rename_buffer.pop();
+        }
+        Action::ClearLine => app.session_rename_buffer.clear(),
+        Action::SubmitInput => app.finish_session_rename(),
+        Action::ExitMode => app.cancel_session_rename(),
+        Action::Quit => app.should_quit = true,
+        _ => {}
+    }
+}
+
 /// Handle actions in Command mode (text input for :commands)
 pub fn handle_command_action(app: &mut App, action: Action) {
     match action {
@@ -145,21 +267,17 @@ pub fn handle_command_action(app: &mut App, action: Action) {
                         app.should_quit = true;
                     }
                 }
-                "q!" | "quit!" => app.should_quit = true,
-                "w" | "write" => match save_session(&app.session) {
-                    Ok(path) => {
-                        app.dirty = false;
-                        app.set_message(format!("Saved to {}", path.display()));
-                    }
-                    Err(e) => app.set_error(format!("Save failed: {e}")),
+                "q!" | "quit!" => app.quit_discarding_changes(),
+                "w" | "write" | "w!" | "write!" => match app.save_session(cmd.ends_with('!')) {
+                    Ok(path) => app.set_message(format!("Saved to {}", path.display())),
+                    Err(e) => set_save_error(app, e),
                 },
-                "x" | "wq" => match save_session(&app.session) {
+                "x" | "wq" => match app.save_session(false) {
                     Ok(_) => {
-                        app.dirty = false;
                         if app.session.has_comments() {
                             if app.output_to_stdout {
                                 // Skip confirmation dialog, export directly
-                                handle_export(app);
+                                handle_export(app, app.export_format);
                                 return;
                             }
                             app.exit_command_mode();
@@ -169,13 +287,16 @@ pub fn handle_command_action(app: &mut App, action: Action) {
                             app.should_quit = true;
                         }
                     }
-                    Err(e) => app.set_error(format!("Save failed: {e}")),
+                    Err(e) => set_save_error(app, e),
                 },
                 "e" | "reload" => match app.reload_diff_files() {
                     Ok(count) => app.set_message(format!("Reloaded {count} files")),
                     Err(e) => app.set_error(format!("Reload failed: {e}")),
                 },
-                "clip" | "export" => handle_export(app),
+                "clip" => handle_export(app, app.export_format),
+                cmd if cmd == "export" || cmd.starts_with("export ") => {
+                    handle_export_command(app, &cmd["export".len()..]);
+                }
                 "clear" => app.clear_all_comments(),
                 "version" => {
                     app.set_message(format!("tuicr v{}", env!("CARGO_PKG_VERSION")));
@@ -227,7 +348,57 @@ pub fn handle_command_action(app: &mut App, action: Action) {
                     };
                     app.set_message(format!("Commit selector: {status}"));
                 }
+                "set blame" => app.set_blame(true),
+                "set noblame" => app.set_blame(false),
+                "set blame!" => app.toggle_blame(),
+                "set foldreviewed" => app.set_fold_reviewed(true),
+                "set nofoldreviewed" => app.set_fold_reviewed(false),
+                "set foldreviewed!" => app.toggle_fold_reviewed(),
+                "set flat" => app.set_flat_file_list(true),
+                "set noflat" => app.set_flat_file_list(false),
+                "set flat!" => app.toggle_flat_file_list(),
+                cmd if cmd == "filter" || cmd.starts_with("filter ") => {
+                    app.set_file_filter(&cmd["filter".len()..]);
+                }
+                cmd if cmd.starts_with("sort ") => app.set_file_sort(&cmd["sort ".len()..]),
+                cmd if cmd
+                    .strip_prefix("set ")
+                    .is_some_and(crate::vcs::DiffOptions::is_setting) =>
+                {
+                    app.set_diff_option(&cmd["set ".len()..]);
+                }
                 "diff" => app.toggle_diff_view_mode(),
+                "comments" => app.toggle_comments_panel(),
+                cmd if matches!(
+                    cmd.split_once(char::is_whitespace)
+                        .map_or(cmd, |(name, _)| name),
+                    "cnext" | "cprev"
+                ) =>
+                {
+                    let (name, type_name) = cmd.split_at("cnext".len());
+                    app.jump_to_comment_named(name == "cnext", type_name);
+                }
+                "stats" => {
+                    app.exit_command_mode();
+                    app.toggle_stats();
+                    return;
+                }
+                "sessions" => {
+                    app.exit_command_mode();
+                    app.open_sessions_browser();
+                    return;
+                }
+                cmd if cmd == "diagnostics" || cmd.starts_with("diagnostics ") => {
+                    app.load_diagnostics(&cmd["diagnostics".len()..]);
+                }
+                "annotate" => app.annotate_working_tree(),
+                "unannotate" => app.strip_review_markers(),
+                cmd if cmd == "merge" || cmd.starts_with("merge ") => {
+                    app.merge_session_file(&cmd["merge".len()..]);
+                }
+                cmd if cmd == "name" || cmd.starts_with("name ") => {
+                    app.set_session_name(&cmd["name".len()..]);
+                }
                 "commits" => {
                     if let Err(e) = app.enter_commit_select_mode() {
                         app.set_error(format!("Failed to load commits: {e}"));
@@ -251,26 +422,24 @@ pub fn handle_search_action(app: &mut App, action: Action) {
         Action::DeleteChar => {
             app.search_buffer.pop();
         }
-        Action::DeleteWord => {
-            if !app.search_buffer.is_empty() {
-                while app
-                    .search_buffer
-                    .chars()
-                    .last()
-                    .map(|c| c.is_whitespace())
-                    .unwrap_or(false)
-                {
-                    app.search_buffer.pop();
-                }
-                while app
-                    .search_buffer
-                    .chars()
-                    .last()
-                    .map(|c| !c.is_whitespace())
-                    .unwrap_or(false)
-                {
-                    app.search_buffer.pop();
-                }
+        Action::DeleteWord if !app.search_buffer.is_empty() => {
+            while app
+                .search_buffer
+                .chars()
+                .last()
+                .map(|c| c.is_whitespace())
+                .unwrap_or(false)
+            {
+                app.search_buffer.pop();
+            }
+            while app
+                .search_buffer
+                .chars()
+                .last()
+                .map(|c| !c.is_whitespace())
+                .unwrap_or(false)
+            {
+                app.search_buffer.pop();
             }
         }
         Action::ClearLine => {
@@ -299,6 +468,7 @@ pub fn handle_comment_action(app: &mut App, action: Action) {
         Action::ExitMode => app.exit_comment_mode(),
         Action::SubmitInput => app.save_comment(),
         Action::CycleCommentType => app.cycle_comment_type(),
+        Action::OpenSnippetPicker => app.open_snippet_picker(),
         Action::TextCursorLeft => {
             app.comment_cursor = prev_char_boundary(&app.comment_buffer, app.comment_cursor);
         }
@@ -334,17 +504,7 @@ pub fn handle_confirm_action(app: &mut App, action: Action) {
     match action {
         Action::ConfirmYes => {
             if let Some(app::ConfirmAction::CopyAndQuit) = app.pending_confirm {
-                if app.output_to_stdout {
-                    match generate_export_content(&app.session, &app.diff_source) {
-                        Ok(content) => app.pending_stdout_output = Some(content),
-                        Err(e) => app.set_warning(format!("{e}")),
-                    }
-                } else {
-                    match export_to_clipboard(&app.session, &app.diff_source) {
-                        Ok(msg) => app.set_message(msg),
-                        Err(e) => app.set_warning(format!("{e}")),
-                    }
-                }
+                export_review(app, app.export_format);
             }
             app.exit_confirm_mode();
             app.should_quit = true;
@@ -478,6 +638,28 @@ pub fn handle_file_list_action(app: &mut App, action: Action) {
     }
 }
 
+/// Handle actions when the comments panel is focused
+pub fn handle_comments_panel_action(app: &mut App, action: Action) {
+    match action {
+        Action::CursorDown(n) => app.comments_panel_down(n),
+        Action::CursorUp(n) => app.comments_panel_up(n),
+        Action::GoToTop => app.comments_panel_cursor = 0,
+        Action::GoToBottom => app.comments_panel_down(usize::MAX),
+        // Space previews the comment in the diff, Enter also moves focus there
+        Action::ToggleExpand => {
+            app.jump_to_selected_comment();
+        }
+        Action::SelectFile => {
+            if app.jump_to_selected_comment() {
+                app.focused_panel = FocusedPanel::Diff;
+            }
+        }
+        Action::EditComment => app.edit_selected_comment(),
+        Action::ExitMode => app.focused_panel = FocusedPanel::Diff,
+        _ => handle_shared_normal_action(app, action),
+    }
+}
+
 /// Handle actions when diff panel is focused
 pub fn handle_diff_action(app: &mut App, action: Action) {
     match action {
@@ -497,6 +679,8 @@ pub fn handle_diff_action(app: &mut App, action: Action) {
                         app.set_error(format!("Failed to expand: {e}"));
                     }
                 }
+            } else if let Some(file_idx) = app.collapsed_file_at_cursor() {
+                app.toggle_generated_file(file_idx);
             }
         }
         _ => handle_shared_normal_action(app, action),
@@ -535,11 +719,18 @@ fn handle_shared_normal_action(app: &mut App, action: Action) {
         Action::ToggleReviewed => app.toggle_reviewed(),
         Action::ToggleFocus => {
             let has_selector = app.has_inline_commit_selector();
-            app.focused_panel = match (app.focused_panel, has_selector) {
-                (FocusedPanel::FileList, _) => FocusedPanel::Diff,
-                (FocusedPanel::Diff, true) => FocusedPanel::CommitSelector,
-                (FocusedPanel::Diff, false) => FocusedPanel::FileList,
-                (FocusedPanel::CommitSelector, _) => FocusedPanel::FileList,
+            let has_comments = app.show_comments_panel;
+            app.focused_panel = match (app.focused_panel, has_selector, has_comments) {
+                (FocusedPanel::FileList, _, _) => FocusedPanel::Diff,
+                (FocusedPanel::Diff, true, _) => FocusedPanel::CommitSelector,
+                (FocusedPanel::Diff | FocusedPanel::CommitSelector, _, true) => {
+                    FocusedPanel::Comments
+                }
+                (
+                    FocusedPanel::Diff | FocusedPanel::CommitSelector | FocusedPanel::Comments,
+                    _,
+                    _,
+                ) => FocusedPanel::FileList,
             };
         }
         Action::ExpandAll => {
@@ -550,6 +741,10 @@ fn handle_shared_normal_action(app: &mut App, action: Action) {
             app.collapse_all_dirs();
             app.set_message("All directories collapsed");
         }
+        Action::ToggleFileContext => app.toggle_file_context(),
+        Action::CycleFullFileView => app.cycle_full_file_view(),
+        Action::ToggleGeneratedFile => app.toggle_generated_file(app.diff_state.current_file_idx),
+        Action::PromoteDiagnostic => app.promote_diagnostic(),
         Action::ToggleHelp => app.toggle_help(),
         Action::EnterCommandMode => app.enter_command_mode(),
         Action::EnterSearchMode => app.enter_search_mode(),
@@ -563,11 +758,16 @@ fn handle_shared_normal_action(app: &mut App, action: Action) {
         }
         Action::AddFileComment => app.enter_comment_mode(true, None),
         Action::EditComment => {
-            if !app.enter_edit_mode() {
+            let editing = app.enter_edit_mode();
+            if !editing {
                 app.set_message("No comment at cursor");
             }
         }
-        Action::ExportToClipboard => handle_export(app),
+        Action::ExportToClipboard => handle_export(app, app.export_format),
+        Action::Undo => app.undo(),
+        Action::Redo => app.redo(),
+        Action::NextComment => app.jump_to_comment(true, None),
+        Action::PrevComment => app.jump_to_comment(false, None),
         Action::SearchNext => {
             app.search_next_in_diff();
         }
@@ -581,20 +781,16 @@ fn handle_shared_normal_action(app: &mut App, action: Action) {
                 app.set_message("Move cursor to a diff line to start visual selection");
             }
         }
-        Action::CycleCommitNext => {
-            if app.has_inline_commit_selector() {
-                app.cycle_commit_next();
-                if let Err(e) = app.reload_inline_selection() {
-                    app.set_error(format!("Failed to load diff: {e}"));
-                }
+        Action::CycleCommitNext if app.has_inline_commit_selector() => {
+            app.cycle_commit_next();
+            if let Err(e) = app.reload_inline_selection() {
+                app.set_error(format!("Failed to load diff: {e}"));
             }
         }
-        Action::CycleCommitPrev => {
-            if app.has_inline_commit_selector() {
-                app.cycle_commit_prev();
-                if let Err(e) = app.reload_inline_selection() {
-                    app.set_error(format!("Failed to load diff: {e}"));
-                }
+        Action::CycleCommitPrev if app.has_inline_commit_selector() => {
+            app.cycle_commit_prev();
+            if let Err(e) = app.reload_inline_selection() {
+                app.set_error(format!("Failed to load diff: {e}"));
             }
         }
         _ => {}
@@ -32,6 +32,12 @@ pub enum Action {
     AddFileComment,
     EditComment,
     PendingDCommand,
+    Undo,
+    Redo,
+    NextComment,
+    PrevComment,
+    /// Turn the diagnostic at the cursor into a comment (`P`)
+    PromoteDiagnostic,
     SearchNext,
     SearchPrev,
 
@@ -64,6 +70,13 @@ pub enum Action {
 
     // Comment type
     CycleCommentType,
+    /// Open the snippet picker while writing a comment (`Ctrl-t`)
+    OpenSnippetPicker,
+
+    // Session browser
+    RenameSession,
+    TogglePinSession,
+    DeleteSession,
 
     // Confirm dialog
     ConfirmYes,
@@ -82,6 +95,12 @@ pub enum Action {
     ToggleExpand,
     ExpandAll,
     CollapseAll,
+    /// Expand/collapse all hidden context in the current file (`E`)
+    ToggleFileContext,
+    /// Cycle the current file through diff / full new side / full old side (`F`)
+    CycleFullFileView,
+    /// Expand/collapse the current generated or vendored file (`X`)
+    ToggleGeneratedFile,
 
     // No-op
     None,
@@ -94,6 +113,10 @@ pub fn map_key_to_action(key: KeyEvent, mode: InputMode) -> Action {
         InputMode::Search => map_search_mode(key),
         InputMode::Comment => map_comment_mode(key),
         InputMode::Help => map_help_mode(key),
+        InputMode::Stats => map_stats_mode(key),
+        InputMode::SnippetPicker => map_snippet_picker_mode(key),
+        InputMode::Sessions => map_sessions_mode(key),
+        InputMode::SessionRename => map_command_mode(key),
         InputMode::Confirm => map_confirm_mode(key),
         InputMode::CommitSelect => map_commit_select_mode(key),
         InputMode::VisualSelect => map_visual_mode(key),
@@ -138,8 +161,13 @@ fn map_normal_mode(key: KeyEvent) -> Action {
         (KeyCode::Char('C'), _) => Action::AddFileComment,
         (KeyCode::Char('i'), KeyModifiers::NONE) => Action::EditComment,
         (KeyCode::Char('d'), KeyModifiers::NONE) => Action::PendingDCommand,
+        (KeyCode::Char('u'), KeyModifiers::NONE) => Action::Undo,
+        (KeyCode::Char('r'), KeyModifiers::CONTROL) => Action::Redo,
         (KeyCode::Char('v') | KeyCode::Char('V'), _) => Action::EnterVisualMode,
         (KeyCode::Char('y'), KeyModifiers::NONE) => Action::ExportToClipboard,
+        (KeyCode::Char('m'), KeyModifiers::NONE) => Action::NextComment,
+        (KeyCode::Char('M'), _) => Action::PrevComment,
+        (KeyCode::Char('P'), _) => Action::PromoteDiagnostic,
         (KeyCode::Char('n'), KeyModifiers::NONE) => Action::SearchNext,
         (KeyCode::Char('N'), _) => Action::SearchPrev,
 
@@ -155,6 +183,9 @@ fn map_normal_mode(key: KeyEvent) -> Action {
         (KeyCode::Char(' '), KeyModifiers::NONE) => Action::ToggleExpand,
         (KeyCode::Char('o'), KeyModifiers::NONE) => Action::ExpandAll,
         (KeyCode::Char('O'), _) => Action::CollapseAll,
+        (KeyCode::Char('E'), _) => Action::ToggleFileContext,
+        (KeyCode::Char('F'), _) => Action::CycleFullFileView,
+        (KeyCode::Char('X'), _) => Action::ToggleGeneratedFile,
 
         _ => Action::None,
     }
@@ -198,6 +229,7 @@ fn map_comment_mode(key: KeyEvent) -> Action {
         (KeyCode::Char('j'), KeyModifiers::CONTROL) => Action::InsertChar('\n'),
         // Comment type: Tab to cycle
         (KeyCode::Tab, KeyModifiers::NONE) => Action::CycleCommentType,
+        (KeyCode::Char('t'), KeyModifiers::CONTROL) => Action::OpenSnippetPicker,
         // Cursor movement
         (KeyCode::Char('a'), KeyModifiers::CONTROL) => Action::TextCursorLineStart,
         (KeyCode::Char('e'), KeyModifiers::CONTROL) => Action::TextCursorLineEnd,
@@ -239,6 +271,42 @@ fn map_comment_mode(key: KeyEvent) -> Action {
     }
 }
 
+fn map_stats_mode(key: KeyEvent) -> Action {
+    match (key.code, key.modifiers) {
+        (KeyCode::Esc, KeyModifiers::NONE) | (KeyCode::Char('q'), KeyModifiers::NONE) => {
+            Action::ExitMode
+        }
+        _ => Action::None,
+    }
+}
+
+fn map_snippet_picker_mode(key: KeyEvent) -> Action {
+    match (key.code, key.modifiers) {
+        (KeyCode::Esc, KeyModifiers::NONE) => Action::ExitMode,
+        (KeyCode::Enter, _) => Action::SubmitInput,
+        (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => Action::CursorDown(1),
+        (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => Action::CursorUp(1),
+        (KeyCode::Backspace, _) => Action::DeleteChar,
+        (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => Action::InsertChar(c),
+        _ => Action::None,
+    }
+}
+
+fn map_sessions_mode(key: KeyEvent) -> Action {
+    match (key.code, key.modifiers) {
+        (KeyCode::Esc, KeyModifiers::NONE) | (KeyCode::Char('q'), KeyModifiers::NONE) => {
+            Action::ExitMode
+        }
+        (KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => Action::CursorDown(1),
+        (KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => Action::CursorUp(1),
+        (KeyCode::Enter, _) => Action::SubmitInput,
+        (KeyCode::Char('r'), KeyModifiers::NONE) => Action::RenameSession,
+        (KeyCode::Char('p'), KeyModifiers::NONE) => Action::TogglePinSession,
+        (KeyCode::Char('d'), KeyModifiers::NONE) => Action::DeleteSession,
+        _ => Action::None,
+    }
+}
+
 fn map_help_mode(key: KeyEvent) -> Action {
     match (key.code, key.modifiers) {
         // Close help
@@ -1,11 +1,13 @@
 mod app;
 mod config;
+mod diagnostics;
 mod error;
 mod handler;
 mod input;
 mod model;
 mod output;
 mod persistence;
+mod sessions;
 mod syntax;
 mod text_edit;
 mod theme;
@@ -33,12 +35,14 @@ use ratatui::{Terminal, backend::CrosstermBackend};
 
 use app::{App, FocusedPanel, InputMode};
 use handler::{
-    handle_command_action, handle_comment_action, handle_commit_select_action,
-    handle_commit_selector_action, handle_confirm_action, handle_diff_action,
-    handle_file_list_action, handle_help_action, handle_search_action, handle_visual_action,
+    handle_command_action, handle_comment_action, handle_comments_panel_action,
+    handle_commit_select_action, handle_commit_selector_action, handle_confirm_action,
+    handle_diff_action, handle_file_list_action, handle_help_action, handle_search_action,
+    handle_session_rename_action, handle_sessions_action, handle_snippet_picker_action,
+    handle_stats_action, handle_visual_action,
 };
 use input::{Action, map_key_to_action};
-use theme::{parse_cli_args, resolve_theme_with_config};
+use theme::{SessionsCommand, parse_cli_args, resolve_theme_with_config};
 
 /// Timeout for the "press Ctrl+C again to exit" feature
 const CTRL_C_EXIT_TIMEOUT: Duration = Duration::from_secs(2);
@@ -58,7 +62,7 @@ fn main() -> anyhow::Result<()> {
 
     // Parse CLI arguments and resolve theme
     // This also configures syntax highlighting colors before diff parsing
-    let cli_args = parse_cli_args();
+    let mut cli_args = parse_cli_args();
     let mut startup_warnings = Vec::new();
     let config = match config::load_config() {
         Ok(config) => config,
@@ -67,6 +71,60 @@ fn main() -> anyhow::Result<()> {
             None
         }
     };
+
+    let sessions_config = config
+        .as_ref()
+        .map(|cfg| cfg.sessions.clone())
+        .unwrap_or_default();
+    if let Some(dir) = sessions_config.dir() {
+        persistence::set_reviews_dir(dir);
+    }
+    let repo_storage = sessions_config.storage == config::SessionStorage::Repo;
+    if repo_storage {
+        match vcs::detect_vcs() {
+            Ok(vcs) => persistence::use_repo_storage(vcs.info().root_path.clone()),
+            Err(e) => startup_warnings.push(format!(
+                "Repository session storage unavailable ({e}), using the sessions directory"
+            )),
+        }
+    }
+    let retention = sessions_config.retention();
+    let sessions_command = cli_args.sessions.take();
+
+    // With `auto_gc`, expire old sessions before any are listed or resumed.
+    // Sessions shared through the repository are only deleted by an explicit
+    // `sessions gc`.
+    let collected = if !sessions_config.auto_gc
+        || repo_storage
+        || sessions_command == Some(SessionsCommand::Gc)
+    {
+        Vec::new()
+    } else {
+        persistence::collect_garbage(&retention).unwrap_or_else(|e| {
+            startup_warnings.push(format!("Failed to delete old sessions: {e}"));
+            Vec::new()
+        })
+    };
+
+    let open_session = match sessions_command {
+        Some(command) => {
+            for warning in &startup_warnings {
+                eprintln!("Warning: {warning}");
+            }
+            if !collected.is_empty() {
+                eprintln!("{}", sessions::collected_summary(&collected));
+            }
+            match sessions::run(command, &retention) {
+                Ok(Some(session)) => Some(session),
+                Ok(None) => return Ok(()),
+                Err(e) => {
+                    eprintln!("Error: {e:#}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => None,
+    };
     let (theme, theme_warnings) = resolve_theme_with_config(
         cli_args.theme,
         config.as_ref().and_then(|cfg| cfg.theme.as_deref()),
@@ -85,16 +143,37 @@ fn main() -> anyhow::Result<()> {
         None
     };
 
+    let path_patterns = (!cli_args.paths.is_empty() || !cli_args.excludes.is_empty()).then(|| {
+        model::PathPatterns {
+            include: cli_args.paths.clone(),
+            exclude: cli_args.excludes.clone(),
+        }
+    });
+
     // Initialize app
     let mut app = match App::new(
         theme,
         cli_args.output_to_stdout,
         cli_args.revisions.as_deref(),
+        path_patterns,
+        &config.unwrap_or_default(),
     ) {
         Ok(mut app) => {
+            if let Some(session) = open_session
+                && let Err(e) = app.open_session(session)
+            {
+                eprintln!("Error: Failed to open session: {e}");
+                std::process::exit(1);
+            }
             app.supports_keyboard_enhancement = keyboard_enhancement_supported;
+            app.export_format = cli_args.export_format;
+            for path in &cli_args.diagnostics {
+                app.load_diagnostics(path);
+            }
             if let Some(message) = startup_warnings.first() {
                 app.set_warning(message.clone());
+            } else if !collected.is_empty() {
+                app.set_message(sessions::collected_summary(&collected));
             }
             app
         }
@@ -139,6 +218,9 @@ fn main() -> anyhow::Result<()> {
 
     // Main loop
     loop {
+        // Session lock, recovery and autosave
+        app.tick();
+
         // Render
         terminal.draw(|frame| {
             ui::render(frame, &mut app);
@@ -175,7 +257,7 @@ fn main() -> anyhow::Result<()> {
                             .contains(crossterm::event::KeyModifiers::CONTROL)
                     {
                         // If in comment mode, cancel the comment first
-                        if app.input_mode == InputMode::Comment {
+                        if app.is_writing_comment() {
                             app.exit_comment_mode();
                         }
 
@@ -198,11 +280,20 @@ fn main() -> anyhow::Result<()> {
                         app.message = None;
                     }
 
-                    // Handle pending z command for zz centering
+                    // Handle pending z command for zz centering and za/zo/zc/zM/zR folds
                     if pending_z {
                         pending_z = false;
-                        if key.code == crossterm::event::KeyCode::Char('z') {
-                            app.center_cursor();
+                        let z_command: Option<fn(&mut App)> = match key.code {
+                            crossterm::event::KeyCode::Char('z') => Some(App::center_cursor),
+                            crossterm::event::KeyCode::Char('a') => Some(App::toggle_fold),
+                            crossterm::event::KeyCode::Char('o') => Some(App::open_fold),
+                            crossterm::event::KeyCode::Char('c') => Some(App::close_fold),
+                            crossterm::event::KeyCode::Char('M') => Some(App::fold_all),
+                            crossterm::event::KeyCode::Char('R') => Some(App::unfold_all),
+                            _ => None,
+                        };
+                        if let Some(command) = z_command {
+                            command(&mut app);
                             continue;
                         }
                         // Otherwise fall through to normal handling
@@ -212,7 +303,9 @@ fn main() -> anyhow::Result<()> {
                     if pending_d {
                         pending_d = false;
                         if key.code == crossterm::event::KeyCode::Char('d') {
-                            if !app.delete_comment_at_cursor() {
+                            if app.focused_panel == FocusedPanel::Comments {
+                                app.delete_selected_comment();
+                            } else if !app.delete_comment_at_cursor() {
                                 app.set_message("No comment at cursor");
                             }
                             continue;
@@ -228,6 +321,10 @@ fn main() -> anyhow::Result<()> {
                                 app.toggle_file_list();
                                 continue;
                             }
+                            crossterm::event::KeyCode::Char('c') => {
+                                app.toggle_comments_panel();
+                                continue;
+                            }
                             crossterm::event::KeyCode::Char('h') => {
                                 app.focused_panel = app::FocusedPanel::FileList;
                                 continue;
@@ -273,6 +370,10 @@ fn main() -> anyhow::Result<()> {
                     // Dispatch by input mode
                     match app.input_mode {
                         InputMode::Help => handle_help_action(&mut app, action),
+                        InputMode::Stats => handle_stats_action(&mut app, action),
+                        InputMode::SnippetPicker => handle_snippet_picker_action(&mut app, action),
+                        InputMode::Sessions => handle_sessions_action(&mut app, action),
+                        InputMode::SessionRename => handle_session_rename_action(&mut app, action),
                         InputMode::Command => handle_command_action(&mut app, action),
                         InputMode::Search => handle_search_action(&mut app, action),
                         InputMode::Comment => handle_comment_action(&mut app, action),
@@ -285,6 +386,9 @@ fn main() -> anyhow::Result<()> {
                             FocusedPanel::CommitSelector => {
                                 handle_commit_selector_action(&mut app, action)
                             }
+                            FocusedPanel::Comments => {
+                                handle_comments_panel_action(&mut app, action)
+                            }
                         },
                     }
                 }
@@ -293,6 +397,8 @@ fn main() -> anyhow::Result<()> {
         }
 
         if app.should_quit {
+            // Keep changes left unsaved, e.g. by Ctrl+C, for the next run
+            app.autosave();
             break;
         }
     }
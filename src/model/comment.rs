@@ -47,34 +47,44 @@ impl LineRange {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum CommentType {
-    Note,
-    Suggestion,
-    Issue,
-    Praise,
-}
+/// Category of a comment, stored as its lowercase id (e.g. `issue`).
+///
+/// The available types, their labels and colors come from
+/// [`CommentTypes`](super::comment_types::CommentTypes).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct CommentType(String);
 
 impl CommentType {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            CommentType::Note => "NOTE",
-            CommentType::Suggestion => "SUGGESTION",
-            CommentType::Issue => "ISSUE",
-            CommentType::Praise => "PRAISE",
-        }
+    pub fn new(name: &str) -> Self {
+        Self(name.trim().to_lowercase())
+    }
+
+    pub fn id(&self) -> &str {
+        &self.0
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl From<String> for CommentType {
+    fn from(name: String) -> Self {
+        Self::new(&name)
+    }
+}
+
+impl From<CommentType> for String {
+    fn from(comment_type: CommentType) -> Self {
+        comment_type.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LineContext {
     pub new_line: Option<u32>,
     pub old_line: Option<u32>,
     pub content: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Comment {
     pub id: String,
     pub content: String,
@@ -89,6 +99,9 @@ pub struct Comment {
     /// None for file-level comments or single-line comments (backward compatibility)
     #[serde(default)]
     pub line_range: Option<LineRange>,
+    /// Reviewer who wrote the comment, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
 }
 
 impl Comment {
@@ -101,6 +114,7 @@ impl Comment {
             line_context: None,
             side,
             line_range: None,
+            author: None,
         }
     }
 
@@ -119,8 +133,47 @@ impl Comment {
             line_context: None,
             side,
             line_range: Some(line_range),
+            author: None,
         }
     }
+
+    pub fn with_author(mut self, author: Option<String>) -> Self {
+        self.author = author;
+        self
+    }
+
+    /// Initials of the author, e.g. `JD` for `Jane Doe`
+    pub fn author_initials(&self) -> Option<String> {
+        let initials: String = self
+            .author
+            .as_deref()?
+            .split_whitespace()
+            .filter_map(|word| word.chars().next())
+            .flat_map(char::to_uppercase)
+            .collect();
+        (!initials.is_empty()).then_some(initials)
+    }
+
+    /// Whether `name` is the author's full name or initials, ignoring case
+    /// and extra whitespace
+    pub fn is_by(&self, name: &str) -> bool {
+        let Some(author) = &self.author else {
+            return false;
+        };
+        let name = normalize_name(name);
+        normalize_name(author) == name
+            || self
+                .author_initials()
+                .is_some_and(|initials| initials.to_lowercase() == name)
+    }
+}
+
+/// Lowercase `name` with its words separated by single spaces
+fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 #[cfg(test)]
@@ -226,12 +279,12 @@ mod tests {
         fn new_creates_comment_without_line_range() {
             let comment = Comment::new(
                 "Test comment".to_string(),
-                CommentType::Note,
+                CommentType::new("note"),
                 Some(LineSide::New),
             );
             assert!(comment.line_range.is_none());
             assert_eq!(comment.content, "Test comment");
-            assert_eq!(comment.comment_type, CommentType::Note);
+            assert_eq!(comment.comment_type, CommentType::new("note"));
             assert_eq!(comment.side, Some(LineSide::New));
         }
 
@@ -240,7 +293,7 @@ mod tests {
             let range = LineRange::new(10, 15);
             let comment = Comment::new_with_range(
                 "Range comment".to_string(),
-                CommentType::Issue,
+                CommentType::new("issue"),
                 Some(LineSide::Old),
                 range,
             );
@@ -256,7 +309,7 @@ mod tests {
             let range = LineRange::new(10, 15);
             let comment = Comment::new_with_range(
                 "Test".to_string(),
-                CommentType::Note,
+                CommentType::new("note"),
                 Some(LineSide::New),
                 range,
             );
@@ -300,4 +353,53 @@ mod tests {
             assert_eq!(range.end, 15);
         }
     }
+
+    mod comment_type_tests {
+        use super::*;
+
+        #[test]
+        fn new_normalizes_case_and_whitespace() {
+            assert_eq!(CommentType::new(" Security "), CommentType::new("security"));
+            assert_eq!(CommentType::new("Perf").id(), "perf");
+        }
+
+        #[test]
+        fn deserializes_legacy_and_custom_ids() {
+            let types: Vec<CommentType> = serde_json::from_str(r#"["note", "Security"]"#).unwrap();
+            assert_eq!(
+                types,
+                vec![CommentType::new("note"), CommentType::new("security")]
+            );
+            assert_eq!(serde_json::to_string(&types[1]).unwrap(), r#""security""#);
+        }
+    }
+
+    mod author_tests {
+        use super::*;
+
+        fn by(author: &str) -> Comment {
+            Comment::new("x".to_string(), CommentType::new("note"), None)
+                .with_author(Some(author.to_string()))
+        }
+
+        #[test]
+        fn author_initials_take_the_first_letter_of_each_name() {
+            assert_eq!(by("Jane van Doe").author_initials().as_deref(), Some("JVD"));
+            assert_eq!(by("ana").author_initials().as_deref(), Some("A"));
+            let anonymous = Comment::new("x".to_string(), CommentType::new("note"), None);
+            assert_eq!(anonymous.author_initials(), None);
+        }
+
+        #[test]
+        fn is_by_matches_names_and_initials() {
+            let comment = by("Jane Doe");
+            assert!(comment.is_by("jane doe"));
+            assert!(comment.is_by("  Jane   DOE "));
+            assert!(comment.is_by("JD"));
+            assert!(!comment.is_by("jane"));
+            assert!(!comment.is_by("an"));
+            assert!(!comment.is_by("JX"));
+            assert!(!comment.is_by("john"));
+        }
+    }
 }
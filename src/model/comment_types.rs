@@ -0,0 +1,205 @@
+//! The set of comment types offered when commenting: the built-in
+//! Note/Suggestion/Issue/Praise or the `[[comment_types]]` list from config.
+
+use ratatui::style::Color;
+use std::borrow::Cow;
+
+use super::comment::CommentType;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentTypeDef {
+    pub id: CommentType,
+    /// Display name, shown upper-cased as `[LABEL]`
+    pub name: String,
+    /// Explains the type in the export legend
+    pub description: String,
+    /// `None` uses the theme's color for built-in ids, else the text color
+    pub color: Option<Color>,
+    /// Exports list lower priorities first
+    pub priority: u32,
+    /// Must be addressed before the change can be merged
+    pub blocking: bool,
+}
+
+impl CommentTypeDef {
+    pub fn new(name: &str, description: &str, priority: u32) -> Self {
+        Self {
+            id: CommentType::new(name),
+            name: name.to_string(),
+            description: description.to_string(),
+            color: None,
+            priority,
+            blocking: false,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        self.name.to_uppercase()
+    }
+}
+
+fn builtin_defs() -> Vec<CommentTypeDef> {
+    vec![
+        CommentTypeDef::new("Note", "observations", 2),
+        CommentTypeDef::new("Suggestion", "improvements", 1),
+        CommentTypeDef::new("Issue", "problems to fix", 0),
+        CommentTypeDef::new("Praise", "positive feedback", 3),
+    ]
+}
+
+/// Configured comment types, in the order `Tab` cycles through them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentTypes {
+    defs: Vec<CommentTypeDef>,
+}
+
+impl Default for CommentTypes {
+    fn default() -> Self {
+        Self {
+            defs: builtin_defs(),
+        }
+    }
+}
+
+impl CommentTypes {
+    /// Use `defs` instead of the built-in types; an empty list keeps the built-ins.
+    pub fn new(defs: Vec<CommentTypeDef>) -> Self {
+        if defs.is_empty() {
+            return Self::default();
+        }
+        Self { defs }
+    }
+
+    /// Type used for new comments
+    pub fn default_type(&self) -> CommentType {
+        self.defs[0].id.clone()
+    }
+
+    pub fn next(&self, current: &CommentType) -> CommentType {
+        let next = self
+            .defs
+            .iter()
+            .position(|def| def.id == *current)
+            .map_or(0, |idx| (idx + 1) % self.defs.len());
+        self.defs[next].id.clone()
+    }
+
+    /// Find a configured type by id or name, ignoring case
+    pub fn find(&self, name: &str) -> Option<CommentType> {
+        let wanted = CommentType::new(name);
+        self.defs
+            .iter()
+            .find(|def| def.id == wanted || def.name.eq_ignore_ascii_case(name.trim()))
+            .map(|def| def.id.clone())
+    }
+
+    /// Definition for a type, also for ids saved in older sessions that are
+    /// no longer configured: built-in ids keep their built-in definition and
+    /// anything else sorts last.
+    pub fn resolve(&self, comment_type: &CommentType) -> Cow<'_, CommentTypeDef> {
+        if let Some(def) = self.defs.iter().find(|def| def.id == *comment_type) {
+            return Cow::Borrowed(def);
+        }
+        let def = builtin_defs()
+            .into_iter()
+            .find(|def| def.id == *comment_type)
+            .unwrap_or_else(|| CommentTypeDef::new(comment_type.id(), "", u32::MAX));
+        Cow::Owned(def)
+    }
+
+    /// One-line explanation of every type, highest priority first, e.g.
+    /// `ISSUE (problems to fix), SUGGESTION (improvements)`
+    pub fn legend(&self) -> String {
+        let mut defs: Vec<&CommentTypeDef> = self.defs.iter().collect();
+        defs.sort_by_key(|def| def.priority);
+        defs.iter()
+            .map(|def| {
+                let mut details: Vec<&str> = Vec::new();
+                if !def.description.is_empty() {
+                    details.push(&def.description);
+                }
+                if def.blocking {
+                    details.push("blocking");
+                }
+                if details.is_empty() {
+                    def.label()
+                } else {
+                    format!("{} ({})", def.label(), details.join(", "))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_types() -> CommentTypes {
+        let mut security = CommentTypeDef::new("Security", "vulnerabilities", 0);
+        security.blocking = true;
+        CommentTypes::new(vec![
+            CommentTypeDef::new("Nit", "", 5),
+            security,
+            CommentTypeDef::new("Question", "needs an answer", 1),
+        ])
+    }
+
+    #[test]
+    fn should_cycle_through_configured_types_in_order() {
+        // given
+        let types = custom_types();
+
+        // when / then
+        assert_eq!(types.default_type(), CommentType::new("nit"));
+        assert_eq!(
+            types.next(&CommentType::new("nit")),
+            CommentType::new("security")
+        );
+        assert_eq!(
+            types.next(&CommentType::new("question")),
+            CommentType::new("nit")
+        );
+        // Unknown types restart at the first one
+        assert_eq!(
+            types.next(&CommentType::new("praise")),
+            CommentType::new("nit")
+        );
+    }
+
+    #[test]
+    fn should_resolve_unconfigured_ids_from_older_sessions() {
+        // given
+        let types = custom_types();
+
+        // when
+        let builtin = types.resolve(&CommentType::new("issue"));
+        let unknown = types.resolve(&CommentType::new("perf"));
+
+        // then
+        assert_eq!(builtin.label(), "ISSUE");
+        assert_eq!(builtin.priority, 0);
+        assert_eq!(unknown.label(), "PERF");
+        assert_eq!(unknown.priority, u32::MAX);
+    }
+
+    #[test]
+    fn should_list_legend_by_priority() {
+        assert_eq!(
+            CommentTypes::default().legend(),
+            "ISSUE (problems to fix), SUGGESTION (improvements), NOTE (observations), PRAISE (positive feedback)"
+        );
+        assert_eq!(
+            custom_types().legend(),
+            "SECURITY (vulnerabilities, blocking), QUESTION (needs an answer), NIT"
+        );
+    }
+
+    #[test]
+    fn should_find_types_by_name() {
+        let types = custom_types();
+        assert_eq!(types.find("SECURITY"), Some(CommentType::new("security")));
+        assert_eq!(types.find("note"), None);
+    }
+}
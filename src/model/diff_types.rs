@@ -1,6 +1,8 @@
 use ratatui::style::Style;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use super::comment::LineSide;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -67,6 +69,29 @@ pub struct DiffFile {
     pub is_binary: bool,
 }
 
+const COMMIT_MESSAGE_PREFIX: &str = "commit ";
+const COMMIT_MESSAGE_SUFFIX: &str = " message";
+
+/// Pseudo-file path under which a commit's message is reviewed,
+/// e.g. `commit abc1234 message`.
+pub fn commit_message_path(short_id: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{COMMIT_MESSAGE_PREFIX}{short_id}{COMMIT_MESSAGE_SUFFIX}"
+    ))
+}
+
+/// Returns the commit id if the path names a commit message pseudo-file.
+pub fn commit_message_id(path: &Path) -> Option<&str> {
+    let path = path.to_str()?;
+    let id = path
+        .strip_prefix(COMMIT_MESSAGE_PREFIX)?
+        .strip_suffix(COMMIT_MESSAGE_SUFFIX)?;
+    if id.is_empty() || id.contains(['/', ' ']) {
+        return None;
+    }
+    Some(id)
+}
+
 impl DiffFile {
     pub fn display_path(&self) -> &PathBuf {
         self.new_path
@@ -74,4 +99,287 @@ impl DiffFile {
             .or(self.old_path.as_ref())
             .expect("DiffFile must have at least one path")
     }
+
+    /// Build a pseudo-file that shows a commit message as added lines,
+    /// so it can be commented on like any other file.
+    pub fn commit_message(short_id: &str, message_lines: &[String]) -> Self {
+        let lines: Vec<DiffLine> = message_lines
+            .iter()
+            .enumerate()
+            .map(|(idx, content)| DiffLine {
+                origin: LineOrigin::Addition,
+                content: content.clone(),
+                old_lineno: None,
+                new_lineno: Some(idx as u32 + 1),
+                highlighted_spans: None,
+            })
+            .collect();
+        let new_count = lines.len() as u32;
+
+        Self {
+            old_path: None,
+            new_path: Some(commit_message_path(short_id)),
+            status: FileStatus::Added,
+            hunks: vec![DiffHunk {
+                header: format!("@@ -0,0 +1,{new_count} @@ commit message"),
+                lines,
+                old_start: 0,
+                old_count: 0,
+                new_start: 1,
+                new_count,
+            }],
+            is_binary: false,
+        }
+    }
+
+    pub fn is_commit_message(&self) -> bool {
+        self.old_path.is_none() && commit_message_id(self.display_path()).is_some()
+    }
+
+    /// Number of added and deleted lines across all hunks
+    pub fn line_stats(&self) -> (usize, usize) {
+        self.hunks
+            .iter()
+            .flat_map(|hunk| &hunk.lines)
+            .fold((0, 0), |(added, removed), line| match line.origin {
+                LineOrigin::Addition => (added + 1, removed),
+                LineOrigin::Deletion => (added, removed + 1),
+                LineOrigin::Context => (added, removed),
+            })
+    }
+
+    /// Total number of changed lines
+    pub fn churn(&self) -> usize {
+        let (added, removed) = self.line_stats();
+        added + removed
+    }
+}
+
+/// Build a single hunk showing one side of a file in full.
+///
+/// Lines untouched by the diff become context lines; lines the diff changed on
+/// `side` keep their origin so they stay marked. Changes that only exist on the
+/// other side are left out. `hunks` must be the file's regular diff hunks and
+/// `file_lines` the complete content of `side`.
+pub fn full_file_hunk(hunks: &[DiffHunk], file_lines: &[String], side: LineSide) -> DiffHunk {
+    let (kept_origin, dropped_origin) = match side {
+        LineSide::New => (LineOrigin::Addition, LineOrigin::Deletion),
+        LineSide::Old => (LineOrigin::Deletion, LineOrigin::Addition),
+    };
+    let this_lineno = |line: &DiffLine| match side {
+        LineSide::New => line.new_lineno,
+        LineSide::Old => line.old_lineno,
+    };
+    let other_lineno = |line: &DiffLine| match side {
+        LineSide::New => line.old_lineno,
+        LineSide::Old => line.new_lineno,
+    };
+
+    let mut lines = Vec::new();
+    // Next line on this side that has not been emitted yet
+    let mut next = 1u32;
+    // Offset from this side's line numbers to the other side's for unchanged lines
+    let mut offset = 0i64;
+
+    let fill_until = |lines: &mut Vec<DiffLine>, next: &mut u32, end: u32, offset: i64| {
+        while *next < end {
+            let Some(content) = file_lines.get(*next as usize - 1) else {
+                break;
+            };
+            let other = u32::try_from(i64::from(*next) + offset).ok();
+            let (old_lineno, new_lineno) = match side {
+                LineSide::New => (other, Some(*next)),
+                LineSide::Old => (Some(*next), other),
+            };
+            lines.push(DiffLine {
+                origin: LineOrigin::Context,
+                content: content.clone(),
+                old_lineno,
+                new_lineno,
+                highlighted_spans: None,
+            });
+            *next += 1;
+        }
+    };
+
+    for line in hunks.iter().flat_map(|hunk| &hunk.lines) {
+        if line.origin == dropped_origin {
+            // Unchanged lines before the dropped one still use the old offset
+            if let Some(other) = other_lineno(line) {
+                let end = u32::try_from(i64::from(other) - offset).unwrap_or(0);
+                fill_until(&mut lines, &mut next, end, offset);
+            }
+            offset += 1;
+            continue;
+        }
+        let Some(lineno) = this_lineno(line) else {
+            continue;
+        };
+        fill_until(&mut lines, &mut next, lineno, offset);
+        lines.push(DiffLine {
+            highlighted_spans: None,
+            ..line.clone()
+        });
+        next = lineno + 1;
+        if line.origin == kept_origin {
+            offset -= 1;
+        }
+    }
+    fill_until(&mut lines, &mut next, u32::MAX, offset);
+
+    let old_count = lines.iter().filter(|l| l.old_lineno.is_some()).count() as u32;
+    let new_count = lines.iter().filter(|l| l.new_lineno.is_some()).count() as u32;
+    let side_name = match side {
+        LineSide::New => "new",
+        LineSide::Old => "old",
+    };
+
+    DiffHunk {
+        header: format!("@@ -1,{old_count} +1,{new_count} @@ full file ({side_name} side)"),
+        lines,
+        old_start: 1,
+        old_count,
+        new_start: 1,
+        new_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_commit_message_pseudo_file() {
+        // given
+        let lines = vec!["commit abc".to_string(), "Fix bug".to_string()];
+
+        // when
+        let file = DiffFile::commit_message("abc1234", &lines);
+
+        // then
+        assert!(file.is_commit_message());
+        assert_eq!(
+            file.display_path(),
+            &PathBuf::from("commit abc1234 message")
+        );
+        assert_eq!(file.hunks.len(), 1);
+        assert_eq!(file.hunks[0].new_count, 2);
+        assert_eq!(file.hunks[0].lines[1].new_lineno, Some(2));
+        assert_eq!(file.hunks[0].lines[1].origin, LineOrigin::Addition);
+    }
+
+    fn diff_line(
+        origin: LineOrigin,
+        content: &str,
+        old: Option<u32>,
+        new: Option<u32>,
+    ) -> DiffLine {
+        DiffLine {
+            origin,
+            content: content.to_string(),
+            old_lineno: old,
+            new_lineno: new,
+            highlighted_spans: None,
+        }
+    }
+
+    fn sample_hunks() -> Vec<DiffHunk> {
+        // old: a b c d e   new: a B c d e f
+        vec![
+            DiffHunk {
+                header: "@@ -2,1 +2,1 @@".to_string(),
+                lines: vec![
+                    diff_line(LineOrigin::Deletion, "b", Some(2), None),
+                    diff_line(LineOrigin::Addition, "B", None, Some(2)),
+                ],
+                old_start: 2,
+                old_count: 1,
+                new_start: 2,
+                new_count: 1,
+            },
+            DiffHunk {
+                header: "@@ -5,0 +6,1 @@".to_string(),
+                lines: vec![diff_line(LineOrigin::Addition, "f", None, Some(6))],
+                old_start: 5,
+                old_count: 0,
+                new_start: 6,
+                new_count: 1,
+            },
+        ]
+    }
+
+    fn lines_of(content: &str) -> Vec<String> {
+        content.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn should_build_full_new_side_with_additions_marked() {
+        // given
+        let hunks = sample_hunks();
+        let new_lines = lines_of("a B c d e f");
+
+        // when
+        let hunk = full_file_hunk(&hunks, &new_lines, LineSide::New);
+
+        // then
+        let contents: Vec<&str> = hunk.lines.iter().map(|l| l.content.as_str()).collect();
+        assert_eq!(contents, vec!["a", "B", "c", "d", "e", "f"]);
+        assert_eq!(hunk.lines[1].origin, LineOrigin::Addition);
+        assert_eq!(hunk.lines[5].origin, LineOrigin::Addition);
+        assert_eq!(hunk.lines[2].origin, LineOrigin::Context);
+        assert_eq!(hunk.lines[4].old_lineno, Some(5));
+        assert_eq!(hunk.lines[4].new_lineno, Some(5));
+        assert_eq!(hunk.new_count, 6);
+    }
+
+    #[test]
+    fn should_build_full_old_side_with_deletions_marked() {
+        // given
+        let hunks = sample_hunks();
+        let old_lines = lines_of("a b c d e");
+
+        // when
+        let hunk = full_file_hunk(&hunks, &old_lines, LineSide::Old);
+
+        // then
+        let contents: Vec<&str> = hunk.lines.iter().map(|l| l.content.as_str()).collect();
+        assert_eq!(contents, vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(hunk.lines[1].origin, LineOrigin::Deletion);
+        assert_eq!(hunk.lines[1].new_lineno, None);
+        assert_eq!(hunk.lines[2].old_lineno, Some(3));
+        assert_eq!(hunk.lines[2].new_lineno, Some(3));
+        assert_eq!(hunk.old_count, 5);
+    }
+
+    #[test]
+    fn should_track_line_offsets_across_unbalanced_changes() {
+        // given: one line inserted near the top shifts everything below it
+        let hunks = vec![DiffHunk {
+            header: "@@ -1,0 +2,1 @@".to_string(),
+            lines: vec![diff_line(LineOrigin::Addition, "x", None, Some(2))],
+            old_start: 1,
+            old_count: 0,
+            new_start: 2,
+            new_count: 1,
+        }];
+        let new_lines = lines_of("a x b c");
+
+        // when
+        let hunk = full_file_hunk(&hunks, &new_lines, LineSide::New);
+
+        // then
+        assert_eq!(hunk.lines[3].content, "c");
+        assert_eq!(hunk.lines[3].old_lineno, Some(3));
+        assert_eq!(hunk.lines[3].new_lineno, Some(4));
+    }
+
+    #[test]
+    fn should_extract_commit_id_from_pseudo_path() {
+        assert_eq!(
+            commit_message_id(Path::new("commit abc1234 message")),
+            Some("abc1234")
+        );
+        assert_eq!(commit_message_id(Path::new("src/commit abc message")), None);
+        assert_eq!(commit_message_id(Path::new("src/main.rs")), None);
+    }
 }
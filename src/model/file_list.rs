@@ -0,0 +1,326 @@
+//! How the file list panel filters, orders and lays out files.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use super::comment::Comment;
+use super::diff_types::{DiffFile, FileStatus};
+use super::review::FileReview;
+
+/// Which files the file list shows
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "value")]
+pub enum FileFilter {
+    #[default]
+    All,
+    Unreviewed,
+    Commented,
+    Status(FileStatus),
+    /// Case-insensitive substring of the file path
+    Path(String),
+    /// Files with comments by a reviewer, matched by [`Comment::is_by`]
+    Author(String),
+}
+
+impl FileFilter {
+    pub fn matches(&self, file: &DiffFile, review: Option<&FileReview>) -> bool {
+        match self {
+            Self::All => true,
+            Self::Unreviewed => !review.is_some_and(|r| r.reviewed),
+            Self::Commented => review.is_some_and(|r| r.comment_count() > 0),
+            Self::Status(status) => file.status == *status,
+            Self::Path(needle) => file
+                .display_path()
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(&needle.to_lowercase()),
+            Self::Author(name) => review.is_some_and(|r| {
+                r.file_comments
+                    .iter()
+                    .chain(r.line_comments.values().flatten())
+                    .any(|comment| comment.is_by(name))
+            }),
+        }
+    }
+
+    /// Whether the comments panel lists `comment`; only the author filter
+    /// narrows it
+    pub fn shows_comment(&self, comment: &Comment) -> bool {
+        match self {
+            Self::Author(name) => comment.is_by(name),
+            _ => true,
+        }
+    }
+}
+
+impl FromStr for FileFilter {
+    type Err = String;
+
+    /// Parse a `:filter` argument: `all`, `unreviewed`, `commented`, a
+    /// status (`A`/`added`, `M`, `D`, `R`, `C`), `path <text>` or
+    /// `author <name>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(needle) = s.strip_prefix("path ") {
+            let needle = needle.trim();
+            if needle.is_empty() {
+                return Err("Path filter needs text to match".to_string());
+            }
+            return Ok(Self::Path(needle.to_string()));
+        }
+        if let Some(name) = s.strip_prefix("author ") {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err("Author filter needs a name or initials".to_string());
+            }
+            return Ok(Self::Author(name.to_string()));
+        }
+
+        let status = match s {
+            "" | "all" => return Ok(Self::All),
+            "unreviewed" => return Ok(Self::Unreviewed),
+            "commented" => return Ok(Self::Commented),
+            "A" | "added" => FileStatus::Added,
+            "M" | "modified" => FileStatus::Modified,
+            "D" | "deleted" => FileStatus::Deleted,
+            "R" | "renamed" => FileStatus::Renamed,
+            "C" | "copied" => FileStatus::Copied,
+            _ => {
+                return Err(format!(
+                    "Invalid filter: {s} (expected all, unreviewed, commented, A/M/D/R/C, path <text> or author <name>)"
+                ));
+            }
+        };
+        Ok(Self::Status(status))
+    }
+}
+
+impl fmt::Display for FileFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => f.write_str("all"),
+            Self::Unreviewed => f.write_str("unreviewed"),
+            Self::Commented => f.write_str("commented"),
+            Self::Status(status) => write!(f, "status {}", status.as_char()),
+            Self::Path(needle) => write!(f, "path \"{needle}\""),
+            Self::Author(name) => write!(f, "author \"{name}\""),
+        }
+    }
+}
+
+/// Order of files in the file list and the diff view
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileSort {
+    #[default]
+    Path,
+    /// Most changed lines first
+    Churn,
+    Status,
+}
+
+impl FileSort {
+    pub fn compare(self, a: &DiffFile, b: &DiffFile) -> Ordering {
+        let by_path = || a.display_path().cmp(b.display_path());
+        match self {
+            Self::Path => by_path(),
+            Self::Churn => b.churn().cmp(&a.churn()).then_with(by_path),
+            Self::Status => status_rank(a.status)
+                .cmp(&status_rank(b.status))
+                .then_with(by_path),
+        }
+    }
+}
+
+fn status_rank(status: FileStatus) -> u8 {
+    match status {
+        FileStatus::Added => 0,
+        FileStatus::Modified => 1,
+        FileStatus::Renamed => 2,
+        FileStatus::Copied => 3,
+        FileStatus::Deleted => 4,
+    }
+}
+
+impl FromStr for FileSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "path" => Ok(Self::Path),
+            "churn" => Ok(Self::Churn),
+            "status" => Ok(Self::Status),
+            other => Err(format!(
+                "Invalid sort: {other} (expected path, churn or status)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FileSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Path => "path",
+            Self::Churn => "churn",
+            Self::Status => "status",
+        };
+        f.write_str(name)
+    }
+}
+
+/// File list settings, persisted with the session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileListView {
+    pub filter: FileFilter,
+    pub sort: FileSort,
+    /// Show full paths in one list instead of a directory tree
+    pub flat: bool,
+}
+
+impl FileListView {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Short description of non-default settings for the file list title
+    pub fn label(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if self.filter != FileFilter::All {
+            parts.push(self.filter.to_string());
+        }
+        if self.sort != FileSort::Path {
+            parts.push(format!("by {}", self.sort));
+        }
+        if self.flat {
+            parts.push("flat".to_string());
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Comment, CommentType, DiffHunk, DiffLine, LineOrigin};
+    use std::path::PathBuf;
+
+    fn file(path: &str, status: FileStatus, changed: usize) -> DiffFile {
+        let lines = (0..changed)
+            .map(|i| DiffLine {
+                origin: LineOrigin::Addition,
+                content: String::new(),
+                old_lineno: None,
+                new_lineno: Some(i as u32 + 1),
+                highlighted_spans: None,
+            })
+            .collect();
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status,
+            hunks: vec![DiffHunk {
+                header: String::new(),
+                lines,
+                old_start: 0,
+                old_count: 0,
+                new_start: 1,
+                new_count: changed as u32,
+            }],
+            is_binary: false,
+        }
+    }
+
+    #[test]
+    fn should_parse_filters() {
+        assert_eq!("".parse(), Ok(FileFilter::All));
+        assert_eq!("unreviewed".parse(), Ok(FileFilter::Unreviewed));
+        assert_eq!("D".parse(), Ok(FileFilter::Status(FileStatus::Deleted)));
+        assert_eq!(
+            "path src/ui".parse(),
+            Ok(FileFilter::Path("src/ui".to_string()))
+        );
+        assert_eq!(
+            "author jd".parse(),
+            Ok(FileFilter::Author("jd".to_string()))
+        );
+        assert!("path ".parse::<FileFilter>().is_err());
+        assert!("bogus".parse::<FileFilter>().is_err());
+    }
+
+    #[test]
+    fn should_match_filters_against_file_and_review() {
+        // given
+        let file = file("src/UI/app.rs", FileStatus::Modified, 1);
+        let mut review = FileReview::new(file.display_path().clone(), file.status);
+
+        // when / then
+        assert!(FileFilter::Unreviewed.matches(&file, None));
+        assert!(!FileFilter::Commented.matches(&file, Some(&review)));
+        assert!(FileFilter::Path("ui/".to_string()).matches(&file, None));
+        assert!(!FileFilter::Status(FileStatus::Added).matches(&file, None));
+
+        review.reviewed = true;
+        review.add_file_comment(Comment::new(
+            "why?".to_string(),
+            CommentType::new("note"),
+            None,
+        ));
+        assert!(!FileFilter::Unreviewed.matches(&file, Some(&review)));
+        assert!(FileFilter::Commented.matches(&file, Some(&review)));
+        assert!(!FileFilter::Author("jd".to_string()).matches(&file, Some(&review)));
+
+        review.add_file_comment(
+            Comment::new("nit".to_string(), CommentType::new("note"), None)
+                .with_author(Some("Jane Doe".to_string())),
+        );
+        assert!(FileFilter::Author("jd".to_string()).matches(&file, Some(&review)));
+        assert!(FileFilter::Author("jane doe".to_string()).matches(&file, Some(&review)));
+        assert!(!FileFilter::Author("jane".to_string()).matches(&file, Some(&review)));
+    }
+
+    #[test]
+    fn should_sort_by_churn_then_path() {
+        // given
+        let mut files = [
+            file("b.rs", FileStatus::Modified, 2),
+            file("a.rs", FileStatus::Deleted, 2),
+            file("c.rs", FileStatus::Added, 9),
+        ];
+
+        // when
+        files.sort_by(|a, b| FileSort::Churn.compare(a, b));
+
+        // then
+        let paths: Vec<_> = files.iter().map(|f| f.display_path().clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("c.rs"),
+                PathBuf::from("a.rs"),
+                PathBuf::from("b.rs")
+            ]
+        );
+
+        // when
+        files.sort_by(|a, b| FileSort::Status.compare(a, b));
+
+        // then
+        assert_eq!(files[0].status, FileStatus::Added);
+        assert_eq!(files[2].status, FileStatus::Deleted);
+    }
+
+    #[test]
+    fn should_label_only_non_default_settings() {
+        assert_eq!(FileListView::default().label(), None);
+
+        let view = FileListView {
+            filter: FileFilter::Unreviewed,
+            sort: FileSort::Churn,
+            flat: true,
+        };
+        assert_eq!(view.label().as_deref(), Some("unreviewed, by churn, flat"));
+    }
+}
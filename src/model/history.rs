@@ -0,0 +1,179 @@
+//! Undo/redo history of review changes, saved with the session.
+
+use serde::{Deserialize, Serialize};
+
+use super::review::FileReview;
+
+/// Oldest changes are dropped beyond this many undo steps
+const MAX_UNDO_STEPS: usize = 100;
+
+/// One undoable change: the affected files' reviews before and after it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewChange {
+    pub description: String,
+    pub before: Vec<FileReview>,
+    pub after: Vec<FileReview>,
+    /// Session notes before and after the change, if it changed them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<NotesChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotesChange {
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReviewHistory {
+    #[serde(default)]
+    undo: Vec<ReviewChange>,
+    #[serde(default)]
+    redo: Vec<ReviewChange>,
+}
+
+impl ReviewHistory {
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty() && self.redo.is_empty()
+    }
+
+    /// Add a new change; it replaces anything that could be redone.
+    pub fn push(&mut self, change: ReviewChange) {
+        self.redo.clear();
+        self.undo.push(change);
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Take the latest change to revert, moving it to the redo stack
+    pub fn undo(&mut self) -> Option<&ReviewChange> {
+        let change = self.undo.pop()?;
+        self.redo.push(change);
+        self.redo.last()
+    }
+
+    /// Take the latest undone change to re-apply, moving it back
+    pub fn redo(&mut self) -> Option<&ReviewChange> {
+        let change = self.redo.pop()?;
+        self.undo.push(change);
+        self.undo.last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Comment, CommentType, FileStatus, ReviewSession, SessionDiffSource};
+    use std::path::PathBuf;
+
+    fn change(description: &str) -> ReviewChange {
+        let review = FileReview::new(PathBuf::from("a.rs"), FileStatus::Modified);
+        ReviewChange {
+            description: description.to_string(),
+            before: vec![review.clone()],
+            after: vec![review],
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn should_move_changes_between_undo_and_redo() {
+        // given
+        let mut history = ReviewHistory::default();
+        history.push(change("first"));
+        history.push(change("second"));
+
+        // when / then
+        assert_eq!(history.undo().unwrap().description, "second");
+        assert_eq!(history.undo().unwrap().description, "first");
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().unwrap().description, "first");
+    }
+
+    #[test]
+    fn should_drop_redo_steps_on_new_change() {
+        // given
+        let mut history = ReviewHistory::default();
+        history.push(change("first"));
+        history.undo();
+
+        // when
+        history.push(change("second"));
+
+        // then
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn should_cap_undo_steps() {
+        // given
+        let mut history = ReviewHistory::default();
+
+        // when
+        for i in 0..=MAX_UNDO_STEPS {
+            history.push(change(&i.to_string()));
+        }
+
+        // then
+        let mut count = 0;
+        while history.undo().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, MAX_UNDO_STEPS);
+    }
+
+    #[test]
+    fn should_undo_and_redo_session_changes() {
+        // given
+        let path = PathBuf::from("a.rs");
+        let mut session = ReviewSession::new(
+            PathBuf::from("/repo"),
+            "abc".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+        session.add_file(path.clone(), FileStatus::Modified);
+        let before = session.snapshot([&path]);
+        let comment = Comment::new("typo".to_string(), CommentType::new("issue"), None);
+        session
+            .get_file_mut(&path)
+            .unwrap()
+            .add_file_comment(comment);
+        session.record_change("Add comment", before);
+
+        // when
+        let undone = session.undo();
+
+        // then
+        assert_eq!(undone.as_deref(), Some("Add comment"));
+        assert!(!session.has_comments());
+
+        // when
+        let redone = session.redo();
+
+        // then
+        assert_eq!(redone.as_deref(), Some("Add comment"));
+        assert!(session.has_comments());
+    }
+
+    #[test]
+    fn should_not_record_unchanged_files() {
+        // given
+        let path = PathBuf::from("a.rs");
+        let mut session = ReviewSession::new(
+            PathBuf::from("/repo"),
+            "abc".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+        session.add_file(path.clone(), FileStatus::Modified);
+
+        // when
+        let before = session.snapshot([&path]);
+        session.record_change("Nothing", before);
+
+        // then
+        assert!(session.history.is_empty());
+    }
+}
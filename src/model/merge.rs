@@ -0,0 +1,384 @@
+//! Combining the comments of two reviews of the same change.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use super::comment::{Comment, LineRange};
+use super::review::{FileReview, ReviewSession, SessionDiffSource};
+
+/// Whether a file counts as reviewed after a merge
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReviewedPolicy {
+    /// Reviewed if either session reviewed it
+    #[default]
+    Either,
+    /// Reviewed only if both sessions reviewed it
+    Both,
+}
+
+impl ReviewedPolicy {
+    pub const VALID_VALUES: &str = "either, both";
+}
+
+impl FromStr for ReviewedPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "either" => Ok(Self::Either),
+            "both" => Ok(Self::Both),
+            _ => Err(format!(
+                "Unknown reviewed policy '{s}'. Valid policies: {}",
+                Self::VALID_VALUES
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ReviewedPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Either => "either",
+            Self::Both => "both",
+        })
+    }
+}
+
+/// How [`ReviewSession::merge`] combines two sessions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// Whether the sessions are of the same repository; merging sessions of
+    /// different ones is reported as a conflict
+    pub same_repo: bool,
+    pub reviewed: ReviewedPolicy,
+}
+
+/// Outcome of [`ReviewSession::merge`]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Comments copied from the other session
+    pub added: usize,
+    /// Comments left out because this session already has them
+    pub duplicates: usize,
+    /// Files only one of the sessions marked reviewed; whether they are
+    /// reviewed after the merge depends on the [`ReviewedPolicy`]
+    pub reviewed_in_one: Vec<PathBuf>,
+    /// Ways the sessions differ that may make the merged comments point at
+    /// the wrong lines
+    pub conflicts: Vec<String>,
+}
+
+impl MergeReport {
+    /// One-line summary, e.g. `Merged 3 comments (1 duplicate skipped);
+    /// 2 files reviewed in only one session`
+    pub fn summary(&self) -> String {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        let mut summary = format!("Merged {} comment{}", self.added, plural(self.added));
+        if self.duplicates > 0 {
+            summary.push_str(&format!(
+                " ({} duplicate{} skipped)",
+                self.duplicates,
+                plural(self.duplicates)
+            ));
+        }
+        let disagreements = self.reviewed_in_one.len();
+        if disagreements > 0 {
+            summary.push_str(&format!(
+                "; {disagreements} file{} reviewed in only one session",
+                plural(disagreements)
+            ));
+        }
+        summary
+    }
+}
+
+impl ReviewSession {
+    /// Add the comments of `other` that this session doesn't have yet. Files
+    /// are reviewed as `options.reviewed` says; the files only one of them
+    /// reviewed are reported. Sessions of different repositories or reviewed
+    /// commits are reported rather than refused.
+    pub fn merge(&mut self, other: &ReviewSession, options: MergeOptions) -> MergeReport {
+        let mut report = MergeReport {
+            conflicts: self.merge_conflicts(other, options.same_repo),
+            ..MergeReport::default()
+        };
+
+        for (path, theirs) in &other.files {
+            let ours = self
+                .files
+                .entry(path.clone())
+                .or_insert_with(|| FileReview::new(path.clone(), theirs.status));
+            if ours.reviewed != theirs.reviewed {
+                report.reviewed_in_one.push(path.clone());
+            }
+            ours.reviewed = match options.reviewed {
+                ReviewedPolicy::Either => ours.reviewed || theirs.reviewed,
+                ReviewedPolicy::Both => ours.reviewed && theirs.reviewed,
+            };
+
+            for comment in &theirs.file_comments {
+                match merge_comment(&mut ours.file_comments, comment, None) {
+                    true => report.added += 1,
+                    false => report.duplicates += 1,
+                }
+            }
+            for (line, comments) in &theirs.line_comments {
+                let ours = ours.line_comments.entry(*line).or_default();
+                for comment in comments {
+                    match merge_comment(ours, comment, Some(*line)) {
+                        true => report.added += 1,
+                        false => report.duplicates += 1,
+                    }
+                }
+            }
+        }
+
+        report.reviewed_in_one.sort();
+
+        self.session_notes = match (self.session_notes.take(), &other.session_notes) {
+            (Some(ours), Some(theirs)) if ours != *theirs => Some(format!("{ours}\n\n{theirs}")),
+            (ours, theirs) => ours.or_else(|| theirs.clone()),
+        };
+        report
+    }
+
+    fn merge_conflicts(&self, other: &ReviewSession, same_repo: bool) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        if !same_repo {
+            conflicts.push(format!(
+                "Sessions belong to different repositories: {} and {}",
+                self.repo_path.display(),
+                other.repo_path.display()
+            ));
+        }
+        let (ours, theirs) = (self.target_label(), other.target_label());
+        if ours != theirs {
+            conflicts.push(format!(
+                "Sessions review different changes: {ours} and {theirs}"
+            ));
+        }
+        conflicts
+    }
+
+    /// The change under review, e.g. `working tree at abc1234` or
+    /// `commits abc1234..def5678`
+    fn target_label(&self) -> String {
+        let short = |id: &str| id.chars().take(7).collect::<String>();
+        let commits = match self.commit_range.as_deref().unwrap_or_default() {
+            [] => String::new(),
+            [only] => format!("commit {}", short(only)),
+            [first, .., last] => format!("commits {}..{}", short(first), short(last)),
+        };
+        match self.diff_source {
+            SessionDiffSource::WorkingTree => {
+                format!("working tree at {}", short(&self.base_commit))
+            }
+            SessionDiffSource::CommitRange => commits,
+            SessionDiffSource::WorkingTreeAndCommits => format!("working tree and {commits}"),
+        }
+    }
+}
+
+/// Add `comment` to `comments` unless an identical one is there. A comment
+/// that shares its id with a different one (both edited after the sessions
+/// diverged) gets a new id so each can still be edited on its own.
+fn merge_comment(comments: &mut Vec<Comment>, comment: &Comment, line: Option<u32>) -> bool {
+    if comments
+        .iter()
+        .any(|ours| is_same_comment(ours, comment, line))
+    {
+        return false;
+    }
+    let mut comment = comment.clone();
+    if comments.iter().any(|ours| ours.id == comment.id) {
+        comment.id = uuid::Uuid::new_v4().to_string();
+    }
+    comments.push(comment);
+    true
+}
+
+/// Same text, type and anchor; the id, author and creation time don't matter
+fn is_same_comment(a: &Comment, b: &Comment, line: Option<u32>) -> bool {
+    let range = |c: &Comment| c.line_range.or(line.map(LineRange::single));
+    a.content.trim() == b.content.trim()
+        && a.comment_type == b.comment_type
+        && a.side.unwrap_or_default() == b.side.unwrap_or_default()
+        && range(a) == range(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CommentType, FileStatus, LineSide};
+    use std::path::PathBuf;
+
+    fn session(base_commit: &str) -> ReviewSession {
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/test-repo"),
+            base_commit.to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        session.add_file(PathBuf::from("src/main.rs"), FileStatus::Modified);
+        session
+    }
+
+    fn line_comment(content: &str, line: u32) -> Comment {
+        Comment::new_with_range(
+            content.to_string(),
+            CommentType::new("issue"),
+            Some(LineSide::New),
+            LineRange::single(line),
+        )
+    }
+
+    fn same_repo() -> MergeOptions {
+        MergeOptions {
+            same_repo: true,
+            ..MergeOptions::default()
+        }
+    }
+
+    fn review<'a>(session: &'a mut ReviewSession, path: &str) -> &'a mut FileReview {
+        session.files.get_mut(&PathBuf::from(path)).unwrap()
+    }
+
+    #[test]
+    fn should_union_comments_and_skip_duplicates() {
+        // given
+        let mut ours = session("abc1234");
+        review(&mut ours, "src/main.rs").add_line_comment(12, line_comment("Off by one", 12));
+        let mut theirs = session("abc1234");
+        review(&mut theirs, "src/main.rs").add_line_comment(12, line_comment("Off by one", 12));
+        review(&mut theirs, "src/main.rs").add_line_comment(20, line_comment("Unwrap", 20));
+        theirs.add_file(PathBuf::from("src/lib.rs"), FileStatus::Added);
+        review(&mut theirs, "src/lib.rs").add_file_comment(Comment::new(
+            "Needs docs".to_string(),
+            CommentType::new("note"),
+            None,
+        ));
+
+        // when
+        let report = ours.merge(&theirs, same_repo());
+
+        // then
+        assert_eq!(report.added, 2);
+        assert_eq!(report.duplicates, 1);
+        assert!(report.conflicts.is_empty());
+        assert_eq!(ours.comment_count(), 3);
+        assert_eq!(review(&mut ours, "src/lib.rs").status, FileStatus::Added);
+        assert_eq!(report.summary(), "Merged 2 comments (1 duplicate skipped)");
+    }
+
+    #[test]
+    fn should_mark_files_reviewed_in_either_session() {
+        // given
+        let mut ours = session("abc1234");
+        let mut theirs = session("abc1234");
+        review(&mut theirs, "src/main.rs").reviewed = true;
+
+        // when
+        let report = ours.merge(&theirs, same_repo());
+
+        // then
+        assert!(ours.is_file_reviewed(&PathBuf::from("src/main.rs")));
+        assert_eq!(report.reviewed_in_one, vec![PathBuf::from("src/main.rs")]);
+        assert_eq!(
+            report.summary(),
+            "Merged 0 comments; 1 file reviewed in only one session"
+        );
+    }
+
+    #[test]
+    fn should_mark_files_reviewed_only_in_both_sessions_when_asked() {
+        // given
+        let mut ours = session("abc1234");
+        review(&mut ours, "src/main.rs").reviewed = true;
+        ours.add_file(PathBuf::from("src/lib.rs"), FileStatus::Modified);
+        review(&mut ours, "src/lib.rs").reviewed = true;
+        let mut theirs = session("abc1234");
+        theirs.add_file(PathBuf::from("src/lib.rs"), FileStatus::Modified);
+        review(&mut theirs, "src/lib.rs").reviewed = true;
+        let options = MergeOptions {
+            reviewed: ReviewedPolicy::Both,
+            ..same_repo()
+        };
+
+        // when
+        let report = ours.merge(&theirs, options);
+
+        // then
+        assert!(!ours.is_file_reviewed(&PathBuf::from("src/main.rs")));
+        assert!(ours.is_file_reviewed(&PathBuf::from("src/lib.rs")));
+        assert_eq!(report.reviewed_in_one, vec![PathBuf::from("src/main.rs")]);
+    }
+
+    #[test]
+    fn should_parse_reviewed_policies() {
+        assert_eq!("either".parse(), Ok(ReviewedPolicy::Either));
+        assert_eq!("BOTH".parse(), Ok(ReviewedPolicy::Both));
+        assert!("all".parse::<ReviewedPolicy>().is_err());
+    }
+
+    #[test]
+    fn should_give_diverged_comments_their_own_id() {
+        // given
+        let mut ours = session("abc1234");
+        let original = line_comment("Off by one", 12);
+        let mut edited = original.clone();
+        edited.content = "Off by one here too".to_string();
+        review(&mut ours, "src/main.rs").add_line_comment(12, original.clone());
+        let mut theirs = session("abc1234");
+        review(&mut theirs, "src/main.rs").add_line_comment(12, edited);
+
+        // when
+        ours.merge(&theirs, same_repo());
+
+        // then
+        let comments = &review(&mut ours, "src/main.rs").line_comments[&12];
+        assert_eq!(comments.len(), 2);
+        assert_ne!(comments[0].id, comments[1].id);
+    }
+
+    #[test]
+    fn should_report_sessions_of_different_repositories() {
+        // given
+        let mut ours = session("abc1234");
+        let mut theirs = session("abc1234");
+        theirs.repo_path = PathBuf::from("/tmp/other-repo");
+
+        // when
+        let report = ours.merge(&theirs, MergeOptions::default());
+
+        // then
+        assert_eq!(
+            report.conflicts,
+            vec![
+                "Sessions belong to different repositories: /tmp/test-repo and /tmp/other-repo"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_sessions_of_different_changes() {
+        // given
+        let mut ours = session("abc1234");
+        let mut theirs = session("abc1234");
+        theirs.diff_source = SessionDiffSource::CommitRange;
+        theirs.commit_range = Some(vec!["def5678aa".to_string(), "9876543bb".to_string()]);
+
+        // when
+        let report = ours.merge(&theirs, same_repo());
+
+        // then
+        assert_eq!(
+            report.conflicts,
+            vec![
+                "Sessions review different changes: working tree at abc1234 and commits def5678..9876543"
+                    .to_string()
+            ]
+        );
+    }
+}
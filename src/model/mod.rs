@@ -1,7 +1,20 @@
 pub mod comment;
+pub mod comment_types;
 pub mod diff_types;
+pub mod file_list;
+pub mod history;
+pub mod merge;
+pub mod path_filter;
 pub mod review;
+pub mod snippet;
+pub mod stats;
+pub mod version;
 
 pub use comment::{Comment, CommentType, LineRange, LineSide};
-pub use diff_types::{DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin};
+pub use comment_types::{CommentTypeDef, CommentTypes};
+pub use diff_types::{
+    DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin, commit_message_id, full_file_hunk,
+};
+pub use merge::{MergeOptions, ReviewedPolicy};
+pub use path_filter::{DEFAULT_GENERATED_PATTERNS, PathFilter, PathPatterns};
 pub use review::{ReviewSession, SessionDiffSource};
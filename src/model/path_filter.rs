@@ -0,0 +1,312 @@
+//! Include/exclude globs that narrow which files are part of a review.
+//!
+//! Patterns follow `.gitignore` conventions: a pattern without a `/` matches
+//! any path component, a pattern containing a `/` is anchored at the repository
+//! root, a trailing `/` matches directories only, `*` and `?` never cross a `/`
+//! and `**` matches any number of directories. In exclude lists a leading `!`
+//! re-includes paths excluded by an earlier pattern.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::diff_types::DiffFile;
+
+/// Name of the per-repository ignore file read from the repository root
+pub const IGNORE_FILE_NAME: &str = ".tuicrignore";
+
+/// Globs for files collapsed as generated or vendored when no
+/// `[generated] patterns` are configured
+pub const DEFAULT_GENERATED_PATTERNS: &[&str] =
+    &["vendor/", "dist/", "node_modules/", "*.min.js", "*.min.css"];
+
+/// Globs given on the command line, persisted with the session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathPatterns {
+    /// When non-empty, only files matching one of these are reviewed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+impl PathPatterns {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    glob: Vec<char>,
+    negated: bool,
+    anchored: bool,
+    dir_only: bool,
+}
+
+impl Rule {
+    fn parse(pattern: &str) -> Option<Self> {
+        let pattern = pattern.trim();
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let (rooted, pattern) = match pattern.strip_prefix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        if pattern.is_empty() {
+            return None;
+        }
+        Some(Self {
+            glob: pattern.chars().collect(),
+            negated,
+            anchored: rooted || pattern.contains('/'),
+            dir_only,
+        })
+    }
+
+    fn matches(&self, components: &[String]) -> bool {
+        // Directory-only rules never match the file name itself
+        let candidates = if self.dir_only {
+            components.len().saturating_sub(1)
+        } else {
+            components.len()
+        };
+
+        if self.anchored {
+            // Match the full path or any of its parent directories
+            (1..=candidates).any(|len| {
+                let prefix: Vec<char> = components[..len].join("/").chars().collect();
+                glob_match(&self.glob, &prefix)
+            })
+        } else {
+            components[..candidates].iter().any(|component| {
+                let component: Vec<char> = component.chars().collect();
+                glob_match(&self.glob, &component)
+            })
+        }
+    }
+}
+
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            glob_match(rest, text)
+                || (0..text.len()).any(|i| text[i] == '/' && glob_match(rest, &text[i + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        ['*', rest @ ..] => {
+            for i in 0..=text.len() {
+                if glob_match(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        ['?', rest @ ..] => text
+            .split_first()
+            .is_some_and(|(c, tail)| *c != '/' && glob_match(rest, tail)),
+        [c, rest @ ..] => text
+            .split_first()
+            .is_some_and(|(t, tail)| t == c && glob_match(rest, tail)),
+    }
+}
+
+/// Decides which files are dropped from the review scope
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathFilter {
+    include: Vec<Rule>,
+    exclude: Vec<Rule>,
+}
+
+impl PathFilter {
+    /// Build a filter from default excludes (config and `.tuicrignore`, in
+    /// that order) followed by the command-line patterns, so later patterns
+    /// win.
+    pub fn new(patterns: &PathPatterns, default_excludes: &[String]) -> Self {
+        Self {
+            include: patterns
+                .include
+                .iter()
+                .filter_map(|p| Rule::parse(p))
+                .collect(),
+            exclude: default_excludes
+                .iter()
+                .chain(&patterns.exclude)
+                .filter_map(|p| Rule::parse(p))
+                .collect(),
+        }
+    }
+
+    /// Filter matching a plain glob list, e.g. generated file patterns.
+    pub fn from_globs(globs: &[String]) -> Self {
+        Self::new(&PathPatterns::default(), globs)
+    }
+
+    /// Patterns from an ignore file, skipping blank lines and `#` comments.
+    pub fn parse_ignore_file(contents: &str) -> Vec<String> {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let components: Vec<String> = path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+
+        if !self.include.is_empty() && !self.include.iter().any(|r| r.matches(&components)) {
+            return true;
+        }
+
+        // Last matching exclude rule decides, so `!pattern` can re-include
+        self.exclude
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(&components))
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Drop excluded files, returning how many were removed.
+    /// Commit message pseudo-files are always kept.
+    pub fn apply(&self, files: &mut Vec<DiffFile>) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let before = files.len();
+        files.retain(|file| file.is_commit_message() || !self.is_excluded(file.display_path()));
+        before - files.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::FileStatus;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> PathFilter {
+        let patterns = PathPatterns {
+            include: include.iter().map(|p| p.to_string()).collect(),
+            exclude: exclude.iter().map(|p| p.to_string()).collect(),
+        };
+        PathFilter::new(&patterns, &[])
+    }
+
+    #[test]
+    fn should_match_unanchored_patterns_against_any_component() {
+        // given
+        let filter = filter(&[], &["*.lock", "node_modules/"]);
+
+        // when / then
+        assert!(filter.is_excluded(Path::new("Cargo.lock")));
+        assert!(filter.is_excluded(Path::new("web/yarn.lock")));
+        assert!(filter.is_excluded(Path::new("web/node_modules/pkg/index.js")));
+        assert!(!filter.is_excluded(Path::new("src/lock.rs")));
+        assert!(!filter.is_excluded(Path::new("node_modules")));
+    }
+
+    #[test]
+    fn should_anchor_patterns_containing_a_slash() {
+        // given
+        let filter = filter(&[], &["src/generated", "/docs/*.md"]);
+
+        // when / then
+        assert!(filter.is_excluded(Path::new("src/generated/api.rs")));
+        assert!(filter.is_excluded(Path::new("docs/intro.md")));
+        assert!(!filter.is_excluded(Path::new("lib/src/generated/api.rs")));
+        assert!(!filter.is_excluded(Path::new("docs/nested/intro.md")));
+    }
+
+    #[test]
+    fn should_match_double_star_across_directories() {
+        // given
+        let filter = filter(&[], &["**/__snapshots__/**", "tests/**/*.snap"]);
+
+        // when / then
+        assert!(filter.is_excluded(Path::new("__snapshots__/a.snap")));
+        assert!(filter.is_excluded(Path::new("src/ui/__snapshots__/b.txt")));
+        assert!(filter.is_excluded(Path::new("tests/one.snap")));
+        assert!(filter.is_excluded(Path::new("tests/deep/er/two.snap")));
+        assert!(!filter.is_excluded(Path::new("src/one.snap")));
+    }
+
+    #[test]
+    fn should_let_later_negations_re_include_files() {
+        // given
+        let patterns = PathPatterns {
+            include: Vec::new(),
+            exclude: vec!["!keep.lock".to_string()],
+        };
+        let filter = PathFilter::new(&patterns, &["*.lock".to_string()]);
+
+        // when / then
+        assert!(filter.is_excluded(Path::new("Cargo.lock")));
+        assert!(!filter.is_excluded(Path::new("keep.lock")));
+    }
+
+    #[test]
+    fn should_exclude_files_outside_include_patterns() {
+        // given
+        let filter = filter(&["src/", "README.md"], &["src/vendor/"]);
+
+        // when / then
+        assert!(!filter.is_excluded(Path::new("src/app.rs")));
+        assert!(!filter.is_excluded(Path::new("README.md")));
+        assert!(filter.is_excluded(Path::new("Cargo.toml")));
+        assert!(filter.is_excluded(Path::new("src/vendor/lib.rs")));
+    }
+
+    #[test]
+    fn should_parse_ignore_file_skipping_comments_and_blanks() {
+        // given
+        let contents = "# generated\n*.lock\n\n  snapshots/  \n";
+
+        // when
+        let patterns = PathFilter::parse_ignore_file(contents);
+
+        // then
+        assert_eq!(patterns, vec!["*.lock", "snapshots/"]);
+    }
+
+    #[test]
+    fn should_drop_excluded_files_but_keep_commit_messages() {
+        // given
+        let filter = filter(&[], &["*.lock"]);
+        let file = |path: &str| DiffFile {
+            old_path: None,
+            new_path: Some(path.into()),
+            status: FileStatus::Added,
+            hunks: Vec::new(),
+            is_binary: false,
+        };
+        let mut files = vec![
+            file("Cargo.lock"),
+            file("src/main.rs"),
+            DiffFile::commit_message("abc1234", &["Update lockfile".to_string()]),
+        ];
+
+        // when
+        let removed = filter.apply(&mut files);
+
+        // then
+        assert_eq!(removed, 1);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].display_path(), Path::new("src/main.rs"));
+    }
+}
@@ -5,8 +5,12 @@ use std::path::PathBuf;
 
 use super::comment::Comment;
 use super::diff_types::FileStatus;
+use super::file_list::FileListView;
+use super::history::{NotesChange, ReviewChange, ReviewHistory};
+use super::path_filter::PathPatterns;
+use super::version::CURRENT_VERSION;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FileReview {
     pub path: PathBuf,
     pub reviewed: bool,
@@ -53,6 +57,12 @@ pub enum SessionDiffSource {
 pub struct ReviewSession {
     pub id: String,
     pub version: String,
+    /// Optional label shown in the session browser
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Pinned sessions are never deleted for their age
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
     pub repo_path: PathBuf,
     #[serde(default)]
     pub branch_name: Option<String>,
@@ -65,6 +75,15 @@ pub struct ReviewSession {
     pub updated_at: DateTime<Utc>,
     pub files: HashMap<PathBuf, FileReview>,
     pub session_notes: Option<String>,
+    /// Include/exclude globs the review was scoped to
+    #[serde(default, skip_serializing_if = "PathPatterns::is_empty")]
+    pub path_filter: PathPatterns,
+    /// File list filter, sort order and layout
+    #[serde(default, skip_serializing_if = "FileListView::is_default")]
+    pub file_list: FileListView,
+    /// Undo/redo stack of comment and reviewed-state changes
+    #[serde(default, skip_serializing_if = "ReviewHistory::is_empty")]
+    pub history: ReviewHistory,
 }
 
 impl ReviewSession {
@@ -77,7 +96,9 @@ impl ReviewSession {
         let now = Utc::now();
         Self {
             id: uuid::Uuid::new_v4().to_string(),
-            version: "1.2".to_string(),
+            version: CURRENT_VERSION.to_string(),
+            name: None,
+            pinned: false,
             repo_path,
             branch_name,
             base_commit,
@@ -87,6 +108,9 @@ impl ReviewSession {
             updated_at: now,
             files: HashMap::new(),
             session_notes: None,
+            path_filter: PathPatterns::default(),
+            file_list: FileListView::default(),
+            history: ReviewHistory::default(),
         }
     }
 
@@ -104,6 +128,10 @@ impl ReviewSession {
         self.files.get_mut(path)
     }
 
+    pub fn comment_count(&self) -> usize {
+        self.files.values().map(|f| f.comment_count()).sum()
+    }
+
     pub fn has_comments(&self) -> bool {
         self.files.values().any(|f| f.comment_count() > 0)
     }
@@ -121,4 +149,69 @@ impl ReviewSession {
     pub fn is_file_reviewed(&self, path: &PathBuf) -> bool {
         self.files.get(path).map(|r| r.reviewed).unwrap_or(false)
     }
+
+    /// Current reviews of `paths`, taken before a change so it can be undone
+    pub fn snapshot<'a>(&self, paths: impl IntoIterator<Item = &'a PathBuf>) -> Vec<FileReview> {
+        paths
+            .into_iter()
+            .filter_map(|path| self.files.get(path).cloned())
+            .collect()
+    }
+
+    /// Record the change made to the files in `before` since it was taken.
+    /// Nothing is recorded if the files are unchanged.
+    pub fn record_change(&mut self, description: impl Into<String>, before: Vec<FileReview>) {
+        let notes = self.session_notes.clone();
+        self.record_change_with_notes(description, before, notes);
+    }
+
+    /// Record the change made to the files in `before` and to the session
+    /// notes, which were `notes_before`, since they were taken
+    pub fn record_change_with_notes(
+        &mut self,
+        description: impl Into<String>,
+        before: Vec<FileReview>,
+        notes_before: Option<String>,
+    ) {
+        let after = self.snapshot(before.iter().map(|review| &review.path));
+        let notes = (notes_before != self.session_notes).then(|| NotesChange {
+            before: notes_before,
+            after: self.session_notes.clone(),
+        });
+        if after == before && notes.is_none() {
+            return;
+        }
+        self.history.push(ReviewChange {
+            description: description.into(),
+            before,
+            after,
+            notes,
+        });
+    }
+
+    /// Revert the latest change, returning its description
+    pub fn undo(&mut self) -> Option<String> {
+        let change = self.history.undo()?.clone();
+        self.restore(change.before);
+        if let Some(notes) = change.notes {
+            self.session_notes = notes.before;
+        }
+        Some(change.description)
+    }
+
+    /// Re-apply the latest undone change, returning its description
+    pub fn redo(&mut self) -> Option<String> {
+        let change = self.history.redo()?.clone();
+        self.restore(change.after);
+        if let Some(notes) = change.notes {
+            self.session_notes = notes.after;
+        }
+        Some(change.description)
+    }
+
+    fn restore(&mut self, reviews: Vec<FileReview>) {
+        for review in reviews {
+            self.files.insert(review.path.clone(), review);
+        }
+    }
 }
@@ -0,0 +1,118 @@
+//! Reusable comment text from the `[[snippets]]` config list.
+
+use serde::Deserialize;
+
+use super::comment::LineRange;
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Snippet {
+    pub name: String,
+    /// Inserted text; `{file}`, `{lines}` and `{code}` are expanded
+    pub text: String,
+}
+
+/// What the comment being written refers to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnippetContext {
+    pub file: String,
+    pub lines: Option<LineRange>,
+    /// Source of the commented lines, without diff markers
+    pub code: String,
+}
+
+impl Snippet {
+    pub fn matches(&self, filter: &str) -> bool {
+        self.name.to_lowercase().contains(&filter.to_lowercase())
+    }
+
+    pub fn expand(&self, context: &SnippetContext) -> String {
+        let lines = match context.lines {
+            Some(range) if range.is_single() => range.start.to_string(),
+            Some(range) => format!("{}-{}", range.start, range.end),
+            None => String::new(),
+        };
+        // Expand in one pass so placeholder-like text in the code stays as is
+        let mut expanded = String::with_capacity(self.text.len());
+        let mut rest = self.text.as_str();
+        while let Some(start) = rest.find('{') {
+            expanded.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let value = [
+                ("{file}", context.file.as_str()),
+                ("{lines}", lines.as_str()),
+                ("{code}", context.code.as_str()),
+            ]
+            .into_iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder));
+            match value {
+                Some((placeholder, value)) => {
+                    expanded.push_str(value);
+                    rest = &rest[placeholder.len()..];
+                }
+                None => {
+                    expanded.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(text: &str) -> Snippet {
+        Snippet {
+            name: "Test".to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_expand_placeholders_from_context() {
+        // given
+        let context = SnippetContext {
+            file: "src/app.rs".to_string(),
+            lines: Some(LineRange::new(10, 12)),
+            code: "value.unwrap() // {file}".to_string(),
+        };
+
+        // when
+        let text = snippet("{file}:{lines} {unknown}\n```\n{code}\n```").expand(&context);
+
+        // then
+        assert_eq!(
+            text,
+            "src/app.rs:10-12 {unknown}\n```\nvalue.unwrap() // {file}\n```"
+        );
+    }
+
+    #[test]
+    fn should_expand_missing_context_to_empty_text() {
+        // given
+        let context = SnippetContext {
+            file: "a.rs".to_string(),
+            lines: Some(LineRange::single(7)),
+            code: String::new(),
+        };
+
+        // when / then
+        assert_eq!(snippet("L{lines}: {code}").expand(&context), "L7: ");
+        assert_eq!(snippet("{lines}").expand(&SnippetContext::default()), "");
+    }
+
+    #[test]
+    fn should_match_names_ignoring_case() {
+        let snippet = Snippet {
+            name: "Add a test".to_string(),
+            text: String::new(),
+        };
+        assert!(snippet.matches("TEST"));
+        assert!(snippet.matches(""));
+        assert!(!snippet.matches("unwrap"));
+    }
+}
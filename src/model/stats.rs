@@ -0,0 +1,184 @@
+//! Summary numbers for the `:stats` popup.
+
+use super::comment::CommentType;
+use super::comment_types::CommentTypes;
+use super::diff_types::{DiffFile, FileStatus};
+use super::review::ReviewSession;
+
+/// Order in which statuses are listed
+const STATUSES: [FileStatus; 5] = [
+    FileStatus::Added,
+    FileStatus::Modified,
+    FileStatus::Renamed,
+    FileStatus::Copied,
+    FileStatus::Deleted,
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReviewStats {
+    /// Changed files, not counting commit message pseudo-files
+    pub files: usize,
+    pub reviewed: usize,
+    /// Non-zero file counts per status
+    pub by_status: Vec<(FileStatus, usize)>,
+    pub additions: usize,
+    pub deletions: usize,
+    pub hunks: usize,
+    /// Non-zero comment counts per type, by export priority
+    pub comments: Vec<(CommentType, usize)>,
+}
+
+impl ReviewStats {
+    pub fn compute(
+        diff_files: &[DiffFile],
+        session: &ReviewSession,
+        comment_types: &CommentTypes,
+    ) -> Self {
+        let mut stats = Self::default();
+        let mut status_counts = [0; STATUSES.len()];
+
+        for file in diff_files.iter().filter(|f| !f.is_commit_message()) {
+            let (added, removed) = file.line_stats();
+            stats.files += 1;
+            stats.additions += added;
+            stats.deletions += removed;
+            stats.hunks += file.hunks.len();
+            if session.is_file_reviewed(file.display_path()) {
+                stats.reviewed += 1;
+            }
+            if let Some(idx) = STATUSES.iter().position(|s| *s == file.status) {
+                status_counts[idx] += 1;
+            }
+        }
+        stats.by_status = STATUSES
+            .into_iter()
+            .zip(status_counts)
+            .filter(|(_, count)| *count > 0)
+            .collect();
+
+        let comments = session.files.values().flat_map(|review| {
+            review
+                .file_comments
+                .iter()
+                .chain(review.line_comments.values().flatten())
+        });
+        for comment in comments {
+            match stats
+                .comments
+                .iter_mut()
+                .find(|(t, _)| *t == comment.comment_type)
+            {
+                Some((_, count)) => *count += 1,
+                None => stats.comments.push((comment.comment_type.clone(), 1)),
+            }
+        }
+        stats.comments.sort_by(|(a, _), (b, _)| {
+            let priority = |t| comment_types.resolve(t).priority;
+            priority(a)
+                .cmp(&priority(b))
+                .then_with(|| a.id().cmp(b.id()))
+        });
+
+        stats
+    }
+
+    pub fn reviewed_percent(&self) -> usize {
+        if self.files == 0 {
+            return 100;
+        }
+        self.reviewed * 100 / self.files
+    }
+
+    pub fn comment_count(&self) -> usize {
+        self.comments.iter().map(|(_, count)| count).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Comment, DiffHunk, DiffLine, LineOrigin, SessionDiffSource};
+    use std::path::PathBuf;
+
+    fn line(origin: LineOrigin) -> DiffLine {
+        DiffLine {
+            origin,
+            content: String::new(),
+            old_lineno: None,
+            new_lineno: None,
+            highlighted_spans: None,
+        }
+    }
+
+    fn file(path: &str, status: FileStatus, lines: Vec<DiffLine>) -> DiffFile {
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status,
+            hunks: vec![DiffHunk {
+                header: String::new(),
+                lines,
+                old_start: 1,
+                old_count: 0,
+                new_start: 1,
+                new_count: 0,
+            }],
+            is_binary: false,
+        }
+    }
+
+    #[test]
+    fn should_summarize_files_lines_and_comments() {
+        // given
+        let files = vec![
+            file(
+                "a.rs",
+                FileStatus::Modified,
+                vec![
+                    line(LineOrigin::Addition),
+                    line(LineOrigin::Context),
+                    line(LineOrigin::Deletion),
+                    line(LineOrigin::Deletion),
+                ],
+            ),
+            file("b.rs", FileStatus::Added, vec![line(LineOrigin::Addition)]),
+            DiffFile::commit_message("abc1234", &["Subject".to_string()]),
+        ];
+        let mut session = ReviewSession::new(
+            PathBuf::from("/repo"),
+            "abc".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+        for file in &files {
+            session.add_file(file.display_path().clone(), file.status);
+        }
+        let review = session.get_file_mut(&PathBuf::from("a.rs")).unwrap();
+        review.reviewed = true;
+        let issue = CommentType::new("issue");
+        review.add_line_comment(1, Comment::new("x".to_string(), issue.clone(), None));
+        review.add_file_comment(Comment::new("y".to_string(), issue.clone(), None));
+        review.add_file_comment(Comment::new(
+            "z".to_string(),
+            CommentType::new("note"),
+            None,
+        ));
+
+        // when
+        let stats = ReviewStats::compute(&files, &session, &CommentTypes::default());
+
+        // then
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.reviewed_percent(), 50);
+        assert_eq!(
+            stats.by_status,
+            vec![(FileStatus::Added, 1), (FileStatus::Modified, 1)]
+        );
+        assert_eq!((stats.additions, stats.deletions, stats.hunks), (2, 2, 2));
+        assert_eq!(
+            stats.comments,
+            vec![(issue, 2), (CommentType::new("note"), 1)]
+        );
+        assert_eq!(stats.comment_count(), 3);
+    }
+}
@@ -0,0 +1,43 @@
+//! Version of the stored session format.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchemaVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+/// Format written by this version of tuicr
+pub const CURRENT_VERSION: SchemaVersion = SchemaVersion::new(1, 4);
+
+impl SchemaVersion {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// Parse `major.minor`; a bare `major` is `major.0`
+    pub fn parse(version: &str) -> Option<Self> {
+        let (major, minor) = version.split_once('.').unwrap_or((version, "0"));
+        Some(Self::new(major.parse().ok()?, minor.parse().ok()?))
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_versions() {
+        assert_eq!(SchemaVersion::parse("1.2"), Some(SchemaVersion::new(1, 2)));
+        assert_eq!(SchemaVersion::parse("2"), Some(SchemaVersion::new(2, 0)));
+        assert_eq!(SchemaVersion::parse("one"), None);
+        assert!(SchemaVersion::new(1, 10) > SchemaVersion::new(1, 2));
+    }
+}
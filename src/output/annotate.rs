@@ -0,0 +1,657 @@
+//! Review comments as code comments in the reviewed files, e.g.
+//! `// REVIEW[tuicr](ISSUE): ...` above the line commented on, either as a
+//! patch or written into the working tree.
+//!
+//! Markers are recognized by their comment token followed by the
+//! `REVIEW[tuicr]` tag, so stripping them leaves the developer's own
+//! `REVIEW:` comments alone.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::model::review::FileReview;
+use crate::model::{CommentTypes, DiffHunk, LineSide};
+
+const MARKER: &str = "REVIEW[tuicr]";
+
+/// Line comment syntax of a language, with the closing token for languages
+/// that only have block comments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommentSyntax {
+    start: &'static str,
+    end: Option<&'static str>,
+}
+
+impl CommentSyntax {
+    const fn line(start: &'static str) -> Self {
+        Self { start, end: None }
+    }
+
+    const fn block(start: &'static str, end: &'static str) -> Self {
+        Self {
+            start,
+            end: Some(end),
+        }
+    }
+
+    /// Comment syntax of a syntect syntax, by its name; `None` for formats
+    /// without comments, like JSON, and syntaxes not listed here
+    pub fn for_syntax(name: &str) -> Option<Self> {
+        let syntax = match name {
+            "C" | "C++" | "C#" | "D" | "Dart" | "Go" | "Groovy" | "Java" | "JavaScript"
+            | "JavaScript (Babel)" | "TypeScript" | "TypeScriptReact" | "Kotlin"
+            | "Objective-C" | "Objective-C++" | "PHP" | "Protocol Buffer" | "Rust" | "Scala"
+            | "SCSS" | "Less" | "Solidity" | "Swift" | "Zig" | "Odin" | "GLSL" | "WGSL" | "QML"
+            | "GraphQL" | "jsonnet" | "Verilog" | "SystemVerilog" | "ActionScript" | "Gomod"
+            | "Typst" => Self::line("//"),
+            "Python"
+            | "Ruby"
+            | "Perl"
+            | "R"
+            | "Bourne Again Shell (bash)"
+            | "Shell-Unix-Generic"
+            | "Fish"
+            | "YAML"
+            | "TOML"
+            | "Makefile"
+            | "CMake"
+            | "Dockerfile"
+            | "Dockerfile (with bash)"
+            | "Nix"
+            | "Elixir"
+            | "Julia"
+            | "Nim"
+            | "Terraform"
+            | "Crystal"
+            | "CoffeeScript"
+            | "GDScript (Godot Engine)"
+            | "Tcl"
+            | "AWK"
+            | "Puppet"
+            | "Rego"
+            | "Requirements.txt"
+            | "DotENV"
+            | "Git Ignore"
+            | "Git Attributes"
+            | "Git Config"
+            | "nginx"
+            | "Apache Conf"
+            | "Robot Framework"
+            | "Vyper"
+            | "JQ"
+            | "Cabal"
+            | "Crontab" => Self::line("#"),
+            "SQL" | "Haskell" | "Lua" | "Elm" | "Ada" | "Idris" | "PureScript" | "VHDL"
+            | "Lean 4" => Self::line("--"),
+            "Clojure" | "Lisp" | "Racket" => Self::line(";;"),
+            "INI" => Self::line(";"),
+            "Erlang" | "LaTeX" | "TeX" | "MATLAB" => Self::line("%"),
+            "VimL" => Self::line("\""),
+            "Batch File" => Self::line("REM"),
+            "Fortran (Modern)" => Self::line("!"),
+            "CSS" => Self::block("/*", "*/"),
+            "HTML" | "XML" | "Markdown" | "MultiMarkdown" | "Vue Component" | "Svelte" => {
+                Self::block("<!--", "-->")
+            }
+            "OCaml" | "F#" | "SML" | "Pascal" => Self::block("(*", "*)"),
+            _ => return None,
+        };
+        Some(syntax)
+    }
+
+    /// `text` as a comment. In block comments the text's own comment
+    /// tokens are broken up, so it can neither close the comment early nor
+    /// open a nested one.
+    fn comment(&self, text: &str) -> String {
+        match self.end {
+            Some(end) => {
+                let text = break_token(&break_token(text, end), self.start);
+                format!("{} {text} {end}", self.start)
+            }
+            None => format!("{} {text}", self.start),
+        }
+    }
+
+    fn is_marker(&self, line: &str) -> bool {
+        line.trim_start()
+            .strip_prefix(self.start)
+            .map(str::trim_start)
+            .and_then(|text| text.strip_prefix(MARKER))
+            .is_some_and(|rest| rest.starts_with(['(', ':']))
+    }
+}
+
+/// `text` with a space before the last character of each `token` in it,
+/// e.g. `-->` becomes `-- >`
+fn break_token(text: &str, token: &str) -> String {
+    let (head, last) = token.split_at(token.len() - 1);
+    text.replace(token, &format!("{head} {last}"))
+}
+
+/// Marker lines for a file's comments
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FileAnnotations {
+    /// Markers with the index of the line (in the new version of the file)
+    /// they go above
+    pub markers: Vec<(usize, String)>,
+    /// Comments on deleted lines outside the diff's hunks, which have no
+    /// place in the new version
+    pub unplaced: usize,
+}
+
+/// Marker lines for a file's comments. Comments on deleted lines go above
+/// the line that follows the deletion in the new version.
+pub fn file_annotations(
+    review: &FileReview,
+    hunks: &[DiffHunk],
+    comment_types: &CommentTypes,
+    syntax: CommentSyntax,
+) -> FileAnnotations {
+    let mut annotations = FileAnnotations::default();
+    for comment in &review.file_comments {
+        let label = comment_types.resolve(&comment.comment_type).label();
+        for text in marker_texts(&format!("{MARKER}({label})"), &comment.content) {
+            annotations.markers.push((0, syntax.comment(&text)));
+        }
+    }
+
+    let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+    line_comments.sort_by_key(|(line, _)| **line);
+    for (line, comments) in line_comments {
+        for comment in comments {
+            let range = comment
+                .line_range
+                .map_or((*line, *line), |r| (r.start, r.end));
+            let lines = match range {
+                (start, end) if start == end => format!("line {start}"),
+                (start, end) => format!("lines {start}-{end}"),
+            };
+            let (index, place) = match comment.side.unwrap_or_default() {
+                LineSide::New => (
+                    range.0.saturating_sub(1) as usize,
+                    (range.0 != range.1).then(|| format!(" on {lines}")),
+                ),
+                LineSide::Old => match index_after_deleted_line(hunks, range.0) {
+                    Some(index) => (index, Some(format!(" on removed {lines}"))),
+                    None => {
+                        annotations.unplaced += 1;
+                        continue;
+                    }
+                },
+            };
+            let label = comment_types.resolve(&comment.comment_type).label();
+            let header = format!("{MARKER}({label}){}", place.unwrap_or_default());
+            for text in marker_texts(&header, &comment.content) {
+                annotations.markers.push((index, syntax.comment(&text)));
+            }
+        }
+    }
+    annotations
+}
+
+/// `header: first line`, then `REVIEW[tuicr]: line` for the remaining lines
+fn marker_texts(header: &str, content: &str) -> Vec<String> {
+    let mut lines = content.lines();
+    let first = lines.next().unwrap_or_default();
+    std::iter::once(format!("{header}: {first}"))
+        .chain(lines.map(|line| format!("{MARKER}: {line}")))
+        .collect()
+}
+
+/// Index in the new version of the first line at or after the old-side
+/// `line`, or `None` if the line is not in the diff
+fn index_after_deleted_line(hunks: &[DiffHunk], line: u32) -> Option<usize> {
+    hunks.iter().find_map(|hunk| {
+        let position = hunk
+            .lines
+            .iter()
+            .position(|diff_line| diff_line.old_lineno == Some(line))?;
+        let next_new = hunk.lines[position..]
+            .iter()
+            .find_map(|diff_line| diff_line.new_lineno);
+        Some(match next_new {
+            Some(new_line) => new_line as usize - 1,
+            None => (hunk.new_start + hunk.new_count).saturating_sub(1) as usize,
+        })
+    })
+}
+
+/// A file with marker lines inserted
+#[derive(Debug)]
+pub struct AnnotatedFile {
+    pub path: PathBuf,
+    /// Lines of the annotated file with their line endings, and whether each
+    /// is an inserted marker. Only the last line can lack an ending.
+    lines: Vec<(String, bool)>,
+}
+
+impl AnnotatedFile {
+    /// Insert `annotations` into `content`, each indented like the line it
+    /// goes above and ending like it, so CRLF files stay CRLF. Annotations
+    /// past the end go above the last line, so the file's end (and a missing
+    /// final newline) is left as it is. A shebang or XML declaration stays
+    /// the first line, with the annotations above it going below it.
+    pub fn new(path: &Path, content: &str, mut annotations: Vec<(usize, String)>) -> Self {
+        let original: Vec<&str> = content.split_inclusive('\n').collect();
+        let last = original.len().saturating_sub(1);
+        let first = match original.first() {
+            Some(line) if is_preamble(line) && (last > 0 || line.ends_with('\n')) => 1,
+            _ => 0,
+        };
+        let place = |index: usize| index.min(last).max(first);
+        // Stable, so annotations on the same line keep their order
+        annotations.sort_by_key(|(index, _)| place(*index));
+
+        let mut lines = Vec::with_capacity(original.len() + annotations.len());
+        let mut annotations = annotations.into_iter().peekable();
+        // A final line without an ending gives its markers the previous one
+        let mut ending = "\n";
+        for (index, line) in original.iter().enumerate() {
+            let text = line_text(line);
+            if text.len() < line.len() {
+                ending = &line[text.len()..];
+            }
+            let indent = &text[..text.len() - text.trim_start().len()];
+            while let Some((_, marker)) = annotations.next_if(|(at, _)| place(*at) == index) {
+                lines.push((format!("{indent}{marker}{ending}"), true));
+            }
+            lines.push((line.to_string(), false));
+        }
+        // Only an empty file, or one that is just a preamble line, has
+        // annotations left
+        lines.extend(annotations.map(|(_, marker)| (format!("{marker}{ending}"), true)));
+
+        Self {
+            path: path.to_path_buf(),
+            lines,
+        }
+    }
+
+    /// Number of marker lines inserted
+    pub fn marker_count(&self) -> usize {
+        self.lines.iter().filter(|(_, inserted)| *inserted).count()
+    }
+
+    /// Content of the annotated file
+    pub fn content(&self) -> String {
+        self.lines.iter().map(|(line, _)| line.as_str()).collect()
+    }
+
+    /// Unified diff from the original file to the annotated one, with three
+    /// lines of context
+    pub fn diff(&self) -> String {
+        const CONTEXT: usize = 3;
+        let inserted: Vec<usize> = (0..self.lines.len()).filter(|&i| self.lines[i].1).collect();
+
+        // Ranges of lines shown in each hunk, merged when their context meets
+        let mut hunks: Vec<(usize, usize)> = Vec::new();
+        for &i in &inserted {
+            let start = i.saturating_sub(CONTEXT);
+            let end = (i + CONTEXT + 1).min(self.lines.len());
+            match hunks.last_mut() {
+                Some((_, last_end)) if start <= *last_end => *last_end = end,
+                _ => hunks.push((start, end)),
+            }
+        }
+
+        let path = self.path.display();
+        let mut diff = format!("--- a/{path}\n+++ b/{path}\n");
+        for (start, end) in hunks {
+            let old_before = self.lines[..start].iter().filter(|(_, i)| !i).count();
+            let old_count = self.lines[start..end].iter().filter(|(_, i)| !i).count();
+            let old_start = if old_count == 0 {
+                old_before
+            } else {
+                old_before + 1
+            };
+            let _ = writeln!(
+                diff,
+                "@@ -{old_start},{old_count} +{},{} @@",
+                start + 1,
+                end - start
+            );
+            for (line, inserted) in &self.lines[start..end] {
+                diff.push(if *inserted { '+' } else { ' ' });
+                diff.push_str(line);
+                if !line.ends_with('\n') {
+                    diff.push_str("\n\\ No newline at end of file\n");
+                }
+            }
+        }
+        diff
+    }
+}
+
+/// Whether `line` has to stay the first line of its file: a shebang (not a
+/// Rust inner attribute) or an XML declaration
+fn is_preamble(line: &str) -> bool {
+    (line.starts_with("#!") && !line.starts_with("#![")) || line.starts_with("<?xml")
+}
+
+/// `line` without its line ending
+fn line_text(line: &str) -> &str {
+    line.strip_suffix('\n')
+        .map_or(line, |line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+/// Patch inserting the markers of all `files`
+pub fn generate_patch(files: &[AnnotatedFile]) -> String {
+    files.iter().map(AnnotatedFile::diff).collect()
+}
+
+/// `content` without marker lines, or `None` if it has none
+pub fn strip_markers(content: &str, syntax: CommentSyntax) -> Option<String> {
+    let mut stripped = String::with_capacity(content.len());
+    let mut found = false;
+    for line in content.split_inclusive('\n') {
+        if syntax.is_marker(line) {
+            found = true;
+        } else {
+            stripped.push_str(line);
+        }
+    }
+    found.then_some(stripped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::diff_types::{DiffLine, LineOrigin};
+    use crate::model::{Comment, CommentType, FileStatus, LineRange};
+
+    const RUST: CommentSyntax = CommentSyntax::line("//");
+
+    fn diff_line(origin: LineOrigin, old: Option<u32>, new: Option<u32>) -> DiffLine {
+        DiffLine {
+            origin,
+            content: String::new(),
+            old_lineno: old,
+            new_lineno: new,
+            highlighted_spans: None,
+        }
+    }
+
+    #[test]
+    fn should_place_markers_above_commented_lines() {
+        // given
+        let mut review = FileReview::new(PathBuf::from("src/lib.rs"), FileStatus::Modified);
+        review.add_line_comment(
+            2,
+            Comment::new(
+                "Use a constant".to_string(),
+                CommentType::new("issue"),
+                Some(LineSide::New),
+            ),
+        );
+        review.add_line_comment(
+            4,
+            Comment::new_with_range(
+                "Extract this\ninto a helper".to_string(),
+                CommentType::new("suggestion"),
+                Some(LineSide::New),
+                LineRange::new(3, 4),
+            ),
+        );
+        let content = "fn main() {\n    let x = 42;\n    a();\n    b();\n}\n";
+
+        // when
+        let annotations = file_annotations(&review, &[], &CommentTypes::default(), RUST);
+        let file = AnnotatedFile::new(Path::new("src/lib.rs"), content, annotations.markers);
+
+        // then
+        assert_eq!(
+            file.content(),
+            "fn main() {\n    \
+             // REVIEW[tuicr](ISSUE): Use a constant\n    \
+             let x = 42;\n    \
+             // REVIEW[tuicr](SUGGESTION) on lines 3-4: Extract this\n    \
+             // REVIEW[tuicr]: into a helper\n    \
+             a();\n    b();\n}\n"
+        );
+        assert_eq!(file.marker_count(), 3);
+    }
+
+    #[test]
+    fn should_place_deleted_line_markers_where_the_lines_were() {
+        // given
+        let hunks = vec![DiffHunk {
+            header: "@@ -1,3 +1,2 @@".to_string(),
+            lines: vec![
+                diff_line(LineOrigin::Context, Some(1), Some(1)),
+                diff_line(LineOrigin::Deletion, Some(2), None),
+                diff_line(LineOrigin::Context, Some(3), Some(2)),
+            ],
+            old_start: 1,
+            old_count: 3,
+            new_start: 1,
+            new_count: 2,
+        }];
+        let mut review = FileReview::new(PathBuf::from("a.py"), FileStatus::Modified);
+        review.add_line_comment(
+            2,
+            Comment::new(
+                "Still needed".to_string(),
+                CommentType::new("note"),
+                Some(LineSide::Old),
+            ),
+        );
+        let python = CommentSyntax::for_syntax("Python").unwrap();
+
+        // when
+        let annotations = file_annotations(&review, &hunks, &CommentTypes::default(), python);
+        let file = AnnotatedFile::new(Path::new("a.py"), "a\nc\n", annotations.markers);
+
+        // then
+        assert_eq!(
+            file.content(),
+            "a\n# REVIEW[tuicr](NOTE) on removed line 2: Still needed\nc\n"
+        );
+    }
+
+    #[test]
+    fn should_count_deleted_line_comments_outside_the_diff() {
+        // given
+        let mut review = FileReview::new(PathBuf::from("a.py"), FileStatus::Modified);
+        review.add_line_comment(
+            7,
+            Comment::new(
+                "Gone".to_string(),
+                CommentType::new("note"),
+                Some(LineSide::Old),
+            ),
+        );
+        let python = CommentSyntax::for_syntax("Python").unwrap();
+
+        // when
+        let annotations = file_annotations(&review, &[], &CommentTypes::default(), python);
+
+        // then
+        assert!(annotations.markers.is_empty());
+        assert_eq!(annotations.unplaced, 1);
+    }
+
+    #[test]
+    fn should_keep_a_shebang_on_the_first_line() {
+        // given
+        let mut review = FileReview::new(PathBuf::from("run.sh"), FileStatus::Modified);
+        review.add_file_comment(Comment::new(
+            "Quote the variables".to_string(),
+            CommentType::new("issue"),
+            None,
+        ));
+        let shell = CommentSyntax::for_syntax("Bourne Again Shell (bash)").unwrap();
+
+        // when
+        let annotations = file_annotations(&review, &[], &CommentTypes::default(), shell);
+        let file = AnnotatedFile::new(
+            Path::new("run.sh"),
+            "#!/bin/sh
+echo $1
+",
+            annotations.markers,
+        );
+
+        // then
+        assert_eq!(
+            file.content(),
+            "#!/bin/sh
+# REVIEW[tuicr](ISSUE): Quote the variables
+echo $1
+"
+        );
+    }
+
+    #[test]
+    fn should_keep_an_xml_declaration_on_the_first_line() {
+        // given
+        let mut review = FileReview::new(PathBuf::from("pom.xml"), FileStatus::Modified);
+        review.add_file_comment(Comment::new(
+            "Bump the version".to_string(),
+            CommentType::new("note"),
+            None,
+        ));
+        let xml = CommentSyntax::for_syntax("XML").unwrap();
+        let content = "<?xml version=\"1.0\"?>\n<project/>\n";
+
+        // when
+        let annotations = file_annotations(&review, &[], &CommentTypes::default(), xml);
+        let file = AnnotatedFile::new(Path::new("pom.xml"), content, annotations.markers);
+
+        // then
+        assert_eq!(
+            file.content(),
+            "<?xml version=\"1.0\"?>\n<!-- REVIEW[tuicr](NOTE): Bump the version -->\n<project/>\n"
+        );
+    }
+
+    #[test]
+    fn should_annotate_above_rust_inner_attributes() {
+        // given
+        let annotations = vec![(0, "// REVIEW[tuicr](NOTE): top".to_string())];
+
+        // when
+        let file = AnnotatedFile::new(Path::new("lib.rs"), "#![allow(dead_code)]\n", annotations);
+
+        // then
+        assert_eq!(
+            file.content(),
+            "// REVIEW[tuicr](NOTE): top\n#![allow(dead_code)]\n"
+        );
+    }
+
+    #[test]
+    fn should_generate_a_patch_that_only_adds_markers() {
+        // given
+        let content = (1..=10).map(|n| format!("line {n}\n")).collect::<String>();
+        let annotations = vec![
+            (0, "// REVIEW[tuicr](NOTE): first".to_string()),
+            (9, "// REVIEW[tuicr](NOTE): last".to_string()),
+        ];
+
+        // when
+        let file = AnnotatedFile::new(Path::new("f.rs"), &content, annotations);
+
+        // then
+        assert_eq!(
+            file.diff(),
+            "--- a/f.rs\n+++ b/f.rs\n\
+             @@ -1,3 +1,4 @@\n+// REVIEW[tuicr](NOTE): first\n line 1\n line 2\n line 3\n\
+             @@ -7,4 +8,5 @@\n line 7\n line 8\n line 9\n+// REVIEW[tuicr](NOTE): last\n line 10\n"
+        );
+    }
+
+    #[test]
+    fn should_keep_a_missing_final_newline() {
+        // given
+        let annotations = vec![(5, "# REVIEW[tuicr](NOTE): past the end".to_string())];
+
+        // when
+        let file = AnnotatedFile::new(Path::new("f.py"), "a\nb", annotations);
+
+        // then
+        assert_eq!(file.content(), "a\n# REVIEW[tuicr](NOTE): past the end\nb");
+        assert!(file.diff().ends_with(" b\n\\ No newline at end of file\n"));
+    }
+
+    #[test]
+    fn should_strip_only_marker_lines() {
+        // given
+        let html = CommentSyntax::for_syntax("HTML").unwrap();
+        let content = "<div>\n  <!-- REVIEW[tuicr](ISSUE): Missing alt -->\n  <img>\n  <!-- REVIEW notes -->\n</div>";
+
+        // when
+        let stripped = strip_markers(content, html);
+
+        // then
+        assert_eq!(
+            stripped.as_deref(),
+            Some("<div>\n  <img>\n  <!-- REVIEW notes -->\n</div>")
+        );
+        assert_eq!(strip_markers("<div></div>\n", html), None);
+    }
+
+    #[test]
+    fn should_leave_the_developers_own_review_comments() {
+        // given
+        let content = "// REVIEW: check with the API team\n// REVIEW(jane): later\n\
+                       // REVIEW[tuicr](NOTE): generated\nfn main() {}\n";
+
+        // when
+        let stripped = strip_markers(content, RUST);
+
+        // then
+        assert_eq!(
+            stripped.as_deref(),
+            Some("// REVIEW: check with the API team\n// REVIEW(jane): later\nfn main() {}\n")
+        );
+    }
+
+    #[test]
+    fn should_keep_crlf_line_endings_through_annotating_and_stripping() {
+        // given
+        let content = "fn main() {\r\n    run();\r\n}";
+        let annotations = vec![
+            (1, "// REVIEW[tuicr](NOTE): check".to_string()),
+            (5, "// REVIEW[tuicr](NOTE): end".to_string()),
+        ];
+
+        // when
+        let file = AnnotatedFile::new(Path::new("f.rs"), content, annotations);
+
+        // then
+        assert_eq!(
+            file.content(),
+            "fn main() {\r\n    // REVIEW[tuicr](NOTE): check\r\n    run();\r\n\
+             // REVIEW[tuicr](NOTE): end\r\n}"
+        );
+        assert!(
+            file.diff()
+                .contains("+    // REVIEW[tuicr](NOTE): check\r\n     run();\r\n")
+        );
+        assert_eq!(
+            strip_markers(&file.content(), RUST).as_deref(),
+            Some(content)
+        );
+    }
+
+    #[test]
+    fn should_break_up_comment_tokens_in_block_comments() {
+        // given
+        let html = CommentSyntax::for_syntax("HTML").unwrap();
+        let css = CommentSyntax::for_syntax("CSS").unwrap();
+
+        // when
+        let html_comment = html.comment("REVIEW[tuicr](NOTE): drop <!-- old --> here");
+        let css_comment = css.comment("REVIEW[tuicr](NOTE): end with */ or /* nest");
+
+        // then
+        assert_eq!(
+            html_comment,
+            "<!-- REVIEW[tuicr](NOTE): drop <!- - old -- > here -->"
+        );
+        assert_eq!(
+            css_comment,
+            "/* REVIEW[tuicr](NOTE): end with * / or / * nest */"
+        );
+    }
+}
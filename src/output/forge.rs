@@ -0,0 +1,462 @@
+//! Export of a review as request bodies for GitHub's and GitLab's review
+//! APIs, to post it on the pull or merge request with `gh api` / `glab api`.
+//!
+//! Only line comments can be anchored in the diff; file comments and
+//! comments on commit messages go into the review body on GitHub and into
+//! discussions without a position on GitLab.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use super::sorted_comments;
+use crate::error::{Result, TuicrError};
+use crate::model::{
+    Comment, CommentTypeDef, CommentTypes, DiffFile, LineOrigin, LineRange, LineSide,
+    ReviewSession, commit_message_id,
+};
+
+/// Generate the body of a `POST /repos/{owner}/{repo}/pulls/{number}/reviews`
+/// request, or an error if there are no comments. Comments are placed on the
+/// diff of `head_commit`, the newest reviewed commit, when commits are
+/// reviewed.
+pub fn generate_github_review(
+    session: &ReviewSession,
+    head_commit: Option<&str>,
+    comment_types: &CommentTypes,
+) -> Result<String> {
+    if !session.has_comments() {
+        return Err(TuicrError::NoComments);
+    }
+
+    let mut comments = Vec::new();
+    let mut unanchored = Vec::new();
+    for (path, line, comment) in sorted_comments(session) {
+        let def = comment_types.resolve(&comment.comment_type);
+        match anchor(path, line, comment) {
+            Some(range) => {
+                let side = github_side(comment.side);
+                let mut review_comment = json!({
+                    "path": path.to_string_lossy(),
+                    "body": comment_body(comment, &def),
+                    "line": range.end,
+                    "side": side,
+                });
+                if !range.is_single() {
+                    review_comment["start_line"] = range.start.into();
+                    review_comment["start_side"] = side.into();
+                }
+                comments.push(review_comment);
+            }
+            None => unanchored.push(format!(
+                "- {} `{}` - {}",
+                label(&def),
+                location(path, line, comment),
+                one_line(comment)
+            )),
+        }
+    }
+
+    let body: Vec<String> = session
+        .session_notes
+        .iter()
+        .cloned()
+        .chain((!unanchored.is_empty()).then(|| unanchored.join("\n")))
+        .collect();
+    let mut review = json!({
+        "body": body.join("\n\n"),
+        "event": "COMMENT",
+        "comments": comments,
+    });
+    // Without a commit GitHub uses the pull request's latest one
+    if let Some(head) = head_commit {
+        review["commit_id"] = head.into();
+    }
+    Ok(serde_json::to_string_pretty(&review)?)
+}
+
+/// The commits a GitLab position is relative to: the merge request's
+/// `diff_refs`, here the old side and the tip of the reviewed commits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffRefs {
+    pub base_sha: String,
+    pub start_sha: String,
+    pub head_sha: String,
+}
+
+/// Generate a JSON array whose elements are each the body of one
+/// `POST /projects/{id}/merge_requests/{iid}/discussions` request, or an
+/// error if there are no comments. The API takes one discussion per request,
+/// so the elements are posted one at a time.
+///
+/// `diff_files` tell context lines, which GitLab positions on both sides,
+/// from added and deleted lines. Positions carry `diff_refs` when given;
+/// without them (reviews of the working tree) GitLab rejects positioned
+/// discussions until they are added.
+pub fn generate_gitlab_discussions(
+    session: &ReviewSession,
+    diff_files: &[DiffFile],
+    diff_refs: Option<&DiffRefs>,
+    comment_types: &CommentTypes,
+) -> Result<String> {
+    if !session.has_comments() {
+        return Err(TuicrError::NoComments);
+    }
+
+    let mut discussions = Vec::new();
+    if let Some(notes) = &session.session_notes {
+        discussions.push(json!({ "body": notes }));
+    }
+    for (path, line, comment) in sorted_comments(session) {
+        let def = comment_types.resolve(&comment.comment_type);
+        let discussion = match anchor(path, line, comment) {
+            Some(range) => {
+                // GitLab anchors a discussion on one line; name the others
+                let mut body = String::new();
+                if !range.is_single() {
+                    let _ = write!(body, "Lines {}-{}: ", range.start, range.end);
+                }
+                body.push_str(&comment_body(comment, &def));
+                json!({
+                    "body": body,
+                    "position": gitlab_position(path, range.end, comment.side, diff_files, diff_refs),
+                })
+            }
+            None => {
+                let location = location(path, line, comment);
+                json!({ "body": format!("`{location}`: {}", comment_body(comment, &def)) })
+            }
+        };
+        discussions.push(discussion);
+    }
+    Ok(serde_json::to_string_pretty(&discussions)?)
+}
+
+/// Position of a discussion on `line` of `path`. A context line carries
+/// both its `old_line` and `new_line`, an added or deleted line only the
+/// one of its side.
+fn gitlab_position(
+    path: &Path,
+    line: u32,
+    side: Option<LineSide>,
+    diff_files: &[DiffFile],
+    diff_refs: Option<&DiffRefs>,
+) -> Value {
+    let side = side.unwrap_or_default();
+    let file = diff_files.iter().find(|file| file.display_path() == path);
+    let diff_line = file
+        .into_iter()
+        .flat_map(|file| &file.hunks)
+        .flat_map(|hunk| &hunk.lines)
+        .find(|diff_line| match side {
+            LineSide::Old => diff_line.old_lineno == Some(line),
+            LineSide::New => diff_line.new_lineno == Some(line),
+        });
+    let (old_line, new_line) = match (side, diff_line) {
+        (_, Some(diff_line)) if diff_line.origin == LineOrigin::Context => {
+            (diff_line.old_lineno, diff_line.new_lineno)
+        }
+        (LineSide::Old, _) => (Some(line), None),
+        (LineSide::New, _) => (None, Some(line)),
+    };
+    let old_path = file
+        .and_then(|file| file.old_path.as_deref())
+        .unwrap_or(path);
+
+    let mut position = json!({
+        "position_type": "text",
+        "old_path": old_path.to_string_lossy(),
+        "new_path": path.to_string_lossy(),
+    });
+    if let Some(old_line) = old_line {
+        position["old_line"] = old_line.into();
+    }
+    if let Some(new_line) = new_line {
+        position["new_line"] = new_line.into();
+    }
+    if let Some(refs) = diff_refs {
+        position["base_sha"] = refs.base_sha.clone().into();
+        position["start_sha"] = refs.start_sha.clone().into();
+        position["head_sha"] = refs.head_sha.clone().into();
+    }
+    position
+}
+
+/// Lines a comment can be anchored on in the diff; `None` for file comments
+/// and comments on commit messages, which are not part of the diff
+fn anchor(path: &Path, line: Option<u32>, comment: &Comment) -> Option<LineRange> {
+    if commit_message_id(path).is_some() {
+        return None;
+    }
+    comment.line_range.or(line.map(LineRange::single))
+}
+
+fn github_side(side: Option<LineSide>) -> &'static str {
+    match side.unwrap_or_default() {
+        LineSide::Old => "LEFT",
+        LineSide::New => "RIGHT",
+    }
+}
+
+/// `**[ISSUE]** (blocking)`, as in the Markdown export
+fn label(def: &CommentTypeDef) -> String {
+    if def.blocking {
+        format!("**[{}]** (blocking)", def.label())
+    } else {
+        format!("**[{}]**", def.label())
+    }
+}
+
+/// Comment in Markdown, with its author on a line of its own since the API
+/// posts every comment as the token's user
+fn comment_body(comment: &Comment, def: &CommentTypeDef) -> String {
+    let mut body = format!("{} {}", label(def), comment.content);
+    if let Some(author) = &comment.author {
+        let _ = write!(body, "\n\n— {author}");
+    }
+    body
+}
+
+/// Comment as a list item of the review body
+fn one_line(comment: &Comment) -> String {
+    let content = comment.content.replace('\n', " ");
+    match &comment.author {
+        Some(author) => format!("{content} ({author})"),
+        None => content,
+    }
+}
+
+fn location(path: &Path, line: Option<u32>, comment: &Comment) -> String {
+    let path = path.display();
+    match comment.line_range.or(line.map(LineRange::single)) {
+        Some(range) if range.is_single() => format!("{path}:{}", range.start),
+        Some(range) => format!("{path}:{}-{}", range.start, range.end),
+        None => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::diff_types::commit_message_path;
+    use crate::model::{CommentType, DiffHunk, DiffLine, FileStatus, SessionDiffSource};
+    use serde_json::Value;
+    use std::path::PathBuf;
+
+    fn create_test_session() -> ReviewSession {
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/test-repo"),
+            "abc1234def".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::CommitRange,
+        );
+        session.session_notes = Some("Looks good overall".to_string());
+        session.add_file(PathBuf::from("src/main.rs"), FileStatus::Modified);
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.add_file_comment(Comment::new(
+            "Consider adding documentation".to_string(),
+            CommentType::new("suggestion"),
+            None,
+        ));
+        review.add_line_comment(
+            12,
+            Comment::new(
+                "Dead code".to_string(),
+                CommentType::new("note"),
+                Some(LineSide::Old),
+            ),
+        );
+        review.add_line_comment(
+            42,
+            Comment::new_with_range(
+                "Magic number should be a constant".to_string(),
+                CommentType::new("issue"),
+                Some(LineSide::New),
+                LineRange::new(40, 42),
+            )
+            .with_author(Some("Jane Doe".to_string())),
+        );
+        session
+    }
+
+    /// Diff of src/main.rs deleting old line 12 and changing new line 41,
+    /// so new line 42 (old line 42) is context
+    fn diff_files(old_path: PathBuf) -> Vec<DiffFile> {
+        let line = |origin, old_lineno, new_lineno| DiffLine {
+            origin,
+            content: String::new(),
+            old_lineno,
+            new_lineno,
+            highlighted_spans: None,
+        };
+        vec![DiffFile {
+            old_path: Some(old_path),
+            new_path: Some(PathBuf::from("src/main.rs")),
+            status: FileStatus::Modified,
+            hunks: vec![
+                DiffHunk {
+                    header: "@@ -12,1 +11,0 @@".to_string(),
+                    lines: vec![line(LineOrigin::Deletion, Some(12), None)],
+                    old_start: 12,
+                    old_count: 1,
+                    new_start: 11,
+                    new_count: 0,
+                },
+                DiffHunk {
+                    header: "@@ -41,2 +41,2 @@".to_string(),
+                    lines: vec![
+                        line(LineOrigin::Deletion, Some(41), None),
+                        line(LineOrigin::Addition, None, Some(41)),
+                        line(LineOrigin::Context, Some(42), Some(42)),
+                    ],
+                    old_start: 41,
+                    old_count: 2,
+                    new_start: 41,
+                    new_count: 2,
+                },
+            ],
+            is_binary: false,
+        }]
+    }
+
+    #[test]
+    fn should_anchor_github_review_comments_on_their_lines() {
+        // given
+        let session = create_test_session();
+
+        // when
+        let review = generate_github_review(&session, Some("bbb222"), &CommentTypes::default());
+
+        // then
+        let review: Value = serde_json::from_str(&review.unwrap()).unwrap();
+        assert_eq!(review["event"], "COMMENT");
+        assert_eq!(review["commit_id"], "bbb222");
+        let comments = review["comments"].as_array().unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(
+            comments[0],
+            json!({
+                "path": "src/main.rs",
+                "body": "**[NOTE]** Dead code",
+                "line": 12,
+                "side": "LEFT",
+            })
+        );
+        assert_eq!(comments[1]["line"], 42);
+        assert_eq!(comments[1]["start_line"], 40);
+        assert_eq!(comments[1]["side"], "RIGHT");
+        assert_eq!(comments[1]["start_side"], "RIGHT");
+        assert_eq!(
+            comments[1]["body"],
+            "**[ISSUE]** Magic number should be a constant\n\n— Jane Doe"
+        );
+    }
+
+    #[test]
+    fn should_put_summary_and_file_comments_in_github_review_body() {
+        // given
+        let mut session = create_test_session();
+        let path = commit_message_path("abc1234");
+        session.add_file(path.clone(), FileStatus::Modified);
+        session.get_file_mut(&path).unwrap().add_line_comment(
+            1,
+            Comment::new(
+                "Use the imperative mood".to_string(),
+                CommentType::new("suggestion"),
+                None,
+            ),
+        );
+
+        // when
+        let review = generate_github_review(&session, None, &CommentTypes::default());
+
+        // then
+        let review: Value = serde_json::from_str(&review.unwrap()).unwrap();
+        assert_eq!(
+            review["body"],
+            format!(
+                "Looks good overall\n\n\
+                 - **[SUGGESTION]** `{}:1` - Use the imperative mood\n\
+                 - **[SUGGESTION]** `src/main.rs` - Consider adding documentation",
+                path.display()
+            )
+        );
+        assert!(review.get("commit_id").is_none());
+    }
+
+    #[test]
+    fn should_position_gitlab_discussions_on_their_lines() {
+        // given
+        let session = create_test_session();
+
+        // when
+        let discussions = generate_gitlab_discussions(
+            &session,
+            &diff_files(PathBuf::from("src/main.rs")),
+            None,
+            &CommentTypes::default(),
+        );
+
+        // then
+        let discussions: Value = serde_json::from_str(&discussions.unwrap()).unwrap();
+        let discussions = discussions.as_array().unwrap();
+        assert_eq!(discussions.len(), 4);
+        assert_eq!(discussions[0], json!({ "body": "Looks good overall" }));
+        assert_eq!(
+            discussions[1],
+            json!({ "body": "`src/main.rs`: **[SUGGESTION]** Consider adding documentation" })
+        );
+        assert_eq!(
+            discussions[2]["position"],
+            json!({
+                "position_type": "text",
+                "old_path": "src/main.rs",
+                "new_path": "src/main.rs",
+                "old_line": 12,
+            })
+        );
+        assert_eq!(discussions[3]["position"]["old_line"], 42);
+        assert_eq!(discussions[3]["position"]["new_line"], 42);
+        assert!(
+            discussions[3]["body"]
+                .as_str()
+                .unwrap()
+                .starts_with("Lines 40-42: **[ISSUE]**")
+        );
+    }
+
+    #[test]
+    fn should_give_gitlab_positions_the_diff_refs_and_old_path() {
+        // given
+        let session = create_test_session();
+        let refs = DiffRefs {
+            base_sha: "base000".to_string(),
+            start_sha: "base000".to_string(),
+            head_sha: "bbb222".to_string(),
+        };
+
+        // when
+        let discussions = generate_gitlab_discussions(
+            &session,
+            &diff_files(PathBuf::from("src/old_main.rs")),
+            Some(&refs),
+            &CommentTypes::default(),
+        );
+
+        // then
+        let discussions: Value = serde_json::from_str(&discussions.unwrap()).unwrap();
+        assert_eq!(
+            discussions[2]["position"],
+            json!({
+                "position_type": "text",
+                "old_path": "src/old_main.rs",
+                "new_path": "src/main.rs",
+                "old_line": 12,
+                "base_sha": "base000",
+                "start_sha": "base000",
+                "head_sha": "bbb222",
+            })
+        );
+    }
+}
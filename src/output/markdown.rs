@@ -1,19 +1,24 @@
+use std::borrow::Cow;
 use std::fmt::Write;
 use std::io::Write as IoWrite;
+use std::path::Path;
 
 use arboard::Clipboard;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 
 use crate::app::DiffSource;
 use crate::error::{Result, TuicrError};
-use crate::model::{LineRange, LineSide, ReviewSession};
+use crate::model::{
+    CommentTypeDef, CommentTypes, LineRange, LineSide, ReviewSession, commit_message_id,
+};
 
-/// (file_path, line_range, side, comment_type, content)
+/// (file_path, line_range, side, comment_type, author, content)
 type CommentEntry<'a> = (
     String,
     Option<LineRange>,
     Option<LineSide>,
-    &'a str,
+    Cow<'a, CommentTypeDef>,
+    Option<&'a str>,
     &'a str,
 );
 
@@ -22,28 +27,28 @@ type CommentEntry<'a> = (
 pub fn generate_export_content(
     session: &ReviewSession,
     diff_source: &DiffSource,
+    comment_types: &CommentTypes,
 ) -> Result<String> {
     if !session.has_comments() {
         return Err(TuicrError::NoComments);
     }
-    Ok(generate_markdown(session, diff_source))
+    Ok(generate_markdown(session, diff_source, comment_types))
 }
 
-pub fn export_to_clipboard(session: &ReviewSession, diff_source: &DiffSource) -> Result<String> {
-    let content = generate_export_content(session, diff_source)?;
-
+/// Copy exported `content` to the clipboard, returning the message to show
+pub fn copy_to_clipboard(content: &str) -> Result<String> {
     // Prefer OSC 52 in tmux/SSH where arboard may silently fail
     if should_prefer_osc52() {
-        copy_osc52(&content)?;
+        copy_osc52(content)?;
         return Ok("Review copied to clipboard (via terminal)".to_string());
     }
 
     // Try arboard (system clipboard) first, fall back to OSC 52 for SSH/remote sessions
-    match Clipboard::new().and_then(|mut cb| cb.set_text(&content)) {
+    match Clipboard::new().and_then(|mut cb| cb.set_text(content)) {
         Ok(_) => Ok("Review copied to clipboard".to_string()),
         Err(_) => {
             // Fall back to OSC 52 escape sequence (works over SSH)
-            copy_osc52(&content)?;
+            copy_osc52(content)?;
             Ok("Review copied to clipboard (via terminal)".to_string())
         }
     }
@@ -76,7 +81,11 @@ fn write_osc52<W: IoWrite>(writer: &mut W, text: &str) -> Result<()> {
     Ok(())
 }
 
-fn generate_markdown(session: &ReviewSession, diff_source: &DiffSource) -> String {
+fn generate_markdown(
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    comment_types: &CommentTypes,
+) -> String {
     let mut md = String::new();
 
     // Intro for agents
@@ -113,10 +122,7 @@ fn generate_markdown(session: &ReviewSession, diff_source: &DiffSource) -> Strin
         }
     }
 
-    let _ = writeln!(
-        md,
-        "Comment types: ISSUE (problems to fix), SUGGESTION (improvements), NOTE (observations), PRAISE (positive feedback)"
-    );
+    let _ = writeln!(md, "Comment types: {}", comment_types.legend());
     let _ = writeln!(md);
 
     // Session notes/summary
@@ -128,9 +134,14 @@ fn generate_markdown(session: &ReviewSession, diff_source: &DiffSource) -> Strin
     // Collect all comments into a flat list
     let mut all_comments: Vec<CommentEntry> = Vec::new();
 
-    // Sort files by path for consistent output
+    // Sort files by path for consistent output, commit messages first
     let mut files: Vec<_> = session.files.iter().collect();
-    files.sort_by_key(|(path, _)| path.to_string_lossy().to_string());
+    files.sort_by_key(|(path, _)| {
+        (
+            commit_message_id(path).is_none(),
+            path.to_string_lossy().to_string(),
+        )
+    });
 
     for (path, review) in files {
         let path_str = path.display().to_string();
@@ -141,7 +152,8 @@ fn generate_markdown(session: &ReviewSession, diff_source: &DiffSource) -> Strin
                 path_str.clone(),
                 None,
                 None,
-                comment.comment_type.as_str(),
+                comment_types.resolve(&comment.comment_type),
+                comment.author.as_deref(),
                 &comment.content,
             ));
         }
@@ -160,15 +172,26 @@ fn generate_markdown(session: &ReviewSession, diff_source: &DiffSource) -> Strin
                     path_str.clone(),
                     line_range,
                     comment.side,
-                    comment.comment_type.as_str(),
+                    comment_types.resolve(&comment.comment_type),
+                    comment.author.as_deref(),
                     &comment.content,
                 ));
             }
         }
     }
 
+    // Highest priority first; the sort is stable so files keep their order
+    all_comments.sort_by_key(|(file, _, _, comment_type, _, _)| {
+        (
+            commit_message_id(Path::new(file)).is_none(),
+            comment_type.priority,
+        )
+    });
+
     // Output numbered list
-    for (i, (file, line_range, side, comment_type, content)) in all_comments.iter().enumerate() {
+    for (i, (file, line_range, side, comment_type, author, content)) in
+        all_comments.iter().enumerate()
+    {
         let location = match (line_range, side) {
             // Range on deleted side (old lines)
             (Some(range), Some(LineSide::Old)) if range.is_single() => {
@@ -187,12 +210,20 @@ fn generate_markdown(session: &ReviewSession, diff_source: &DiffSource) -> Strin
             // File comment
             (None, _) => format!("`{file}`"),
         };
+        let blocking = if comment_type.blocking {
+            " (blocking)"
+        } else {
+            ""
+        };
+        let author = author.map(|name| format!(" ({name})")).unwrap_or_default();
         let _ = writeln!(
             md,
-            "{}. **[{}]** {} - {}",
+            "{}. **[{}]**{} {}{} - {}",
             i + 1,
-            comment_type,
+            comment_type.label(),
+            blocking,
             location,
+            author,
             content
         );
     }
@@ -203,7 +234,10 @@ fn generate_markdown(session: &ReviewSession, diff_source: &DiffSource) -> Strin
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{Comment, CommentType, FileStatus, LineRange, LineSide, SessionDiffSource};
+    use crate::model::diff_types::commit_message_path;
+    use crate::model::{
+        Comment, CommentType, CommentTypeDef, FileStatus, LineRange, LineSide, SessionDiffSource,
+    };
     use std::path::PathBuf;
 
     fn create_test_session() -> ReviewSession {
@@ -220,14 +254,14 @@ mod tests {
             review.reviewed = true;
             review.add_file_comment(Comment::new(
                 "Consider adding documentation".to_string(),
-                CommentType::Suggestion,
+                CommentType::new("suggestion"),
                 None,
             ));
             review.add_line_comment(
                 42,
                 Comment::new(
                     "Magic number should be a constant".to_string(),
-                    CommentType::Issue,
+                    CommentType::new("issue"),
                     Some(LineSide::New),
                 ),
             );
@@ -243,7 +277,7 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source);
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
 
         // then
         assert!(markdown.contains("I reviewed your code and have the following comments"));
@@ -263,31 +297,12 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source);
-
-        // then
-        // Should have 2 numbered comments
-        assert!(markdown.contains("1. **[SUGGESTION]**"));
-        assert!(markdown.contains("2. **[ISSUE]**"));
-    }
-
-    #[test]
-    fn should_fail_export_when_no_comments() {
-        // given
-        let session = ReviewSession::new(
-            PathBuf::from("/tmp/test-repo"),
-            "abc1234def".to_string(),
-            Some("main".to_string()),
-            SessionDiffSource::WorkingTree,
-        );
-        let diff_source = DiffSource::WorkingTree;
-
-        // when
-        let result = export_to_clipboard(&session, &diff_source);
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
 
         // then
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), TuicrError::NoComments));
+        // Should have 2 numbered comments, issues before suggestions
+        assert!(markdown.contains("1. **[ISSUE]**"));
+        assert!(markdown.contains("2. **[SUGGESTION]**"));
     }
 
     #[test]
@@ -297,7 +312,7 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let result = generate_export_content(&session, &diff_source);
+        let result = generate_export_content(&session, &diff_source, &CommentTypes::default());
 
         // then
         assert!(result.is_ok());
@@ -319,7 +334,7 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let result = generate_export_content(&session, &diff_source);
+        let result = generate_export_content(&session, &diff_source, &CommentTypes::default());
 
         // then
         assert!(result.is_err());
@@ -336,7 +351,7 @@ mod tests {
         ]);
 
         // when
-        let markdown = generate_markdown(&session, &diff_source);
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
 
         // then
         assert!(markdown.contains("Reviewing commits: abc1234, def4567"));
@@ -349,7 +364,7 @@ mod tests {
         let diff_source = DiffSource::CommitRange(vec!["abc1234567890".to_string()]);
 
         // when
-        let markdown = generate_markdown(&session, &diff_source);
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
 
         // then
         assert!(markdown.contains("Reviewing commit: abc1234"));
@@ -410,7 +425,7 @@ mod tests {
         // given - simulate what would be copied during export
         let session = create_test_session();
         let diff_source = DiffSource::WorkingTree;
-        let markdown = generate_markdown(&session, &diff_source);
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
         let mut buffer: Vec<u8> = Vec::new();
 
         // when
@@ -426,6 +441,31 @@ mod tests {
         assert_eq!(decoded, markdown);
     }
 
+    #[test]
+    fn should_name_the_author_of_attributed_comments() {
+        // given
+        let mut session = create_test_session();
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.add_line_comment(
+            7,
+            Comment::new_with_range(
+                "Off by one".to_string(),
+                CommentType::new("issue"),
+                Some(LineSide::New),
+                LineRange::single(7),
+            )
+            .with_author(Some("Jane Doe".to_string())),
+        );
+
+        // when
+        let markdown =
+            generate_markdown(&session, &DiffSource::WorkingTree, &CommentTypes::default());
+
+        // then
+        assert!(markdown.contains("`src/main.rs:7` (Jane Doe) - Off by one"));
+        assert!(!markdown.contains("() -"));
+    }
+
     #[test]
     fn should_export_single_line_range_as_single_line() {
         // given - a comment with a single-line range should display as L42, not L42-L42
@@ -443,7 +483,7 @@ mod tests {
                 42,
                 Comment::new_with_range(
                     "Single line comment".to_string(),
-                    CommentType::Note,
+                    CommentType::new("note"),
                     Some(LineSide::New),
                     range,
                 ),
@@ -452,7 +492,7 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source);
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
 
         // then
         assert!(markdown.contains("`src/main.rs:42`"));
@@ -476,7 +516,7 @@ mod tests {
                 15, // keyed by end line
                 Comment::new_with_range(
                     "Multi-line comment".to_string(),
-                    CommentType::Issue,
+                    CommentType::new("issue"),
                     Some(LineSide::New),
                     range,
                 ),
@@ -485,7 +525,7 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source);
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
 
         // then
         assert!(markdown.contains("`src/main.rs:10-15`"));
@@ -509,7 +549,7 @@ mod tests {
                 25, // keyed by end line
                 Comment::new_with_range(
                     "Deleted lines comment".to_string(),
-                    CommentType::Suggestion,
+                    CommentType::new("suggestion"),
                     Some(LineSide::Old),
                     range,
                 ),
@@ -518,7 +558,7 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source);
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
 
         // then
         assert!(markdown.contains("`src/main.rs:~20-~25`"));
@@ -541,7 +581,7 @@ mod tests {
                 30,
                 Comment::new_with_range(
                     "Single deleted line".to_string(),
-                    CommentType::Note,
+                    CommentType::new("note"),
                     Some(LineSide::Old),
                     range,
                 ),
@@ -550,7 +590,7 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source);
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
 
         // then
         assert!(markdown.contains("`src/main.rs:~30`"));
@@ -574,7 +614,7 @@ mod tests {
                 50,
                 Comment::new(
                     "Old style comment".to_string(),
-                    CommentType::Note,
+                    CommentType::new("note"),
                     Some(LineSide::New),
                 ),
             );
@@ -582,9 +622,67 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source);
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
 
         // then
         assert!(markdown.contains("`src/main.rs:50`"));
     }
+
+    #[test]
+    fn should_target_commit_message_comments_first() {
+        // given
+        let mut session = create_test_session();
+        let message_path = commit_message_path("abc1234");
+        session.add_file(message_path.clone(), FileStatus::Added);
+        if let Some(review) = session.get_file_mut(&message_path) {
+            review.add_line_comment(
+                5,
+                Comment::new(
+                    "Summary should use imperative mood".to_string(),
+                    CommentType::new("suggestion"),
+                    Some(LineSide::New),
+                ),
+            );
+        }
+        let diff_source = DiffSource::CommitRange(vec!["abc1234def".to_string()]);
+
+        // when
+        let markdown = generate_markdown(&session, &diff_source, &CommentTypes::default());
+
+        // then
+        assert!(markdown.contains(
+            "1. **[SUGGESTION]** `commit abc1234 message:5` - Summary should use imperative mood"
+        ));
+        assert!(markdown.contains("2. **[ISSUE]** `src/main.rs:42`"));
+    }
+
+    #[test]
+    fn should_order_and_label_comments_by_configured_types() {
+        // given
+        let mut session = create_test_session();
+        if let Some(review) = session.get_file_mut(&PathBuf::from("src/main.rs")) {
+            review.add_file_comment(Comment::new(
+                "SQL injection".to_string(),
+                CommentType::new("security"),
+                None,
+            ));
+        }
+        let mut security = CommentTypeDef::new("Security", "vulnerabilities", 0);
+        security.blocking = true;
+        let comment_types = CommentTypes::new(vec![
+            CommentTypeDef::new("Suggestion", "improvements", 1),
+            security,
+        ]);
+
+        // when
+        let markdown = generate_markdown(&session, &DiffSource::WorkingTree, &comment_types);
+
+        // then
+        assert!(markdown.contains(
+            "Comment types: SECURITY (vulnerabilities, blocking), SUGGESTION (improvements)"
+        ));
+        assert!(markdown.contains("1. **[SECURITY]** (blocking) `src/main.rs` - SQL injection"));
+        assert!(markdown.contains("2. **[ISSUE]** `src/main.rs:42`"));
+        assert!(markdown.contains("3. **[SUGGESTION]** `src/main.rs`"));
+    }
 }
@@ -1,3 +1,116 @@
+pub mod annotate;
+pub mod forge;
 pub mod markdown;
+pub mod sarif;
 
-pub use markdown::{export_to_clipboard, generate_export_content};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+pub use markdown::{copy_to_clipboard, generate_export_content};
+
+use crate::model::{Comment, ReviewSession, commit_message_id};
+
+/// Format a review is exported in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Markdown for pasting into an agent or a PR
+    #[default]
+    Markdown,
+    /// SARIF 2.1.0 for code-scanning tools
+    Sarif,
+    /// Body of GitHub's create-a-review API request
+    GitHub,
+    /// JSON array of the bodies of GitLab's create-a-discussion API
+    /// requests, one request per element
+    GitLab,
+    /// Unified diff inserting the comments into the reviewed files as code
+    /// comments
+    Patch,
+}
+
+impl ExportFormat {
+    pub const VALID_VALUES: &str = "markdown, sarif, github, gitlab, patch";
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Markdown => "Markdown",
+            Self::Sarif => "SARIF",
+            Self::GitHub => "GitHub review",
+            Self::GitLab => "GitLab discussions",
+            Self::Patch => "annotated patch",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "sarif" => Ok(Self::Sarif),
+            "github" => Ok(Self::GitHub),
+            "gitlab" => Ok(Self::GitLab),
+            "patch" => Ok(Self::Patch),
+            _ => Err(format!(
+                "Unknown export format '{s}'. Valid formats: {}",
+                Self::VALID_VALUES
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Comments with their file and line (`None` for file comments): commit
+/// messages first, then files by path, and each file's comments by line
+fn sorted_comments(session: &ReviewSession) -> Vec<(&Path, Option<u32>, &Comment)> {
+    let mut files: Vec<_> = session.files.iter().collect();
+    files.sort_by_key(|(path, _)| {
+        (
+            commit_message_id(path).is_none(),
+            path.to_string_lossy().to_string(),
+        )
+    });
+
+    let mut comments = Vec::new();
+    for (path, review) in files {
+        comments.extend(
+            review
+                .file_comments
+                .iter()
+                .map(|comment| (path.as_path(), None, comment)),
+        );
+        let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+        line_comments.sort_by_key(|(line, _)| **line);
+        for (line, line_comments) in line_comments {
+            comments.extend(
+                line_comments
+                    .iter()
+                    .map(|comment| (path.as_path(), Some(*line), comment)),
+            );
+        }
+    }
+    comments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_export_formats() {
+        assert_eq!("markdown".parse(), Ok(ExportFormat::Markdown));
+        assert_eq!("md".parse(), Ok(ExportFormat::Markdown));
+        assert_eq!("SARIF".parse(), Ok(ExportFormat::Sarif));
+        assert_eq!("github".parse(), Ok(ExportFormat::GitHub));
+        assert_eq!("gitlab".parse(), Ok(ExportFormat::GitLab));
+        assert_eq!("patch".parse(), Ok(ExportFormat::Patch));
+        assert!("html".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! Export of a review as a SARIF 2.1.0 log, for code-scanning dashboards.
+//!
+//! Each comment is a result whose rule is its comment type. Line comments
+//! point at their line range; SARIF has no notion of diff sides, so the side
+//! is kept in the location's properties, and comments on deleted lines point
+//! at lines of the old version of the file.
+
+use serde_json::{Map, Value, json};
+
+use super::sorted_comments;
+use crate::error::{Result, TuicrError};
+use crate::model::{
+    Comment, CommentType, CommentTypeDef, CommentTypes, LineRange, LineSide, ReviewSession,
+    commit_message_id,
+};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = env!("CARGO_PKG_REPOSITORY");
+const SOURCE_ROOT: &str = "SRCROOT";
+
+/// Generate the SARIF log, or an error if there are no comments
+pub fn generate_sarif(session: &ReviewSession, comment_types: &CommentTypes) -> Result<String> {
+    if !session.has_comments() {
+        return Err(TuicrError::NoComments);
+    }
+    let log = sarif_log(session, comment_types);
+    Ok(serde_json::to_string_pretty(&log)?)
+}
+
+fn sarif_log(session: &ReviewSession, comment_types: &CommentTypes) -> Value {
+    let mut rules: Vec<CommentType> = Vec::new();
+    let mut results = Vec::new();
+    for (path, line, comment) in sorted_comments(session) {
+        if !rules.contains(&comment.comment_type) {
+            rules.push(comment.comment_type.clone());
+        }
+        let location = match commit_message_id(path) {
+            Some(commit) => json!({
+                "logicalLocations": [{ "fullyQualifiedName": format!("commit {commit}") }]
+            }),
+            None => {
+                let range = comment.line_range.or(line.map(LineRange::single));
+                physical_location(
+                    &percent_encode(&path.to_string_lossy()),
+                    range,
+                    comment.side,
+                )
+            }
+        };
+        let def = comment_types.resolve(&comment.comment_type);
+        results.push(result(comment, &def, location));
+    }
+
+    let rules: Vec<Value> = rules
+        .iter()
+        .map(|comment_type| rule(&comment_types.resolve(comment_type)))
+        .collect();
+    let mut base_uri = format!(
+        "file://{}",
+        percent_encode(&session.repo_path.to_string_lossy())
+    );
+    if !base_uri.ends_with('/') {
+        base_uri.push('/');
+    }
+
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "tuicr",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": INFORMATION_URI,
+                    "rules": rules,
+                }
+            },
+            "originalUriBaseIds": { SOURCE_ROOT: { "uri": base_uri } },
+            "results": results,
+        }]
+    })
+}
+
+/// SARIF level of a comment type: blocking types and issues are errors,
+/// suggestions warnings and praise no problem at all
+fn level(def: &CommentTypeDef) -> &'static str {
+    match def.id.id() {
+        _ if def.blocking => "error",
+        "issue" => "error",
+        "suggestion" => "warning",
+        "praise" => "none",
+        _ => "note",
+    }
+}
+
+fn rule(def: &CommentTypeDef) -> Value {
+    let mut rule = json!({
+        "id": def.id.id(),
+        "name": def.name,
+        "defaultConfiguration": { "level": level(def) },
+    });
+    if !def.description.is_empty() {
+        rule["shortDescription"] = json!({ "text": def.description });
+    }
+    rule
+}
+
+/// `path` as a URI path: bytes other than unreserved characters and `/`
+/// become `%XX` escapes, so spaces, `#` and `%` survive in file names
+fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+fn physical_location(uri: &str, range: Option<LineRange>, side: Option<LineSide>) -> Value {
+    let mut location = json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": uri, "uriBaseId": SOURCE_ROOT },
+        }
+    });
+    if let Some(range) = range {
+        let side = match side.unwrap_or_default() {
+            LineSide::Old => "old",
+            LineSide::New => "new",
+        };
+        let physical = &mut location["physicalLocation"];
+        physical["region"] = json!({ "startLine": range.start, "endLine": range.end });
+        physical["properties"] = json!({ "side": side });
+    }
+    location
+}
+
+fn result(comment: &Comment, def: &CommentTypeDef, location: Value) -> Value {
+    let mut properties = Map::new();
+    if let Some(author) = &comment.author {
+        properties.insert("author".to_string(), author.clone().into());
+    }
+    if def.blocking {
+        properties.insert("blocking".to_string(), true.into());
+    }
+
+    let mut result = json!({
+        "guid": comment.id,
+        "ruleId": def.id.id(),
+        "level": level(def),
+        "message": { "text": comment.content },
+        "locations": [location],
+    });
+    if !properties.is_empty() {
+        result["properties"] = Value::Object(properties);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::diff_types::commit_message_path;
+    use crate::model::{FileStatus, SessionDiffSource};
+    use std::path::PathBuf;
+
+    fn create_test_session() -> ReviewSession {
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/test-repo"),
+            "abc1234def".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        session.add_file(PathBuf::from("src/main.rs"), FileStatus::Modified);
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.add_file_comment(Comment::new(
+            "Consider adding documentation".to_string(),
+            CommentType::new("suggestion"),
+            None,
+        ));
+        review.add_line_comment(
+            42,
+            Comment::new_with_range(
+                "Magic number should be a constant".to_string(),
+                CommentType::new("issue"),
+                Some(LineSide::Old),
+                LineRange::new(40, 42),
+            )
+            .with_author(Some("Jane Doe".to_string())),
+        );
+        session
+    }
+
+    fn generate(session: &ReviewSession) -> Value {
+        let sarif = generate_sarif(session, &CommentTypes::default()).unwrap();
+        serde_json::from_str(&sarif).unwrap()
+    }
+
+    #[test]
+    fn should_describe_the_tool_in_the_run() {
+        // when
+        let log = generate(&create_test_session());
+
+        // then
+        assert_eq!(log["version"], "2.1.0");
+        let driver = &log["runs"][0]["tool"]["driver"];
+        assert_eq!(driver["name"], "tuicr");
+        assert_eq!(driver["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(driver["informationUri"], env!("CARGO_PKG_REPOSITORY"));
+        let rules: Vec<&str> = driver["rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|rule| rule["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(rules, vec!["suggestion", "issue"]);
+        assert_eq!(
+            log["runs"][0]["originalUriBaseIds"]["SRCROOT"]["uri"],
+            "file:///tmp/test-repo/"
+        );
+    }
+
+    #[test]
+    fn should_map_comments_to_results_with_locations() {
+        // when
+        let log = generate(&create_test_session());
+
+        // then
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+
+        let file_comment = &results[0];
+        assert_eq!(file_comment["ruleId"], "suggestion");
+        assert_eq!(file_comment["level"], "warning");
+        let location = &file_comment["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/main.rs");
+        assert!(location.get("region").is_none());
+
+        let line_comment = &results[1];
+        assert_eq!(line_comment["level"], "error");
+        assert_eq!(
+            line_comment["message"]["text"],
+            "Magic number should be a constant"
+        );
+        assert_eq!(line_comment["properties"]["author"], "Jane Doe");
+        let location = &line_comment["locations"][0]["physicalLocation"];
+        assert_eq!(location["region"]["startLine"], 40);
+        assert_eq!(location["region"]["endLine"], 42);
+        assert_eq!(location["properties"]["side"], "old");
+    }
+
+    #[test]
+    fn should_percent_encode_uris_of_paths_with_spaces() {
+        // given
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/my repo"),
+            "abc1234def".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+        let path = PathBuf::from("src/my file#1.rs");
+        session.add_file(path.clone(), FileStatus::Added);
+        session
+            .get_file_mut(&path)
+            .unwrap()
+            .add_file_comment(Comment::new(
+                "Rename this file".to_string(),
+                CommentType::new("note"),
+                None,
+            ));
+
+        // when
+        let log = generate(&session);
+
+        // then
+        let run = &log["runs"][0];
+        assert_eq!(
+            run["originalUriBaseIds"]["SRCROOT"]["uri"],
+            "file:///tmp/my%20repo/"
+        );
+        assert_eq!(
+            run["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "src/my%20file%231.rs"
+        );
+    }
+
+    #[test]
+    fn should_locate_commit_message_comments_logically() {
+        // given
+        let mut session = create_test_session();
+        let path = commit_message_path("abc1234");
+        session.add_file(path.clone(), FileStatus::Modified);
+        session.get_file_mut(&path).unwrap().add_line_comment(
+            1,
+            Comment::new("Typo".to_string(), CommentType::new("note"), None),
+        );
+
+        // when
+        let log = generate(&session);
+
+        // then
+        let result = &log["runs"][0]["results"][0];
+        assert_eq!(result["level"], "note");
+        assert_eq!(
+            result["locations"][0]["logicalLocations"][0]["fullyQualifiedName"],
+            "commit abc1234"
+        );
+    }
+
+    #[test]
+    fn should_fail_when_no_comments() {
+        // given
+        let session = ReviewSession::new(
+            PathBuf::from("/tmp/test-repo"),
+            "abc1234def".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+
+        // when
+        let result = generate_sarif(&session, &CommentTypes::default());
+
+        // then
+        assert!(matches!(result, Err(TuicrError::NoComments)));
+    }
+}
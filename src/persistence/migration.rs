@@ -0,0 +1,201 @@
+//! Upgrades of stored sessions to the current format.
+//!
+//! Each session records the format it was written in as `major.minor`.
+//! Loading applies every upgrade step newer than that version in order, on
+//! the raw JSON, before deserializing. Format history:
+//!
+//! - `1.0`: no branch, diff source or commit range; line comments are
+//!   located only by their line key and are all on the new side
+//! - `1.1`: adds the branch, diff source and commit range, and the `side`
+//!   of line comments
+//! - `1.2`: adds `line_range` for comments spanning several lines
+//! - `1.3`: every line comment has a `side`; one without a `line_range`
+//!   covers only the line it is keyed by
+//! - `1.4`: adds the `author` of comments
+
+use serde_json::{Map, Value};
+
+use crate::error::{Result, TuicrError};
+
+pub use crate::model::version::{CURRENT_VERSION, SchemaVersion};
+
+/// Rewrites a raw session object into the next format
+type Upgrade = fn(&mut Map<String, Value>);
+
+/// Upgrade steps, each producing the given version from the one before
+const MIGRATIONS: &[(SchemaVersion, Upgrade)] = &[
+    (SchemaVersion::new(1, 1), anchor_line_comments_to_new_side),
+    (SchemaVersion::new(1, 3), anchor_line_comments_to_new_side),
+    (SchemaVersion::new(1, 4), leave_comments_without_author),
+];
+
+/// Format of the raw session `value`; sessions without a version are `1.0`
+pub fn session_version(value: &Value) -> Result<SchemaVersion> {
+    match value.get("version") {
+        None => Ok(SchemaVersion::new(1, 0)),
+        Some(Value::String(version)) => SchemaVersion::parse(version).ok_or_else(|| {
+            TuicrError::CorruptedSession(format!("invalid session version '{version}'"))
+        }),
+        Some(other) => Err(TuicrError::CorruptedSession(format!(
+            "invalid session version {other}"
+        ))),
+    }
+}
+
+/// Upgrade the raw session `value` to [`CURRENT_VERSION`]. Sessions written
+/// by a newer tuicr are refused rather than loaded with their new data
+/// dropped.
+pub fn migrate(value: &mut Value) -> Result<()> {
+    let version = session_version(value)?;
+    if version > CURRENT_VERSION {
+        return Err(TuicrError::UnsupportedSessionVersion {
+            version: version.to_string(),
+            supported: CURRENT_VERSION.to_string(),
+        });
+    }
+    let session = value
+        .as_object_mut()
+        .ok_or_else(|| TuicrError::CorruptedSession("not a JSON object".to_string()))?;
+    for (target, upgrade) in MIGRATIONS {
+        if version < *target {
+            upgrade(session);
+        }
+    }
+    session.insert("version".to_string(), CURRENT_VERSION.to_string().into());
+    Ok(())
+}
+
+/// Apply `update` to every line comment
+fn for_each_line_comment(
+    session: &mut Map<String, Value>,
+    mut update: impl FnMut(&mut Map<String, Value>),
+) {
+    let Some(Value::Object(files)) = session.get_mut("files") else {
+        return;
+    };
+    for file in files.values_mut() {
+        let Some(Value::Object(lines)) = file.get_mut("line_comments") else {
+            continue;
+        };
+        for comments in lines.values_mut() {
+            let Value::Array(comments) = comments else {
+                continue;
+            };
+            for comment in comments.iter_mut().filter_map(Value::as_object_mut) {
+                update(comment);
+            }
+        }
+    }
+}
+
+/// 1.0 → 1.1: old-side comments didn't exist yet. 1.2 → 1.3: a comment
+/// without a side is on the new side.
+fn anchor_line_comments_to_new_side(session: &mut Map<String, Value>) {
+    for_each_line_comment(session, |comment| {
+        if comment.get("side").is_none_or(Value::is_null) {
+            comment.insert("side".to_string(), "new".into());
+        }
+    });
+}
+
+/// 1.3 → 1.4: comments written before authors were recorded have none
+fn leave_comments_without_author(_session: &mut Map<String, Value>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{LineRange, LineSide, ReviewSession, SessionDiffSource};
+    use std::path::PathBuf;
+
+    const FIXTURES: &[(&str, &str)] = &[
+        ("1.0", include_str!("fixtures/session_v1_0.json")),
+        ("1.1", include_str!("fixtures/session_v1_1.json")),
+        ("1.2", include_str!("fixtures/session_v1_2.json")),
+        ("1.3", include_str!("fixtures/session_v1_3.json")),
+    ];
+
+    fn load_fixture(json: &str) -> ReviewSession {
+        let mut value: Value = serde_json::from_str(json).unwrap();
+        migrate(&mut value).unwrap();
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn should_load_every_historical_format() {
+        for (version, json) in FIXTURES {
+            // when
+            let session = load_fixture(json);
+
+            // then
+            assert_eq!(session.version, CURRENT_VERSION.to_string(), "{version}");
+            let file = &session.files[&PathBuf::from("src/main.rs")];
+            assert_eq!(file.file_comments.len(), 1, "{version}");
+            let comment = &file.line_comments[&12][0];
+            assert_eq!(comment.side, Some(LineSide::New), "{version}");
+            assert_eq!(
+                comment.line_range.unwrap_or(LineRange::single(12)),
+                LineRange::single(12),
+                "{version}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_default_fields_missing_before_1_1() {
+        // when
+        let session = load_fixture(FIXTURES[0].1);
+
+        // then
+        assert_eq!(session.branch_name, None);
+        assert_eq!(session.diff_source, SessionDiffSource::WorkingTree);
+        assert_eq!(session.commit_range, None);
+    }
+
+    #[test]
+    fn should_keep_explicit_anchors() {
+        // when
+        let session = load_fixture(FIXTURES[2].1);
+
+        // then
+        let old_side = &session.files[&PathBuf::from("src/main.rs")].line_comments[&40][0];
+        assert_eq!(old_side.side, Some(LineSide::Old));
+        assert_eq!(old_side.line_range, Some(LineRange::new(38, 40)));
+    }
+
+    #[test]
+    fn should_load_sessions_from_before_authors() {
+        // given
+        let (version, json) = FIXTURES[3];
+        assert_eq!(version, "1.3");
+
+        // when
+        let session = load_fixture(json);
+
+        // then
+        assert_eq!(session.version, "1.4");
+        let file = &session.files[&PathBuf::from("src/main.rs")];
+        assert!(file.file_comments.iter().all(|c| c.author.is_none()));
+        assert!(
+            file.line_comments
+                .values()
+                .flatten()
+                .all(|c| c.author.is_none())
+        );
+    }
+
+    #[test]
+    fn should_refuse_sessions_from_a_newer_tuicr() {
+        // given
+        let mut value = serde_json::json!({ "version": "1.5" });
+
+        // when
+        let result = migrate(&mut value);
+
+        // then
+        let message = result.unwrap_err().to_string();
+        assert!(
+            message.contains("1.5") && message.contains("1.4"),
+            "{message}"
+        );
+    }
+}
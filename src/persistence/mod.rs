@@ -1,3 +1,11 @@
+pub mod migration;
 pub mod storage;
 
-pub use storage::{load_latest_session_for_context, save_session};
+pub use storage::{
+    CollectedSession, REPO_REVIEWS_DIR, Retention, SessionLock, StoredSession, autosave_session,
+    collect_garbage, delete_session, discard_autosave, find_autosave, find_newer_session,
+    find_session, gc_log_path, is_same_repo, list_sessions, list_sessions_in,
+    load_latest_session_for_context, load_session, lock_session, repo_reviews_dir, save_session,
+    save_session_in, set_reviews_dir, update_session, use_repo_storage, user_reviews_dir,
+    write_atomic,
+};
@@ -1,18 +1,32 @@
 use directories::ProjectDirs;
+use std::collections::HashMap;
 use std::fs;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::{Duration, SystemTime};
 
+use super::migration;
 use crate::error::{Result, TuicrError};
 use crate::model::ReviewSession;
 use crate::model::review::SessionDiffSource;
 
 const SESSION_MAX_AGE_DAYS: u64 = 7;
+/// Environment variable overriding the sessions directory
+const REVIEWS_DIR_ENV: &str = "TUICR_REVIEWS_DIR";
+const GC_LOG_FILENAME: &str = "gc.log";
+/// Subdirectories of the user's sessions directory for per-machine state
+const LOCKS_DIR: &str = "locks";
+const AUTOSAVE_DIR: &str = "autosave";
+/// Sessions directory inside a repository when stored with it
+pub const REPO_REVIEWS_DIR: &str = ".tuicr/reviews";
 const SESSION_FILENAME_MIN_PARTS: usize = 6;
 const SESSION_FILENAME_SUFFIX_PARTS: usize = 4;
 const SESSION_FILENAME_DATE_LEN: usize = 8;
 const SESSION_FILENAME_TIME_LEN: usize = 6;
 const FINGERPRINT_HEX_LEN: usize = 8;
+/// Shortest number `find_session` tries as an id prefix
+const MIN_ID_PREFIX_LEN: usize = 4;
 
 struct SessionFilenameParts {
     repo_fingerprints: Vec<String>,
@@ -72,21 +86,60 @@ fn is_hex_fingerprint(part: &str) -> bool {
     part.len() == FINGERPRINT_HEX_LEN && part.chars().all(|ch| ch.is_ascii_hexdigit())
 }
 
-fn get_reviews_dir() -> Result<PathBuf> {
-    #[cfg(test)]
-    if let Some(dir) = std::env::var_os("TUICR_REVIEWS_DIR") {
-        let path = PathBuf::from(dir);
-        fs::create_dir_all(&path)?;
-        return Ok(path);
+static CONFIGURED_REVIEWS_DIR: OnceLock<PathBuf> = OnceLock::new();
+static REPO_STORAGE_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Store sessions in `dir` (the config's `[sessions] dir`). The
+/// `TUICR_REVIEWS_DIR` environment variable still takes precedence.
+pub fn set_reviews_dir(dir: PathBuf) {
+    let _ = CONFIGURED_REVIEWS_DIR.set(dir);
+}
+
+/// Store sessions in `.tuicr/reviews/` of the repository at `root` instead
+/// of the user's sessions directory
+pub fn use_repo_storage(root: PathBuf) {
+    let _ = REPO_STORAGE_ROOT.set(root);
+}
+
+/// Directory holding the session files, created if missing
+fn reviews_dir() -> Result<PathBuf> {
+    match REPO_STORAGE_ROOT.get() {
+        Some(root) => repo_reviews_dir(root),
+        None => user_reviews_dir(),
     }
+}
+
+/// Repository-local sessions directory of the repository at `root`
+pub fn repo_reviews_dir(root: &Path) -> Result<PathBuf> {
+    let dir = root.join(REPO_REVIEWS_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
 
-    let proj_dirs = ProjectDirs::from("", "", "tuicr").ok_or_else(|| {
-        TuicrError::Io(std::io::Error::other("Could not determine data directory"))
-    })?;
+/// The repository `dir` belongs to if it is a repository-local sessions
+/// directory
+fn repo_root_of_dir(dir: &Path) -> Option<&Path> {
+    dir.ends_with(REPO_REVIEWS_DIR)
+        .then(|| dir.parent()?.parent())
+        .flatten()
+}
 
-    let data_dir = proj_dirs.data_dir().join("reviews");
-    fs::create_dir_all(&data_dir)?;
-    Ok(data_dir)
+/// The user's sessions directory, created if missing
+pub fn user_reviews_dir() -> Result<PathBuf> {
+    let dir = match std::env::var_os(REVIEWS_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => match CONFIGURED_REVIEWS_DIR.get() {
+            Some(dir) => dir.clone(),
+            None => ProjectDirs::from("", "", "tuicr")
+                .ok_or_else(|| {
+                    TuicrError::Io(std::io::Error::other("Could not determine data directory"))
+                })?
+                .data_dir()
+                .join("reviews"),
+        },
+    };
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
 }
 
 const MAX_FILENAME_COMPONENT_LEN: usize = 64;
@@ -149,6 +202,17 @@ fn session_filename(session: &ReviewSession) -> String {
     let repo_name = sanitize_filename_component(repo_name);
     let repo_fingerprint = repo_path_fingerprint(&session.repo_path);
 
+    format!(
+        "{}_{}_{}",
+        repo_name,
+        repo_fingerprint,
+        repo_session_filename(session)
+    )
+}
+
+/// Filename inside a repository: without the repository name and path
+/// fingerprint, so it is the same in every checkout
+fn repo_session_filename(session: &ReviewSession) -> String {
     let branch = session.branch_name.as_deref().unwrap_or("detached");
     let branch = sanitize_filename_component(branch);
 
@@ -161,30 +225,137 @@ fn session_filename(session: &ReviewSession) -> String {
     let timestamp = session.created_at.format("%Y%m%d_%H%M%S");
     let id_fragment = session.id.split('-').next().unwrap_or(&session.id);
 
-    format!(
-        "{}_{}_{}_{}_{}_{}.json",
-        repo_name, repo_fingerprint, branch, diff_source, timestamp, id_fragment
-    )
+    format!("{branch}_{diff_source}_{timestamp}_{id_fragment}.json")
 }
 
 pub fn save_session(session: &ReviewSession) -> Result<PathBuf> {
-    let reviews_dir = get_reviews_dir()?;
-    let filename = session_filename(session);
-    let path = reviews_dir.join(&filename);
-
-    let json = serde_json::to_string_pretty(session)?;
-    fs::write(&path, json)?;
+    save_session_in(&reviews_dir()?, session)
+}
 
+/// Save `session` in the sessions directory `dir`
+pub fn save_session_in(dir: &Path, session: &ReviewSession) -> Result<PathBuf> {
+    let path = session_path_in(dir, session);
+    write_session(&path, session)?;
     Ok(path)
 }
 
+fn session_path_in(dir: &Path, session: &ReviewSession) -> PathBuf {
+    let filename = match repo_root_of_dir(dir) {
+        Some(_) => repo_session_filename(session),
+        None => session_filename(session),
+    };
+    dir.join(filename)
+}
+
+/// Write `session` as pretty JSON with sorted keys so that saving unchanged
+/// data gives the same bytes. Sessions stored in a repository record their
+/// repository as `.` so the file is valid in any checkout.
+fn write_session(path: &Path, session: &ReviewSession) -> Result<()> {
+    let mut value = serde_json::to_value(session)?;
+    if let Some(root) = path.parent().and_then(repo_root_of_dir)
+        && is_same_repo(&session.repo_path, root)
+    {
+        value["repo_path"] = serde_json::Value::from(".");
+    }
+    let mut json = serde_json::to_string_pretty(&value)?;
+    json.push('\n');
+    write_atomic(path, json.as_bytes())
+}
+
+/// Replace `path` with `contents` through a temporary file in the same
+/// directory, so a crash leaves either the old or the new file. Each write
+/// has its own temporary file, so concurrent writers can't mix their
+/// contents. An existing file keeps its permissions.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let filename = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp_path = path.with_file_name(format!(".{filename}.{}.tmp", uuid::Uuid::new_v4()));
+    let permissions = fs::metadata(path)
+        .ok()
+        .map(|metadata| metadata.permissions());
+    let written = fs::File::create_new(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            if let Some(permissions) = permissions {
+                file.set_permissions(permissions)?;
+            }
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp_path, path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    sync_parent_dir(path)
+}
+
+/// Flush `path`'s directory entry, so a rename to it survives a crash.
+/// Windows can't open directories as files and needs no such flush.
+fn sync_parent_dir(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::File::open(dir)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Load the session at `path`, upgrading older formats
 pub fn load_session(path: &PathBuf) -> Result<ReviewSession> {
     let contents = fs::read_to_string(path)?;
-    let session: ReviewSession =
+    let mut value: serde_json::Value =
         serde_json::from_str(&contents).map_err(|e| TuicrError::CorruptedSession(e.to_string()))?;
+    migration::migrate(&mut value)?;
+    let mut session: ReviewSession =
+        serde_json::from_value(value).map_err(|e| TuicrError::CorruptedSession(e.to_string()))?;
+    resolve_repo_path(path, &mut session);
     Ok(session)
 }
 
+/// Make the repository of a session stored in a repository absolute
+fn resolve_repo_path(path: &Path, session: &mut ReviewSession) {
+    if session.repo_path.is_relative()
+        && let Some(root) = path.parent().and_then(repo_root_of_dir)
+    {
+        session.repo_path = match session.repo_path.to_str() {
+            Some(".") => root.to_path_buf(),
+            _ => root.join(&session.repo_path),
+        };
+    }
+}
+
+/// A session of the same review as `session` written by a newer tuicr,
+/// which the search for a session to resume skips, with the error loading
+/// it gives
+pub fn find_newer_session(session: &ReviewSession) -> Result<Option<(PathBuf, TuicrError)>> {
+    for entry in fs::read_dir(reviews_dir()?)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(value) = fs::read_to_string(&path)
+            .map_err(TuicrError::from)
+            .and_then(|contents| Ok(serde_json::from_str::<serde_json::Value>(&contents)?))
+        else {
+            continue;
+        };
+        let Err(error @ TuicrError::UnsupportedSessionVersion { .. }) =
+            migration::migrate(&mut value.clone())
+        else {
+            continue;
+        };
+        // Read as far as this version understands it, only to compare
+        let Ok(mut newer) = serde_json::from_value::<ReviewSession>(value) else {
+            continue;
+        };
+        resolve_repo_path(&path, &mut newer);
+        if is_same_review(&newer, session) {
+            return Ok(Some((path, error)));
+        }
+    }
+    Ok(None)
+}
+
 pub fn load_latest_session_for_context(
     repo_path: &Path,
     branch_name: Option<&str>,
@@ -200,9 +371,7 @@ pub fn load_latest_session_for_context(
         SessionDiffSource::WorkingTreeAndCommits => "worktree_and_commits",
     };
 
-    let reviews_dir = get_reviews_dir()?;
-    let now = SystemTime::now();
-    let max_age = Duration::from_secs(SESSION_MAX_AGE_DAYS * 24 * 60 * 60);
+    let reviews_dir = reviews_dir()?;
 
     let mut session_files: Vec<_> = fs::read_dir(&reviews_dir)?
         .filter_map(|entry| entry.ok())
@@ -217,16 +386,6 @@ pub fn load_latest_session_for_context(
                 return false;
             }
 
-            // Delete sessions older than 7 days
-            if let Ok(metadata) = entry.metadata()
-                && let Ok(modified) = metadata.modified()
-                && let Ok(age) = now.duration_since(modified)
-                && age > max_age
-            {
-                let _ = fs::remove_file(&path);
-                return false;
-            }
-
             let Some(filename) = path.file_name().and_then(|f| f.to_str()) else {
                 return false;
             };
@@ -315,12 +474,373 @@ pub fn load_latest_session_for_context(
     Ok(legacy_candidate)
 }
 
-#[cfg(test)]
-fn delete_session(path: &PathBuf) -> Result<()> {
+/// A session file in the reviews directory
+#[derive(Debug, Clone)]
+pub struct StoredSession {
+    pub path: PathBuf,
+    pub session: ReviewSession,
+    pub modified: SystemTime,
+}
+
+impl StoredSession {
+    /// Session name, or the start of its id for unnamed sessions
+    pub fn label(&self) -> &str {
+        self.session.name.as_deref().unwrap_or_else(|| {
+            self.session
+                .id
+                .split('-')
+                .next()
+                .unwrap_or(&self.session.id)
+        })
+    }
+
+    pub fn repo_name(&self) -> String {
+        self.session
+            .repo_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.session.repo_path.display().to_string())
+    }
+
+    pub fn source_label(&self) -> String {
+        let commits = self.session.commit_range.as_ref().map_or(0, Vec::len);
+        let plural = if commits == 1 { "" } else { "s" };
+        match self.session.diff_source {
+            SessionDiffSource::WorkingTree => "worktree".to_string(),
+            SessionDiffSource::CommitRange => format!("{commits} commit{plural}"),
+            SessionDiffSource::WorkingTreeAndCommits => {
+                format!("worktree + {commits} commit{plural}")
+            }
+        }
+    }
+
+    /// Time since the session was last saved, e.g. `5m` or `3d`
+    pub fn age_label(&self) -> String {
+        let secs = SystemTime::now()
+            .duration_since(self.modified)
+            .unwrap_or_default()
+            .as_secs();
+        match secs {
+            0..60 => "now".to_string(),
+            60..3600 => format!("{}m", secs / 60),
+            3600..86400 => format!("{}h", secs / 3600),
+            _ => format!("{}d", secs / 86400),
+        }
+    }
+}
+
+/// All readable sessions, most recently saved first
+pub fn list_sessions() -> Result<Vec<StoredSession>> {
+    list_sessions_in(&reviews_dir()?)
+}
+
+/// Readable sessions in the sessions directory `dir`, most recently saved
+/// first
+pub fn list_sessions_in(dir: &Path) -> Result<Vec<StoredSession>> {
+    let mut sessions: Vec<StoredSession> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                return None;
+            }
+            let session = load_session(&path).ok()?;
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            Some(StoredSession {
+                path,
+                session,
+                modified,
+            })
+        })
+        .collect();
+
+    sessions.sort_by(|a, b| {
+        b.modified
+            .cmp(&a.modified)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(sessions)
+}
+
+/// Find a session by its 1-based position in `sessions`, its name or a
+/// prefix of its id. Ids are hex, so a number of `MIN_ID_PREFIX_LEN` digits
+/// or more past the end of the list is taken as an id prefix.
+pub fn find_session<'a>(sessions: &'a [StoredSession], key: &str) -> Option<&'a StoredSession> {
+    if let Ok(index) = key.parse::<usize>() {
+        let stored = index.checked_sub(1).and_then(|idx| sessions.get(idx));
+        if stored.is_some() || key.len() < MIN_ID_PREFIX_LEN {
+            return stored;
+        }
+    }
+    sessions
+        .iter()
+        .find(|stored| stored.session.name.as_deref() == Some(key))
+        .or_else(|| {
+            let mut matches = sessions
+                .iter()
+                .filter(|stored| stored.session.id.starts_with(key));
+            // An ambiguous prefix matches nothing
+            matches.next().filter(|_| matches.next().is_none())
+        })
+}
+
+/// Apply `update` to the session stored at `path` and write it back in place
+pub fn update_session(
+    path: &Path,
+    update: impl FnOnce(&mut ReviewSession),
+) -> Result<ReviewSession> {
+    let mut session = load_session(&path.to_path_buf())?;
+    update(&mut session);
+    write_session(path, &session)?;
+    Ok(session)
+}
+
+pub fn delete_session(path: &Path) -> Result<()> {
+    let id = load_session(&path.to_path_buf()).map(|session| session.id);
     fs::remove_file(path)?;
+    if let Ok(id) = id {
+        discard_autosave(&id)?;
+    }
     Ok(())
 }
 
+pub fn is_same_repo(a: &Path, b: &Path) -> bool {
+    normalize_repo_path(a) == normalize_repo_path(b)
+}
+
+/// A subdirectory of the user's sessions directory, created if missing.
+/// Locks and autosaves belong to this machine, so they stay here when
+/// sessions are stored in the repository.
+fn user_state_dir(name: &str) -> Result<PathBuf> {
+    let dir = user_reviews_dir()?.join(name);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Advisory lock on a session, held while this instance has it open.
+/// Released when dropped.
+#[derive(Debug)]
+pub struct SessionLock {
+    pub session_id: String,
+    _file: fs::File,
+}
+
+/// Lock the session `session_id`, failing with
+/// [`TuicrError::SessionLocked`] when another instance holds it
+pub fn lock_session(session_id: &str) -> Result<SessionLock> {
+    let path = user_state_dir(LOCKS_DIR)?.join(format!("{session_id}.lock"));
+    let mut file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(fs::TryLockError::WouldBlock) => {
+            let mut pid = String::new();
+            let _ = file.read_to_string(&mut pid);
+            let holder = match pid.trim() {
+                "" => "unknown process".to_string(),
+                pid => format!("pid {pid}"),
+            };
+            return Err(TuicrError::SessionLocked(holder));
+        }
+        Err(fs::TryLockError::Error(e)) => return Err(e.into()),
+    }
+    file.set_len(0)?;
+    write!(file, "{}", std::process::id())?;
+    Ok(SessionLock {
+        session_id: session_id.to_string(),
+        _file: file,
+    })
+}
+
+fn autosave_path(session_id: &str) -> Result<PathBuf> {
+    Ok(user_state_dir(AUTOSAVE_DIR)?.join(format!("{session_id}.json")))
+}
+
+/// Record the unsaved state of `session` for recovery should this instance
+/// exit without saving
+pub fn autosave_session(session: &ReviewSession) -> Result<()> {
+    let json = serde_json::to_string_pretty(session)?;
+    write_atomic(&autosave_path(&session.id)?, json.as_bytes())
+}
+
+/// Drop the autosave of `session_id`, e.g. after saving it
+pub fn discard_autosave(session_id: &str) -> Result<()> {
+    match fs::remove_file(autosave_path(session_id)?) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Unsaved changes to recover in place of `session`: its own autosave if it
+/// is newer than the saved file. A session that was never saved takes the
+/// newest autosave of another never-saved session of the same review whose
+/// instance has exited.
+pub fn find_autosave(session: &ReviewSession) -> Result<Option<ReviewSession>> {
+    let saved_at = |session: &ReviewSession| -> Result<Option<SystemTime>> {
+        let path = session_path_in(&reviews_dir()?, session);
+        Ok(fs::metadata(path).and_then(|m| m.modified()).ok())
+    };
+    let session_saved_at = saved_at(session)?;
+
+    let mut best: Option<(SystemTime, ReviewSession)> = None;
+    for entry in fs::read_dir(user_state_dir(AUTOSAVE_DIR)?)?.filter_map(|e| e.ok()) {
+        let Ok(modified) = entry.metadata().and_then(|m| m.modified()) else {
+            continue;
+        };
+        let Ok(autosaved) = load_session(&entry.path()) else {
+            continue;
+        };
+        let recoverable = if autosaved.id == session.id {
+            session_saved_at.is_none_or(|saved| saved < modified)
+        } else {
+            session_saved_at.is_none()
+                && is_same_review(&autosaved, session)
+                && saved_at(&autosaved)?.is_none()
+                && lock_session(&autosaved.id).is_ok()
+        };
+        if recoverable && best.as_ref().is_none_or(|(time, _)| *time < modified) {
+            best = Some((modified, autosaved));
+        }
+    }
+    Ok(best.map(|(_, session)| session))
+}
+
+/// Whether two sessions review the same changes of the same repository
+fn is_same_review(a: &ReviewSession, b: &ReviewSession) -> bool {
+    is_same_repo(&a.repo_path, &b.repo_path)
+        && a.diff_source == b.diff_source
+        && a.branch_name == b.branch_name
+        && a.commit_range == b.commit_range
+        && (a.branch_name.is_some() || a.base_commit == b.base_commit)
+}
+
+/// How long unpinned sessions are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    /// Days since the last save; `None` keeps sessions regardless of age
+    pub max_age_days: Option<u64>,
+    /// Most recently saved sessions kept per repository
+    pub max_per_repo: Option<usize>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            max_age_days: Some(SESSION_MAX_AGE_DAYS),
+            max_per_repo: None,
+        }
+    }
+}
+
+/// A session deleted by [`collect_garbage`]
+#[derive(Debug, Clone)]
+pub struct CollectedSession {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// Delete the unpinned sessions `retention` doesn't keep. Each deletion is
+/// appended to `gc.log` in the sessions directory.
+pub fn collect_garbage(retention: &Retention) -> Result<Vec<CollectedSession>> {
+    let now = SystemTime::now();
+    let mut kept_per_repo: HashMap<String, usize> = HashMap::new();
+    let mut collected = Vec::new();
+
+    for stored in list_sessions()? {
+        if stored.session.pinned {
+            continue;
+        }
+        let age = now.duration_since(stored.modified).unwrap_or_default();
+        let kept = kept_per_repo
+            .entry(normalize_repo_path(&stored.session.repo_path))
+            .or_default();
+        let reason = match (retention.max_age_days, retention.max_per_repo) {
+            (Some(days), _) if age > Duration::from_secs(days * 24 * 60 * 60) => {
+                format!("not saved for {days} days")
+            }
+            (_, Some(max)) if *kept >= max => {
+                format!("more than {max} sessions for {}", stored.repo_name())
+            }
+            _ => {
+                *kept += 1;
+                continue;
+            }
+        };
+        // An instance has the session open and would save it again
+        let Ok(_lock) = lock_session(&stored.session.id) else {
+            continue;
+        };
+        delete_session(&stored.path)?;
+        collected.push(CollectedSession {
+            path: stored.path,
+            reason,
+        });
+    }
+    if let Some(days) = retention.max_age_days {
+        collected.extend(collect_state_files(
+            Duration::from_secs(days * 24 * 60 * 60),
+            days,
+        )?);
+    }
+
+    if !collected.is_empty() {
+        let mut log = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(gc_log_path()?)?;
+        let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        for session in &collected {
+            let filename = session
+                .path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            writeln!(log, "{now} deleted {filename}: {}", session.reason)?;
+        }
+    }
+    Ok(collected)
+}
+
+/// Delete autosaves and unheld locks not written within `max_age`. Only
+/// the autosaves are reported.
+fn collect_state_files(max_age: Duration, days: u64) -> Result<Vec<CollectedSession>> {
+    let now = SystemTime::now();
+    let expired = |entry: &fs::DirEntry| {
+        entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() > max_age)
+    };
+
+    let mut collected = Vec::new();
+    for entry in fs::read_dir(user_state_dir(AUTOSAVE_DIR)?)?.filter_map(|e| e.ok()) {
+        if expired(&entry) {
+            fs::remove_file(entry.path())?;
+            collected.push(CollectedSession {
+                path: entry.path(),
+                reason: format!("unsaved changes not recovered for {days} days"),
+            });
+        }
+    }
+    for entry in fs::read_dir(user_state_dir(LOCKS_DIR)?)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let id = path.file_stem().unwrap_or_default().to_string_lossy();
+        if expired(&entry) && lock_session(&id).is_ok() {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(collected)
+}
+
+/// Log of sessions deleted by [`collect_garbage`]
+pub fn gc_log_path() -> Result<PathBuf> {
+    Ok(reviews_dir()?.join(GC_LOG_FILENAME))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -907,4 +1427,350 @@ mod tests {
             normalize_repo_path(&repo_a)
         );
     }
+
+    fn set_mtime(path: &Path, age: Duration) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    #[test]
+    fn should_list_sessions_newest_first_and_find_them() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let mut older = create_test_session();
+        older.name = Some("auth fix".to_string());
+        let older_path = save_session(&older).unwrap();
+        set_mtime(&older_path, Duration::from_secs(3 * 24 * 60 * 60));
+        let newer = create_test_session();
+        save_session(&newer).unwrap();
+
+        // when
+        let sessions = list_sessions().unwrap();
+
+        // then
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session.id, newer.id);
+        assert_eq!(sessions[1].age_label(), "3d");
+        assert_eq!(find_session(&sessions, "2").unwrap().session.id, older.id);
+        assert_eq!(
+            find_session(&sessions, "auth fix").unwrap().session.id,
+            older.id
+        );
+        assert_eq!(
+            find_session(&sessions, &newer.id[..8]).unwrap().session.id,
+            newer.id
+        );
+        assert!(find_session(&sessions, "3").is_none());
+    }
+
+    #[test]
+    fn should_find_sessions_by_a_numeric_id_prefix() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let mut session = create_test_session();
+        session.id = "12345678-0000-4000-8000-000000000000".to_string();
+        save_session(&session).unwrap();
+        let sessions = list_sessions().unwrap();
+
+        // when
+        let found = find_session(&sessions, "12345678");
+
+        // then
+        assert_eq!(found.unwrap().session.id, session.id);
+    }
+
+    #[test]
+    fn should_update_session_in_place() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let path = save_session(&create_test_session()).unwrap();
+
+        // when
+        update_session(&path, |session| {
+            session.name = Some("wip".to_string());
+            session.pinned = true;
+        })
+        .unwrap();
+
+        // then
+        let loaded = load_session(&path).unwrap();
+        assert_eq!(loaded.name.as_deref(), Some("wip"));
+        assert!(loaded.pinned);
+        assert_eq!(list_sessions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_collect_expired_sessions_except_pinned_ones() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let expired = Duration::from_secs((SESSION_MAX_AGE_DAYS + 1) * 24 * 60 * 60);
+        let mut pinned = create_test_session();
+        pinned.pinned = true;
+        let pinned_path = save_session(&pinned).unwrap();
+        set_mtime(&pinned_path, expired);
+        let expired_path = save_session(&create_test_session()).unwrap();
+        set_mtime(&expired_path, expired);
+        let recent_path = save_session(&create_test_session()).unwrap();
+
+        // when
+        let collected = collect_garbage(&Retention::default()).unwrap();
+
+        // then
+        assert_eq!(collected.len(), 1);
+        assert_eq!(collected[0].path, expired_path);
+        assert!(!expired_path.exists());
+        assert!(pinned_path.exists() && recent_path.exists());
+        let log = fs::read_to_string(gc_log_path().unwrap()).unwrap();
+        assert!(log.contains("not saved for 7 days"));
+    }
+
+    #[test]
+    fn should_not_collect_sessions_held_by_an_instance() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let session = create_test_session();
+        let path = save_session(&session).unwrap();
+        set_mtime(
+            &path,
+            Duration::from_secs((SESSION_MAX_AGE_DAYS + 1) * 24 * 60 * 60),
+        );
+        let lock = lock_session(&session.id).unwrap();
+
+        // when
+        let collected = collect_garbage(&Retention::default()).unwrap();
+
+        // then
+        assert!(collected.is_empty());
+        assert!(path.exists());
+        drop(lock);
+        assert_eq!(collect_garbage(&Retention::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_leave_no_temporary_files_after_atomic_writes() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+
+        // when
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        // then
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_keep_permissions_through_atomic_writes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.sh");
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // when
+        write_atomic(&path, b"#!/bin/sh\n# REVIEW[tuicr](NOTE): quote this\n").unwrap();
+
+        // then
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[test]
+    fn should_keep_newest_sessions_per_repo_and_never_expire() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let retention = Retention {
+            max_age_days: None,
+            max_per_repo: Some(1),
+        };
+        let old_path = save_session(&create_test_session()).unwrap();
+        set_mtime(&old_path, Duration::from_secs(365 * 24 * 60 * 60));
+        let new_path = save_session(&create_test_session()).unwrap();
+        let mut other_repo = create_test_session();
+        other_repo.repo_path = PathBuf::from("/tmp/other-repo");
+        let other_path = save_session(&other_repo).unwrap();
+        set_mtime(&other_path, Duration::from_secs(365 * 24 * 60 * 60));
+
+        // when
+        let collected = collect_garbage(&retention).unwrap();
+
+        // then
+        assert_eq!(collected.len(), 1);
+        assert_eq!(collected[0].path, old_path);
+        assert!(collected[0].reason.contains("more than 1 sessions"));
+        assert!(new_path.exists() && other_path.exists());
+    }
+
+    #[test]
+    fn should_not_delete_expired_sessions_while_loading() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let repo_path = std::env::temp_dir().join(format!("tuicr-repo-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&repo_path).unwrap();
+        let session = create_session(
+            repo_path.clone(),
+            "head",
+            Some("main"),
+            SessionDiffSource::WorkingTree,
+            None,
+        );
+        let path = save_session(&session).unwrap();
+        set_mtime(&path, Duration::from_secs(30 * 24 * 60 * 60));
+
+        // when
+        let loaded = load_latest_session_for_context(
+            &repo_path,
+            Some("main"),
+            "head",
+            SessionDiffSource::WorkingTree,
+            None,
+        )
+        .unwrap();
+
+        // then
+        assert_eq!(loaded.unwrap().1.id, session.id);
+    }
+
+    #[test]
+    fn should_store_repo_local_sessions_relative_to_the_repo() {
+        // given
+        let root = std::env::temp_dir().join(format!("tuicr-repo-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        let mut session = create_session(
+            root.clone(),
+            "head",
+            Some("main"),
+            SessionDiffSource::WorkingTree,
+            None,
+        );
+        session.add_file(PathBuf::from("src/lib.rs"), FileStatus::Added);
+        let dir = repo_reviews_dir(&root).unwrap();
+
+        // when
+        let path = save_session_in(&dir, &session).unwrap();
+        let first = fs::read_to_string(&path).unwrap();
+        save_session_in(&dir, &session).unwrap();
+
+        // then
+        let filename = path.file_name().unwrap().to_str().unwrap();
+        assert!(filename.starts_with("main_worktree_"));
+        assert!(first.contains("\"repo_path\": \".\""));
+        assert!(!first.contains(root.to_str().unwrap()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), first);
+        let listed = list_sessions_in(&dir).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].session.repo_path, root);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn should_lock_a_session_for_one_holder_at_a_time() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let lock = lock_session("session-a").unwrap();
+
+        // when
+        let second = lock_session("session-a");
+
+        // then
+        let pid = std::process::id().to_string();
+        assert!(matches!(second, Err(TuicrError::SessionLocked(holder)) if holder.contains(&pid)));
+        assert!(lock_session("session-b").is_ok());
+        drop(lock);
+        assert!(lock_session("session-a").is_ok());
+    }
+
+    #[test]
+    fn should_write_sessions_without_leaving_temp_files() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let session = create_test_session();
+
+        // when
+        let path = save_session(&session).unwrap();
+        save_session(&session).unwrap();
+
+        // then
+        let names: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().unwrap().is_file())
+            .map(|entry| entry.file_name())
+            .collect();
+        assert_eq!(names, vec![path.file_name().unwrap().to_os_string()]);
+    }
+
+    #[test]
+    fn should_recover_autosaved_changes() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let session = create_test_session();
+        save_session(&session).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        let mut changed = session.clone();
+        changed.name = Some("unsaved".to_string());
+        autosave_session(&changed).unwrap();
+
+        // when
+        let recovered = find_autosave(&session).unwrap();
+
+        // then
+        assert_eq!(recovered.unwrap().name.as_deref(), Some("unsaved"));
+        discard_autosave(&session.id).unwrap();
+        assert!(find_autosave(&session).unwrap().is_none());
+    }
+
+    #[test]
+    fn should_recover_unsaved_session_of_the_same_review_only_when_unlocked() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let mut crashed = create_test_session();
+        crashed.name = Some("crashed".to_string());
+        autosave_session(&crashed).unwrap();
+        let mut other_branch = create_test_session();
+        other_branch.branch_name = Some("feature".to_string());
+        autosave_session(&other_branch).unwrap();
+        let fresh = create_test_session();
+
+        // when
+        let held = lock_session(&crashed.id).unwrap();
+        let while_running = find_autosave(&fresh).unwrap();
+        drop(held);
+        let after_exit = find_autosave(&fresh).unwrap();
+
+        // then
+        assert!(while_running.is_none());
+        assert_eq!(after_exit.unwrap().id, crashed.id);
+        let saved = create_test_session();
+        save_session(&saved).unwrap();
+        assert!(find_autosave(&saved).unwrap().is_none());
+    }
+
+    #[test]
+    fn should_refuse_and_report_sessions_from_a_newer_tuicr() {
+        // given
+        let _guard = with_test_reviews_dir();
+        let session = create_test_session();
+        let path = save_session(&session).unwrap();
+        let mut value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        value["version"] = "9.0".into();
+        fs::write(&path, value.to_string()).unwrap();
+
+        // when
+        let loaded = load_session(&path);
+        let newer = find_newer_session(&create_test_session()).unwrap();
+
+        // then
+        assert!(matches!(
+            loaded,
+            Err(TuicrError::UnsupportedSessionVersion { ref version, .. }) if version == "9.0"
+        ));
+        assert_eq!(newer.map(|(found, _)| found), Some(path));
+    }
 }
@@ -0,0 +1,202 @@
+//! The `tuicr sessions` subcommand: manage stored review sessions from the
+//! shell.
+
+use anyhow::{Context, anyhow};
+
+use crate::model::{MergeOptions, ReviewSession};
+use std::path::{Path, PathBuf};
+
+use crate::persistence::{
+    CollectedSession, Retention, StoredSession, collect_garbage, delete_session, find_session,
+    gc_log_path, is_same_repo, list_sessions, list_sessions_in, load_session, repo_reviews_dir,
+    save_session, save_session_in, update_session, user_reviews_dir,
+};
+use crate::theme::SessionsCommand;
+
+/// Run `command`, returning the session to open in the TUI for `open`.
+/// The working directory is changed to that session's repository.
+pub fn run(
+    command: SessionsCommand,
+    retention: &Retention,
+) -> anyhow::Result<Option<ReviewSession>> {
+    let sessions = list_sessions()?;
+    let find = |key: &str| {
+        find_session(&sessions, key)
+            .ok_or_else(|| anyhow!("No session matches '{key}' (see `tuicr sessions list`)"))
+    };
+
+    match command {
+        SessionsCommand::List => print_sessions(&sessions),
+        SessionsCommand::Open(key) => {
+            let stored = find(&key)?;
+            std::env::set_current_dir(&stored.session.repo_path).with_context(|| {
+                format!(
+                    "Can't open repository {}",
+                    stored.session.repo_path.display()
+                )
+            })?;
+            return Ok(Some(stored.session.clone()));
+        }
+        SessionsCommand::Rename { session, name } => {
+            let stored = find(&session)?;
+            update_session(&stored.path, |session| session.name = Some(name.clone()))?;
+            println!("Renamed session {} to {name}", stored.label());
+        }
+        SessionsCommand::Delete(key) => {
+            let stored = find(&key)?;
+            delete_session(&stored.path)?;
+            println!("Deleted session {}", stored.label());
+        }
+        SessionsCommand::Pin(key) => set_pinned(find(&key)?, true)?,
+        SessionsCommand::Unpin(key) => set_pinned(find(&key)?, false)?,
+        SessionsCommand::Gc => {
+            let collected = collect_garbage(retention)?;
+            for session in &collected {
+                let filename = session.path.file_name().unwrap_or_default();
+                println!("Deleted {}: {}", filename.to_string_lossy(), session.reason);
+            }
+            if collected.is_empty() {
+                println!("No sessions to delete");
+            }
+        }
+        SessionsCommand::Export(key) => {
+            let root = repo_root()?;
+            copy_sessions(&user_reviews_dir()?, &repo_reviews_dir(&root)?, &root, key)?;
+        }
+        SessionsCommand::Import(key) => {
+            let root = repo_root()?;
+            copy_sessions(&repo_reviews_dir(&root)?, &user_reviews_dir()?, &root, key)?;
+        }
+        SessionsCommand::Merge {
+            first,
+            second,
+            reviewed,
+        } => {
+            let load = |key: &str| -> anyhow::Result<(String, ReviewSession)> {
+                let path = Path::new(key);
+                if path.is_file() {
+                    let session = load_session(&path.to_path_buf())
+                        .with_context(|| format!("Can't load {key}"))?;
+                    return Ok((key.to_string(), session));
+                }
+                let stored = find(key)?;
+                Ok((stored.label().to_string(), stored.session.clone()))
+            };
+            let (a_label, mut merged) = load(&first)?;
+            let (b_label, other) = load(&second)?;
+            let options = MergeOptions {
+                same_repo: is_same_repo(&merged.repo_path, &other.repo_path),
+                reviewed,
+            };
+            let report = merged.merge(&other, options);
+            for conflict in &report.conflicts {
+                eprintln!("Warning: {conflict}");
+            }
+            for path in &report.reviewed_in_one {
+                eprintln!("Note: {} was reviewed in only one session", path.display());
+            }
+            merged.id = uuid::Uuid::new_v4().to_string();
+            merged.name = Some(format!("{a_label} + {b_label}"));
+            merged.pinned = false;
+            // Undoing the first session's changes would drop merged comments
+            merged.history = Default::default();
+            merged.updated_at = chrono::Utc::now();
+            let path = save_session(&merged)?;
+            println!(
+                "{} from {b_label} into {}",
+                report.summary(),
+                path.display()
+            );
+        }
+    }
+    Ok(None)
+}
+
+fn repo_root() -> anyhow::Result<PathBuf> {
+    let vcs = crate::vcs::detect_vcs().context("Run this inside a repository")?;
+    Ok(vcs.info().root_path.clone())
+}
+
+/// Copy the sessions of the repository at `root` (or the one matching `key`)
+/// from one sessions directory to another. Copies keep their id, so copying
+/// again replaces them.
+fn copy_sessions(from: &Path, to: &Path, root: &Path, key: Option<String>) -> anyhow::Result<()> {
+    let sessions: Vec<StoredSession> = list_sessions_in(from)?
+        .into_iter()
+        .filter(|stored| is_same_repo(&stored.session.repo_path, root))
+        .collect();
+    let selected: Vec<&StoredSession> = match &key {
+        Some(key) => vec![
+            find_session(&sessions, key)
+                .ok_or_else(|| anyhow!("No session of this repository matches '{key}'"))?,
+        ],
+        None => sessions.iter().collect(),
+    };
+    if selected.is_empty() {
+        println!("No sessions of this repository in {}", from.display());
+    }
+    for stored in selected {
+        let path = save_session_in(to, &stored.session)?;
+        println!("Copied session {} to {}", stored.label(), path.display());
+    }
+    Ok(())
+}
+
+/// Note about the sessions deleted at startup
+pub fn collected_summary(collected: &[CollectedSession]) -> String {
+    let plural = if collected.len() == 1 { "" } else { "s" };
+    let log = gc_log_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| "gc.log".to_string());
+    format!("Deleted {} old session{plural}, see {log}", collected.len())
+}
+
+fn set_pinned(stored: &StoredSession, pinned: bool) -> anyhow::Result<()> {
+    update_session(&stored.path, |session| session.pinned = pinned)?;
+    let verb = if pinned { "Pinned" } else { "Unpinned" };
+    println!("{verb} session {}", stored.label());
+    Ok(())
+}
+
+fn print_sessions(sessions: &[StoredSession]) {
+    if sessions.is_empty() {
+        println!("No saved sessions");
+        return;
+    }
+
+    let header = ["#", "NAME", "REPO", "BRANCH", "SOURCE", "AGE", "COMMENTS"].map(String::from);
+    let rows: Vec<[String; 7]> = sessions
+        .iter()
+        .enumerate()
+        .map(|(i, stored)| {
+            let mut name = stored.label().to_string();
+            if stored.session.pinned {
+                name.push_str(" (pinned)");
+            }
+            [
+                (i + 1).to_string(),
+                name,
+                stored.repo_name(),
+                stored.session.branch_name.clone().unwrap_or_default(),
+                stored.source_label(),
+                stored.age_label(),
+                stored.session.comment_count().to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.clone().map(|cell| cell.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
+}
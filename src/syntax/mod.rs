@@ -8,7 +8,7 @@ use crate::model::diff_types::LineOrigin;
 type HighlightedSpans = Vec<(Style, String)>;
 
 /// Per-line highlight results for a file: `Some` if the line was highlighted, `None` on failure.
-type HighlightedLines = Vec<Option<HighlightedSpans>>;
+pub type HighlightedLines = Vec<Option<HighlightedSpans>>;
 
 /// Helper to highlight lines of code from a diff
 pub struct SyntaxHighlighter {
@@ -63,8 +63,6 @@ impl SyntaxHighlighter {
         file_path: &Path,
         lines: &[String],
     ) -> Option<HighlightedLines> {
-        use syntect::easy::HighlightLines;
-
         // Get syntax definition
         let syntax = self.get_syntax(file_path).or_else(|| {
             lines
@@ -72,10 +70,34 @@ impl SyntaxHighlighter {
                 .and_then(|line| self.syntax_set.find_syntax_by_first_line(line))
         })?;
 
-        // Create highlighter
+        Some(self.highlight_lines_with(syntax, lines))
+    }
+
+    /// Highlight a Markdown code block by its info string (e.g. `rust`, `py`).
+    ///
+    /// Returns `None` when the language is unknown.
+    pub fn highlight_code_block(
+        &self,
+        language: &str,
+        lines: &[String],
+    ) -> Option<HighlightedLines> {
+        let syntax = self.syntax_set.find_syntax_by_token(language).or_else(|| {
+            Self::fallback_extension(&language.to_ascii_lowercase())
+                .and_then(|ext| self.syntax_set.find_syntax_by_extension(ext))
+        })?;
+        Some(self.highlight_lines_with(syntax, lines))
+    }
+
+    fn highlight_lines_with(
+        &self,
+        syntax: &syntect::parsing::SyntaxReference,
+        lines: &[String],
+    ) -> HighlightedLines {
+        use syntect::easy::HighlightLines;
+
         let mut highlighter = HighlightLines::new(syntax, &self.theme);
 
-        Some(Self::collect_line_highlights(lines, |line| {
+        Self::collect_line_highlights(lines, |line| {
             // Highlight failures are scoped to the single line; other lines still keep highlighting.
             highlighter
                 .highlight_line(line, &self.syntax_set)
@@ -88,7 +110,7 @@ impl SyntaxHighlighter {
                         })
                         .collect()
                 })
-        }))
+        })
     }
 
     fn collect_line_highlights<F>(lines: &[String], mut highlight_line: F) -> HighlightedLines
@@ -223,7 +245,7 @@ impl SyntaxHighlighter {
     /// Resolve syntax from a file path using this lookup order:
     /// extension -> lowercase extension (when different) -> fallback extension ->
     /// filename token -> filename name -> fallback filename.
-    fn get_syntax(&self, file_path: &Path) -> Option<&syntect::parsing::SyntaxReference> {
+    pub fn get_syntax(&self, file_path: &Path) -> Option<&syntect::parsing::SyntaxReference> {
         // Try by extension first
         if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
             if let Some(syntax) = self.syntax_set.find_syntax_by_extension(ext) {
@@ -302,6 +324,20 @@ mod tests {
         assert!(syntax.is_some());
     }
 
+    #[test]
+    fn should_highlight_code_blocks_by_language_token() {
+        let highlighter = SyntaxHighlighter::default();
+        let lines = vec!["let x = 42;".to_string()];
+
+        assert!(highlighter.highlight_code_block("rust", &lines).is_some());
+        assert!(highlighter.highlight_code_block("rs", &lines).is_some());
+        assert!(
+            highlighter
+                .highlight_code_block("no-such-lang", &lines)
+                .is_none()
+        );
+    }
+
     #[test]
     fn should_highlight_each_line_independently() {
         let highlighter = SyntaxHighlighter::default();
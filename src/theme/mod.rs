@@ -8,6 +8,8 @@ use ratatui::style::Color;
 use two_face::theme::EmbeddedThemeName;
 
 use crate::config::config_path_hint;
+use crate::model::ReviewedPolicy;
+use crate::output::ExportFormat;
 use crate::syntax::SyntaxHighlighter;
 
 /// Complete color theme for the application
@@ -458,10 +460,137 @@ pub struct CliArgs {
     pub theme: Option<ThemeArg>,
     /// Output to stdout instead of clipboard when exporting
     pub output_to_stdout: bool,
+    /// Format reviews are exported in
+    pub export_format: ExportFormat,
     /// Skip checking for updates on startup
     pub no_update_check: bool,
     /// Commit/revision range to review
     pub revisions: Option<String>,
+    /// Globs limiting the review to matching files (repeatable)
+    pub paths: Vec<String>,
+    /// Globs for files to leave out of the review (repeatable)
+    pub excludes: Vec<String>,
+    /// Files of lint or compiler diagnostics to show on the diff (repeatable)
+    pub diagnostics: Vec<String>,
+    /// `tuicr sessions ...` subcommand
+    pub sessions: Option<SessionsCommand>,
+}
+
+/// Session management subcommand; sessions are addressed by their number in
+/// `tuicr sessions list`, their name or an id prefix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionsCommand {
+    List,
+    Open(String),
+    Rename {
+        session: String,
+        name: String,
+    },
+    Delete(String),
+    Pin(String),
+    Unpin(String),
+    /// Delete the sessions the retention settings don't keep
+    Gc,
+    /// Copy the repository's sessions (or one) from the user's sessions
+    /// directory into `.tuicr/reviews/`
+    Export(Option<String>),
+    /// Copy sessions (or one) from `.tuicr/reviews/` into the user's sessions
+    /// directory
+    Import(Option<String>),
+    /// Save the comments of two sessions, each a session file or a session,
+    /// as a new session
+    Merge {
+        first: String,
+        second: String,
+        reviewed: ReviewedPolicy,
+    },
+}
+
+/// Flags whose next argument is their value rather than a positional one
+const VALUE_FLAGS: [&str; 8] = [
+    "--theme",
+    "--export",
+    "-r",
+    "--revisions",
+    "--path",
+    "--exclude",
+    "--diagnostics",
+    "--reviewed",
+];
+
+impl SessionsCommand {
+    /// Parse the arguments following `sessions`
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        for (i, arg) in args.iter().enumerate() {
+            let is_flag_value = i > 0 && VALUE_FLAGS.contains(&args[i - 1].as_str());
+            if !arg.starts_with('-') && !is_flag_value {
+                positional.push(arg.as_str());
+            }
+        }
+
+        let session = |name: &str| {
+            positional
+                .get(1)
+                .map(|key| key.to_string())
+                .ok_or_else(|| format!("sessions {name} requires a session"))
+        };
+        match positional.first().copied().unwrap_or("list") {
+            "list" | "ls" => Ok(Self::List),
+            "open" => session("open").map(Self::Open),
+            "rename" => {
+                let session = session("rename")?;
+                let name = positional[2..].join(" ");
+                if name.is_empty() {
+                    return Err("sessions rename requires a name".to_string());
+                }
+                Ok(Self::Rename { session, name })
+            }
+            "delete" | "rm" => session("delete").map(Self::Delete),
+            "pin" => session("pin").map(Self::Pin),
+            "unpin" => session("unpin").map(Self::Unpin),
+            "gc" => Ok(Self::Gc),
+            "export" => Ok(Self::Export(positional.get(1).map(|key| key.to_string()))),
+            "import" => Ok(Self::Import(positional.get(1).map(|key| key.to_string()))),
+            "merge" => {
+                let [first, second] = positional[1..] else {
+                    return Err("sessions merge requires two sessions".to_string());
+                };
+                Ok(Self::Merge {
+                    first: first.to_string(),
+                    second: second.to_string(),
+                    reviewed: reviewed_policy(args)?,
+                })
+            }
+            other => Err(format!(
+                "Unknown sessions command '{other}'. Valid commands: list, open, rename, delete, pin, unpin, gc, import, export, merge"
+            )),
+        }
+    }
+}
+
+/// Value of `--reviewed` in `args`, the default policy without one
+fn reviewed_policy(args: &[String]) -> Result<ReviewedPolicy, String> {
+    let mut policy = ReviewedPolicy::default();
+    for (i, arg) in args.iter().enumerate() {
+        let value = if arg == "--reviewed" {
+            args.get(i + 1)
+                .filter(|value| !value.starts_with('-'))
+                .ok_or_else(|| {
+                    format!(
+                        "--reviewed requires a policy ({})",
+                        ReviewedPolicy::VALID_VALUES
+                    )
+                })?
+                .as_str()
+        } else if let Some(value) = arg.strip_prefix("--reviewed=") {
+            value
+        } else {
+            continue;
+        };
+        policy = value.parse()?;
+    }
+    Ok(policy)
 }
 
 impl ThemeArg {
@@ -554,20 +683,42 @@ fn print_help() -> ! {
         .unwrap_or_else(|| "tuicr".to_string());
     let valid_values = ThemeArg::valid_values_display();
     let config_path = config_path_hint();
+    let export_formats = ExportFormat::VALID_VALUES;
     println!(
         "tuicr - Review AI-generated diffs like a GitHub pull request
 
 Usage: {name} [OPTIONS]
+       {name} sessions [list|open|rename|delete|pin|unpin|gc|import|export] [SESSION] [NAME]
+       {name} sessions merge [--reviewed either|both] <SESSION|FILE> <SESSION|FILE>
 
 Options:
   -r, --revisions <REVSET>  Commit range/Revset to review (syntax depends on VCS backend)
+  --path <GLOB>          Only review files matching GLOB (repeatable)
+  --exclude <GLOB>       Leave files matching GLOB out of the review (repeatable)
+  --diagnostics <FILE>   Show lint or compiler diagnostics from FILE (cargo JSON,
+                         SARIF or file:line:col: message lines) on the diff (repeatable)
   --theme <THEME>        Color theme to use [default: dark]
                          Valid values: {valid_values}
                          Precedence: --theme > {config_path} > dark
   --stdout               Output to stdout instead of clipboard when exporting
+  --export <FORMAT>      Format reviews are exported in [default: markdown]
+                         Valid values: {export_formats}
   --no-update-check      Skip checking for updates on startup
   -h, --help             Print this help message
 
+Sessions:
+  SESSION is a number from `sessions list`, a session name or an id prefix.
+  Pinned sessions are never deleted; `sessions gc` deletes the others that
+  the [sessions] retention settings in the config don't keep, as does every
+  startup with [sessions] auto_gc = true.
+  Sessions are stored in $TUICR_REVIEWS_DIR, the config's [sessions] dir or
+  the data directory, in that order, or in the repository's .tuicr/reviews/
+  with [sessions] storage = \"repo\". `sessions export` copies this
+  repository's sessions into .tuicr/reviews/ and `sessions import` copies
+  them back. `sessions merge` saves the comments of two sessions of the same
+  change as a new session; with --reviewed both, a file is reviewed only if
+  both sessions reviewed it (default: either).
+
 Press ? in the application for keybinding help."
     );
     std::process::exit(0);
@@ -589,6 +740,10 @@ pub fn parse_cli_args() -> CliArgs {
 fn parse_cli_args_from(args: &[String]) -> Result<CliArgs, String> {
     let mut cli_args = CliArgs::default();
 
+    if args.get(1).is_some_and(|arg| arg == "sessions") {
+        cli_args.sessions = Some(SessionsCommand::parse(&args[2..])?);
+    }
+
     for i in 0..args.len() {
         // Handle --help / -h
         if args[i] == "--help" || args[i] == "-h" {
@@ -632,6 +787,24 @@ fn parse_cli_args_from(args: &[String]) -> Result<CliArgs, String> {
                 .map(Some)?;
         }
 
+        // Handle --export value
+        if args[i] == "--export" {
+            let value = args
+                .get(i + 1)
+                .filter(|value| !value.starts_with('-'))
+                .ok_or_else(|| {
+                    format!(
+                        "--export requires a format ({})",
+                        ExportFormat::VALID_VALUES
+                    )
+                })?;
+            cli_args.export_format = value.parse()?;
+        }
+        // Handle --export=value
+        if let Some(value) = args[i].strip_prefix("--export=") {
+            cli_args.export_format = value.parse()?;
+        }
+
         // Handle -r / --revisions value
         if args[i] == "-r" || args[i] == "--revisions" {
             if let Some(value) = args.get(i + 1) {
@@ -644,6 +817,30 @@ fn parse_cli_args_from(args: &[String]) -> Result<CliArgs, String> {
         if let Some(value) = args[i].strip_prefix("--revisions=") {
             cli_args.revisions = Some(value.to_string());
         }
+
+        // Handle --path / --exclude / --diagnostics value (repeatable)
+        for (flag, kind, values) in [
+            ("--path", "glob", &mut cli_args.paths),
+            ("--exclude", "glob", &mut cli_args.excludes),
+            ("--diagnostics", "file", &mut cli_args.diagnostics),
+        ] {
+            if args[i] == flag {
+                let value = args
+                    .get(i + 1)
+                    .filter(|value| !value.starts_with('-'))
+                    .ok_or_else(|| format!("{flag} requires a {kind}"))?;
+                values.push(value.clone());
+            }
+            if let Some(value) = args[i]
+                .strip_prefix(flag)
+                .and_then(|rest| rest.strip_prefix('='))
+            {
+                if value.is_empty() {
+                    return Err(format!("{flag} requires a {kind}"));
+                }
+                values.push(value.to_string());
+            }
+        }
     }
 
     Ok(cli_args)
@@ -700,6 +897,131 @@ mod tests {
         assert!(err.contains("--theme requires a value"));
     }
 
+    #[test]
+    fn should_collect_repeated_path_and_exclude_globs() {
+        let parsed = parse_for_test(&[
+            "tuicr",
+            "--path",
+            "src/",
+            "--exclude=*.lock",
+            "--path=docs/*.md",
+            "--exclude",
+            "**/__snapshots__/",
+        ])
+        .expect("parse should succeed");
+        assert_eq!(parsed.paths, vec!["src/", "docs/*.md"]);
+        assert_eq!(parsed.excludes, vec!["*.lock", "**/__snapshots__/"]);
+    }
+
+    #[test]
+    fn should_error_when_exclude_glob_missing() {
+        let err = parse_for_test(&["tuicr", "--exclude"]).expect_err("parse should fail");
+        assert!(err.contains("--exclude requires a glob"));
+
+        let err = parse_for_test(&["tuicr", "--path="]).expect_err("parse should fail");
+        assert!(err.contains("--path requires a glob"));
+    }
+
+    #[test]
+    fn should_collect_diagnostics_files() {
+        let parsed = parse_for_test(&[
+            "tuicr",
+            "--diagnostics",
+            "clippy.json",
+            "--diagnostics=eslint.sarif",
+        ])
+        .expect("parse should succeed");
+        assert_eq!(parsed.diagnostics, vec!["clippy.json", "eslint.sarif"]);
+
+        let err = parse_for_test(&["tuicr", "--diagnostics"]).expect_err("parse should fail");
+        assert!(err.contains("--diagnostics requires a file"));
+    }
+
+    #[test]
+    fn should_parse_export_format() {
+        let parsed = parse_for_test(&["tuicr", "--export", "sarif"]).expect("parse should succeed");
+        assert_eq!(parsed.export_format, ExportFormat::Sarif);
+
+        let parsed = parse_for_test(&["tuicr", "--stdout", "--export=markdown"])
+            .expect("parse should succeed");
+        assert_eq!(parsed.export_format, ExportFormat::Markdown);
+
+        let err = parse_for_test(&["tuicr", "--export", "html"]).expect_err("parse should fail");
+        assert!(err.contains("Unknown export format 'html'"));
+    }
+
+    #[test]
+    fn should_parse_sessions_subcommands() {
+        // when
+        let list = parse_for_test(&["tuicr", "sessions"]).expect("parse should succeed");
+        let rename = parse_for_test(&[
+            "tuicr", "sessions", "rename", "2", "--theme", "light", "auth", "fix",
+        ])
+        .expect("parse should succeed");
+
+        // then
+        assert_eq!(list.sessions, Some(SessionsCommand::List));
+        assert_eq!(
+            rename.sessions,
+            Some(SessionsCommand::Rename {
+                session: "2".to_string(),
+                name: "auth fix".to_string(),
+            })
+        );
+        assert_eq!(rename.theme, Some(ThemeArg::Light));
+        assert_eq!(
+            parse_for_test(&["tuicr", "sessions", "export"])
+                .expect("parse should succeed")
+                .sessions,
+            Some(SessionsCommand::Export(None))
+        );
+        assert_eq!(
+            parse_for_test(&["tuicr", "sessions", "merge", "a.json", "2"])
+                .expect("parse should succeed")
+                .sessions,
+            Some(SessionsCommand::Merge {
+                first: "a.json".to_string(),
+                second: "2".to_string(),
+                reviewed: ReviewedPolicy::Either,
+            })
+        );
+        assert_eq!(
+            parse_for_test(&[
+                "tuicr",
+                "sessions",
+                "merge",
+                "--reviewed",
+                "both",
+                "a.json",
+                "2"
+            ])
+            .expect("parse should succeed")
+            .sessions,
+            Some(SessionsCommand::Merge {
+                first: "a.json".to_string(),
+                second: "2".to_string(),
+                reviewed: ReviewedPolicy::Both,
+            })
+        );
+    }
+
+    #[test]
+    fn should_error_for_sessions_command_without_session() {
+        let err = parse_for_test(&["tuicr", "sessions", "open"]).expect_err("parse should fail");
+        assert!(err.contains("sessions open requires a session"));
+
+        let err = parse_for_test(&["tuicr", "sessions", "merge", "a.json"])
+            .expect_err("parse should fail");
+        assert!(err.contains("sessions merge requires two sessions"));
+
+        let err = parse_for_test(&["tuicr", "sessions", "merge", "--reviewed=all", "a", "b"])
+            .expect_err("parse should fail");
+        assert!(err.contains("Unknown reviewed policy 'all'"));
+
+        let err = parse_for_test(&["tuicr", "sessions", "nope"]).expect_err("parse should fail");
+        assert!(err.contains("Unknown sessions command 'nope'"));
+    }
+
     #[test]
     fn should_roundtrip_all_canonical_theme_values() {
         for (name, expected_theme) in ThemeArg::choices() {
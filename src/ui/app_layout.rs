@@ -1,16 +1,24 @@
+use std::path::Path;
+
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
-    style::Style,
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
 };
 use unicode_width::UnicodeWidthStr;
 
 use crate::app::{App, DiffViewMode, FileTreeItem, FocusedPanel, GapId, InputMode};
-use crate::model::{LineOrigin, LineRange, LineSide};
+use crate::diagnostics::{Diagnostic, Diagnostics, Severity};
+use crate::model::{DiffFile, DiffHunk, DiffLine, LineOrigin, LineRange, LineSide};
 use crate::theme::Theme;
-use crate::ui::{comment_panel, help_popup, status_bar, styles};
+use crate::ui::comment_text::CodeHighlights;
+use crate::ui::{
+    blame_gutter, comment_panel, help_popup, sessions_popup, snippet_popup, stats_popup,
+    status_bar, styles,
+};
+use crate::vcs::BlameLine;
 use crate::vcs::git::calculate_gap;
 
 pub fn render(frame: &mut Frame, app: &mut App) {
@@ -46,6 +54,21 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         help_popup::render_help(frame, app);
     }
 
+    if app.input_mode == InputMode::Stats {
+        stats_popup::render_stats(frame, app);
+    }
+
+    if app.input_mode == InputMode::SnippetPicker {
+        snippet_popup::render_snippet_picker(frame, app);
+    }
+
+    if matches!(
+        app.input_mode,
+        InputMode::Sessions | InputMode::SessionRename
+    ) {
+        sessions_popup::render_sessions(frame, app);
+    }
+
     // Comment input is now rendered inline in the diff view
 
     // Render confirm dialog if in confirm mode
@@ -263,6 +286,18 @@ fn render_main_content(frame: &mut Frame, app: &mut App, area: Rect) {
         area
     };
 
+    let content_area = if app.show_comments_panel {
+        let panel_height = (app.comment_entries().len() as u16 + 2).clamp(3, 10);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(panel_height)])
+            .split(content_area);
+        render_comments_panel(frame, app, chunks[1]);
+        chunks[0]
+    } else {
+        content_area
+    };
+
     if app.show_file_list {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
@@ -285,6 +320,69 @@ fn render_main_content(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 }
 
+fn render_comments_panel(frame: &mut Frame, app: &mut App, area: Rect) {
+    let focused = app.focused_panel == FocusedPanel::Comments;
+    let entries = app.comment_entries();
+    app.comments_panel_cursor = app
+        .comments_panel_cursor
+        .min(entries.len().saturating_sub(1));
+
+    let block = Block::default()
+        .title(format!(" Comments ({}) ", entries.len()))
+        .borders(Borders::ALL)
+        .style(styles::panel_style(&app.theme))
+        .border_style(styles::border_style(&app.theme, focused));
+
+    if entries.is_empty() {
+        let empty = Paragraph::new(Line::from(Span::styled(
+            " No comments yet",
+            styles::dim_style(&app.theme),
+        )))
+        .block(block);
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let location = match entry.line {
+                Some((line, LineSide::Old)) => format!("{}:~{line}", entry.path.display()),
+                Some((line, LineSide::New)) => format!("{}:{line}", entry.path.display()),
+                None => entry.path.display().to_string(),
+            };
+            let first_line = entry.comment.content.lines().next().unwrap_or("");
+            let comment_type = app.comment_types.resolve(&entry.comment.comment_type);
+            let style = if focused && i == app.comments_panel_cursor {
+                styles::selected_style(&app.theme)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {location}  "), style),
+                Span::styled(
+                    format!("[{}] ", comment_type.label()),
+                    styles::comment_type_style(&app.theme, &comment_type),
+                ),
+                Span::styled(
+                    entry
+                        .comment
+                        .author_initials()
+                        .map(|initials| format!("{initials} "))
+                        .unwrap_or_default(),
+                    styles::dim_style(&app.theme),
+                ),
+                Span::styled(first_line.to_string(), style),
+            ]))
+        })
+        .collect();
+
+    let mut state = ListState::default().with_selected(Some(app.comments_panel_cursor));
+    let list = List::new(items).block(block);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
 fn render_inline_commit_selector(frame: &mut Frame, app: &mut App, area: Rect) {
     let focused = app.focused_panel == FocusedPanel::CommitSelector;
     let block = Block::default()
@@ -389,8 +487,28 @@ fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let focused = app.focused_panel == FocusedPanel::FileList;
 
+    let mut title = " Files ".to_string();
+    if let Some(view) = app.session.file_list.label() {
+        title.push_str(&format!("[{view}] "));
+    }
+    if app.excluded_file_count > 0 {
+        title.push_str(&format!("({} excluded) ", app.excluded_file_count));
+    }
+    // The flat view has no directory rows, so files show their full path
+    let flat = app.session.file_list.flat;
+    let file_label = |file: &DiffFile| -> String {
+        let path = file.display_path();
+        if flat {
+            path.to_string_lossy().into_owned()
+        } else {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("?")
+                .to_string()
+        }
+    };
     let block = Block::default()
-        .title(" Files ")
+        .title(title)
         .borders(Borders::ALL)
         .style(styles::panel_style(&app.theme))
         .border_style(styles::border_style(&app.theme, focused));
@@ -410,12 +528,11 @@ fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
             }
             FileTreeItem::File { file_idx, depth } => {
                 let file = &app.diff_files[*file_idx];
-                let filename = file
-                    .display_path()
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("?");
-                depth * 2 + 3 + 3 + filename.width()
+                let stats_width: usize = file_stat_spans(&app.theme, file)
+                    .iter()
+                    .map(|span| span.width())
+                    .sum();
+                depth * 2 + 3 + 3 + file_label(file).width() + stats_width
             }
         })
         .max()
@@ -489,7 +606,7 @@ fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
                 FileTreeItem::File { file_idx, depth } => {
                     let file = &app.diff_files[*file_idx];
                     let path = file.display_path();
-                    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("?");
+                    let filename = file_label(file);
                     let status = file.status.as_char();
                     let is_reviewed = app.session.is_file_reviewed(path);
                     let review_mark = if is_reviewed { "✓" } else { " " };
@@ -502,7 +619,7 @@ fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
                         Style::default()
                     };
 
-                    let line = Line::from(vec![
+                    let mut line = Line::from(vec![
                         Span::styled(indent, Style::default()),
                         Span::styled(
                             format!("[{review_mark}]"),
@@ -516,8 +633,9 @@ fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
                             format!(" {status} "),
                             styles::file_status_style(&app.theme, status),
                         ),
-                        Span::styled(filename.to_string(), style),
+                        Span::styled(filename, style),
                     ]);
+                    line.spans.extend(file_stat_spans(&app.theme, file));
 
                     ListItem::new(apply_horizontal_scroll(line, scroll_x))
                 }
@@ -533,6 +651,8 @@ fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
 }
 
 fn render_diff_view(frame: &mut Frame, app: &mut App, area: Rect) {
+    app.load_visible_blame();
+    app.highlight_comment_code();
     match app.diff_view_mode {
         DiffViewMode::Unified => render_unified_diff(frame, app, area),
         DiffViewMode::SideBySide => render_side_by_side_diff(frame, app, area),
@@ -551,8 +671,9 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    // Update viewport height for scroll calculations
+    // Update viewport size for scroll calculations and comment wrapping
     app.diff_state.viewport_height = inner.height as usize;
+    app.set_diff_viewport_width(inner.width as usize);
 
     // Build all diff lines for infinite scroll
     // Track line index to mark the current line (cursor position)
@@ -564,35 +685,46 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
     // Store the logical line index and column where the cursor should be
     let mut comment_cursor_logical_line: Option<usize> = None;
     let mut comment_cursor_column: u16 = 0;
+    let now = chrono::Utc::now();
 
     for (file_idx, file) in app.diff_files.iter().enumerate() {
+        let hunks = app.shown_hunks(file_idx);
         let path = file.display_path();
         let status = file.status.as_char();
         let is_reviewed = app.session.is_file_reviewed(path);
+        let blame = app.blame_for_file(file_idx);
 
         // File header
         let indicator = cursor_indicator_spaced(line_idx, current_line_idx);
 
         // Add checkmark if reviewed (using same character as file list)
         let review_mark = if is_reviewed { "✓ " } else { "" };
+        let is_folded = app.is_file_folded(path);
+        let fold_mark = if is_folded { "⋯ " } else { "" };
 
         lines.push(Line::from(vec![
             Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
             Span::styled(
-                format!("═══ {}{} [{}] ", review_mark, path.display(), status),
+                format!(
+                    "═══ {}{} [{}] {}",
+                    review_mark,
+                    path.display(),
+                    status,
+                    fold_mark
+                ),
                 styles::file_header_style(&app.theme),
             ),
             Span::styled("═".repeat(40), styles::file_header_style(&app.theme)),
         ]));
         line_idx += 1;
 
-        // If file is reviewed, skip rendering the body (fold it away)
-        if is_reviewed {
+        // If file is folded, skip rendering the body
+        if is_folded {
             continue;
         }
 
         // Check if we're editing/adding a file-level comment for this file
-        let is_file_comment_mode = app.input_mode == InputMode::Comment
+        let is_file_comment_mode = app.is_writing_comment()
             && app.comment_is_file_level
             && file_idx == app.diff_state.current_file_idx;
 
@@ -607,7 +739,7 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                     // Render the inline input instead
                     let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                         &app.theme,
-                        app.comment_type,
+                        &app.comment_types.resolve(&app.comment_type),
                         &app.comment_buffer,
                         app.comment_cursor,
                         None,
@@ -634,9 +766,11 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                 } else {
                     let comment_lines = comment_panel::format_comment_lines(
                         &app.theme,
-                        comment.comment_type,
-                        &comment.content,
+                        &app.comment_types.resolve(&comment.comment_type),
+                        comment,
                         None,
+                        app.comment_text_width(),
+                        app.comment_highlights.get(comment),
                     );
                     for mut comment_line in comment_lines {
                         let indicator = cursor_indicator(line_idx, current_line_idx);
@@ -658,7 +792,7 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
         if is_file_comment_mode && app.editing_comment_id.is_none() {
             let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                 &app.theme,
-                app.comment_type,
+                &app.comment_types.resolve(&app.comment_type),
                 &app.comment_buffer,
                 app.comment_cursor,
                 None,
@@ -680,14 +814,18 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
             }
         }
 
-        if file.is_binary {
+        if app.is_file_collapsed(file) {
+            let indicator = cursor_indicator_spaced(line_idx, current_line_idx);
+            lines.push(collapsed_file_line(&app.theme, indicator, file));
+            line_idx += 1;
+        } else if file.is_binary {
             let indicator = cursor_indicator_spaced(line_idx, current_line_idx);
             lines.push(Line::from(vec![
                 Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
                 Span::styled("(binary file)", styles::dim_style(&app.theme)),
             ]));
             line_idx += 1;
-        } else if file.hunks.is_empty() {
+        } else if hunks.is_empty() {
             let indicator = cursor_indicator_spaced(line_idx, current_line_idx);
             lines.push(Line::from(vec![
                 Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
@@ -704,10 +842,10 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                 .cloned()
                 .unwrap_or_default();
 
-            for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+            for (hunk_idx, hunk) in hunks.iter().enumerate() {
                 // Calculate and render gap before this hunk
                 let prev_hunk = if hunk_idx > 0 {
-                    file.hunks.get(hunk_idx - 1)
+                    hunks.get(hunk_idx - 1)
                 } else {
                     None
                 };
@@ -724,27 +862,14 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                         if let Some(expanded_lines) = app.expanded_content.get(&gap_id) {
                             for expanded_line in expanded_lines {
                                 let indicator = cursor_indicator(line_idx, current_line_idx);
-                                let line_num = expanded_line
-                                    .new_lineno
-                                    .map(|n| format!("{n:>4} "))
-                                    .unwrap_or_else(|| "     ".to_string());
-
-                                let line_spans = vec![
-                                    Span::styled(
-                                        indicator,
-                                        styles::current_line_indicator_style(&app.theme),
-                                    ),
-                                    Span::styled(
-                                        line_num,
-                                        styles::expanded_context_style(&app.theme),
-                                    ),
-                                    Span::styled("  ", styles::expanded_context_style(&app.theme)),
-                                    Span::styled(
-                                        expanded_line.content.clone(),
-                                        styles::expanded_context_style(&app.theme),
-                                    ),
-                                ];
-                                lines.push(Line::from(line_spans));
+                                lines.push(expanded_context_line(
+                                    &app.theme,
+                                    app.show_blame,
+                                    blame,
+                                    indicator,
+                                    expanded_line,
+                                    now,
+                                ));
                                 line_idx += 1;
                             }
                         }
@@ -767,14 +892,17 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
 
                 // Hunk header
                 let indicator = cursor_indicator_spaced(line_idx, current_line_idx);
-                lines.push(Line::from(vec![
-                    Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
-                    Span::styled(
-                        hunk.header.to_string(),
-                        styles::diff_hunk_header_style(&app.theme),
-                    ),
-                ]));
+                let is_hunk_folded = app.is_hunk_folded(path, hunk_idx);
+                lines.push(hunk_header_line(
+                    &app.theme,
+                    indicator,
+                    hunk,
+                    is_hunk_folded,
+                ));
                 line_idx += 1;
+                if is_hunk_folded {
+                    continue;
+                }
 
                 // Diff lines
                 for diff_line in &hunk.lines {
@@ -832,11 +960,21 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                         styles::dim_style(&app.theme)
                     };
 
-                    let mut line_spans = vec![
-                        Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
+                    let mut line_spans = vec![Span::styled(
+                        indicator,
+                        styles::current_line_indicator_style(&app.theme),
+                    )];
+                    line_spans.extend(blame_gutter_span(
+                        &app.theme,
+                        app.show_blame,
+                        blame,
+                        diff_line.old_lineno,
+                        now,
+                    ));
+                    line_spans.extend([
                         Span::styled(line_num_str, line_num_style),
                         Span::styled(format!("{prefix} "), style),
-                    ];
+                    ]);
 
                     // Add content spans
                     if let Some(ref highlighted) = diff_line.highlighted_spans {
@@ -857,11 +995,21 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                     lines.push(Line::from(line_spans));
                     line_idx += 1;
 
+                    if let Some(new_ln) = diff_line.new_lineno {
+                        let comments = line_comments.get(&new_ln).map_or(&[][..], Vec::as_slice);
+                        for (_, diagnostic) in app.diagnostics.shown_on_line(path, new_ln, comments)
+                        {
+                            let indicator = cursor_indicator(line_idx, current_line_idx);
+                            lines.push(diagnostic_line(&app.theme, indicator, diagnostic));
+                            line_idx += 1;
+                        }
+                    }
+
                     // Show line comments for both old side (deleted lines) and new side (added/context)
                     // Old side comments (for deleted lines)
                     if let Some(old_ln) = diff_line.old_lineno {
                         // Check if we're adding/editing a comment on this line (old side)
-                        let is_line_comment_mode = app.input_mode == InputMode::Comment
+                        let is_line_comment_mode = app.is_writing_comment()
                             && !app.comment_is_file_level
                             && file_idx == app.diff_state.current_file_idx
                             && app.comment_line == Some((old_ln, LineSide::Old));
@@ -881,7 +1029,7 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                                         let (input_lines, cursor_info) =
                                             comment_panel::format_comment_input_lines(
                                                 &app.theme,
-                                                app.comment_type,
+                                                &app.comment_types.resolve(&app.comment_type),
                                                 &app.comment_buffer,
                                                 app.comment_cursor,
                                                 line_range,
@@ -913,9 +1061,11 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                                             .or_else(|| Some(LineRange::single(old_ln)));
                                         let comment_lines = comment_panel::format_comment_lines(
                                             &app.theme,
-                                            comment.comment_type,
-                                            &comment.content,
+                                            &app.comment_types.resolve(&comment.comment_type),
+                                            comment,
                                             line_range,
+                                            app.comment_text_width(),
+                                            app.comment_highlights.get(comment),
                                         );
                                         for mut comment_line in comment_lines {
                                             let is_current = line_idx == current_line_idx;
@@ -946,7 +1096,7 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                             let (input_lines, cursor_info) =
                                 comment_panel::format_comment_input_lines(
                                     &app.theme,
-                                    app.comment_type,
+                                    &app.comment_types.resolve(&app.comment_type),
                                     &app.comment_buffer,
                                     app.comment_cursor,
                                     line_range,
@@ -974,7 +1124,7 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                     // New side comments (for added/context lines)
                     if let Some(new_ln) = diff_line.new_lineno {
                         // Check if we're adding/editing a comment on this line (new side)
-                        let is_line_comment_mode = app.input_mode == InputMode::Comment
+                        let is_line_comment_mode = app.is_writing_comment()
                             && !app.comment_is_file_level
                             && file_idx == app.diff_state.current_file_idx
                             && app.comment_line == Some((new_ln, LineSide::New));
@@ -994,7 +1144,7 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                                         let (input_lines, cursor_info) =
                                             comment_panel::format_comment_input_lines(
                                                 &app.theme,
-                                                app.comment_type,
+                                                &app.comment_types.resolve(&app.comment_type),
                                                 &app.comment_buffer,
                                                 app.comment_cursor,
                                                 line_range,
@@ -1026,9 +1176,11 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                                             .or_else(|| Some(LineRange::single(new_ln)));
                                         let comment_lines = comment_panel::format_comment_lines(
                                             &app.theme,
-                                            comment.comment_type,
-                                            &comment.content,
+                                            &app.comment_types.resolve(&comment.comment_type),
+                                            comment,
                                             line_range,
+                                            app.comment_text_width(),
+                                            app.comment_highlights.get(comment),
                                         );
                                         for mut comment_line in comment_lines {
                                             let indicator =
@@ -1059,7 +1211,7 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                             let (input_lines, cursor_info) =
                                 comment_panel::format_comment_input_lines(
                                     &app.theme,
-                                    app.comment_type,
+                                    &app.comment_types.resolve(&app.comment_type),
                                     &app.comment_buffer,
                                     app.comment_cursor,
                                     line_range,
@@ -1085,6 +1237,26 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                     }
                 }
             }
+
+            // Trailing context after the last hunk, once expanded
+            let tail_gap = GapId {
+                file_idx,
+                hunk_idx: hunks.len(),
+            };
+            if let Some(expanded_lines) = app.expanded_content.get(&tail_gap) {
+                for expanded_line in expanded_lines {
+                    let indicator = cursor_indicator(line_idx, current_line_idx);
+                    lines.push(expanded_context_line(
+                        &app.theme,
+                        app.show_blame,
+                        blame,
+                        indicator,
+                        expanded_line,
+                        now,
+                    ));
+                    line_idx += 1;
+                }
+            }
         }
 
         // Spacing between files
@@ -1115,7 +1287,6 @@ fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let max_content_width = line_widths.iter().copied().max().unwrap_or(0);
 
-    app.diff_state.viewport_width = inner.width as usize;
     app.diff_state.max_content_width = max_content_width;
 
     // Calculate how many logical lines actually fit in the viewport when wrapped
@@ -1218,15 +1389,219 @@ struct SideBySideContext<'a> {
     theme: &'a Theme,
     content_width: usize,
     current_line_idx: usize,
+    // Blame gutter state; `blame` is set per file
+    show_blame: bool,
+    blame: Option<&'a [BlameLine]>,
+    now: chrono::DateTime<chrono::Utc>,
+    // Diagnostics and the path of the file being rendered
+    diagnostics: &'a Diagnostics,
+    path: &'a Path,
     // Comment input state for inline editing
     comment_input_mode: bool,
     comment_line: Option<(u32, LineSide)>,
-    comment_type: crate::model::CommentType,
+    comment_types: &'a crate::model::CommentTypes,
+    comment_type: &'a crate::model::CommentType,
     comment_buffer: &'a str,
     comment_cursor: usize,
     comment_line_range: Option<LineRange>,
     editing_comment_id: Option<&'a str>,
     supports_keyboard_enhancement: bool,
+    // Comment text width and code highlighting
+    comment_width: usize,
+    comment_highlights: &'a CodeHighlights,
+}
+
+/// Blame gutter for an old-side line, or `None` when the gutter is hidden.
+/// Lines without an old-side line number get a blank gutter to keep alignment.
+fn blame_gutter_span(
+    theme: &Theme,
+    show_blame: bool,
+    blame: Option<&[BlameLine]>,
+    old_lineno: Option<u32>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<Span<'static>> {
+    if !show_blame {
+        return None;
+    }
+    let line_blame = old_lineno
+        .zip(blame)
+        .and_then(|(ln, blame)| blame.get((ln as usize).checked_sub(1)?));
+    Some(blame_gutter::gutter_span(theme, line_blame, now))
+}
+
+/// Hunk header line, noting how many lines are hidden when folded
+fn hunk_header_line(
+    theme: &Theme,
+    indicator: &'static str,
+    hunk: &DiffHunk,
+    folded: bool,
+) -> Line<'static> {
+    let header = if folded {
+        format!("{} ⋯ {} lines folded", hunk.header, hunk.lines.len())
+    } else {
+        hunk.header.clone()
+    };
+    Line::from(vec![
+        Span::styled(indicator, styles::current_line_indicator_style(theme)),
+        Span::styled(header, styles::diff_hunk_header_style(theme)),
+    ])
+}
+
+/// Width of the added/deleted bar after each file list entry
+const STAT_BAR_WIDTH: usize = 5;
+
+/// Compact ` +N/-M ■■■□□` summary of a file's changes, empty when nothing
+/// changed line-wise (binary files, pure renames, commit messages).
+fn file_stat_spans(theme: &Theme, file: &DiffFile) -> Vec<Span<'static>> {
+    let (added, removed) = file.line_stats();
+    let churn = added + removed;
+    if churn == 0 || file.is_commit_message() {
+        return Vec::new();
+    }
+    // Any non-zero side gets at least one cell
+    let add_cells = (added * STAT_BAR_WIDTH)
+        .div_ceil(churn)
+        .min(STAT_BAR_WIDTH - usize::from(removed > 0));
+    let del_cells = STAT_BAR_WIDTH - add_cells;
+    vec![
+        Span::raw(" "),
+        Span::styled(format!("+{added}"), styles::stat_add_style(theme)),
+        Span::styled("/", styles::dim_style(theme)),
+        Span::styled(format!("-{removed}"), styles::stat_del_style(theme)),
+        Span::raw(" "),
+        Span::styled("■".repeat(add_cells), styles::stat_add_style(theme)),
+        Span::styled("■".repeat(del_cells), styles::stat_del_style(theme)),
+    ]
+}
+
+/// Placeholder line for a generated or vendored file that is collapsed
+fn collapsed_file_line(theme: &Theme, indicator: &'static str, file: &DiffFile) -> Line<'static> {
+    let (added, removed) = file.line_stats();
+    Line::from(vec![
+        Span::styled(indicator, styles::current_line_indicator_style(theme)),
+        Span::styled(
+            format!("(generated file, +{added} -{removed} hidden; Enter or X to expand)"),
+            styles::dim_style(theme),
+        ),
+    ])
+}
+
+/// Render an expanded (hidden by default) context line in unified mode
+fn expanded_context_line(
+    theme: &Theme,
+    show_blame: bool,
+    blame: Option<&[BlameLine]>,
+    indicator: &'static str,
+    expanded_line: &DiffLine,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Line<'static> {
+    let line_num = expanded_line
+        .new_lineno
+        .map(|n| format!("{n:>4} "))
+        .unwrap_or_else(|| "     ".to_string());
+
+    let mut line_spans = vec![Span::styled(
+        indicator,
+        styles::current_line_indicator_style(theme),
+    )];
+    line_spans.extend(blame_gutter_span(
+        theme,
+        show_blame,
+        blame,
+        expanded_line.old_lineno,
+        now,
+    ));
+    line_spans.extend([
+        Span::styled(line_num, styles::expanded_context_style(theme)),
+        Span::styled("  ", styles::expanded_context_style(theme)),
+        Span::styled(
+            expanded_line.content.clone(),
+            styles::expanded_context_style(theme),
+        ),
+    ]);
+    Line::from(line_spans)
+}
+
+/// A diagnostic, shown under the line it was reported on
+fn diagnostic_line(
+    theme: &Theme,
+    indicator: &'static str,
+    diagnostic: &Diagnostic,
+) -> Line<'static> {
+    let style = styles::diagnostic_style(theme, diagnostic.severity);
+    let icon = match diagnostic.severity {
+        Severity::Error => "✖",
+        Severity::Warning => "▲",
+        Severity::Note => "●",
+    };
+    let mut spans = vec![
+        Span::styled(indicator, styles::current_line_indicator_style(theme)),
+        Span::styled(
+            format!("       {icon} {}: ", diagnostic.severity.label()),
+            style.add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            diagnostic
+                .message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            style,
+        ),
+    ];
+    let source: Vec<&str> = [diagnostic.tool.as_deref(), diagnostic.code.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect();
+    if !source.is_empty() {
+        spans.push(Span::styled(
+            format!(" [{}]", source.join(" ")),
+            styles::dim_style(theme),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Render an expanded context line in side-by-side mode, showing it on both sides
+fn expanded_context_line_side_by_side(
+    ctx: &SideBySideContext,
+    indicator: &'static str,
+    expanded_line: &DiffLine,
+) -> Line<'static> {
+    let theme = ctx.theme;
+    let line_num = expanded_line
+        .new_lineno
+        .map(|n| format!("{n:>4} "))
+        .unwrap_or_else(|| "     ".to_string());
+
+    let mut line_spans = vec![Span::styled(
+        indicator,
+        styles::current_line_indicator_style(theme),
+    )];
+    line_spans.extend(blame_gutter_span(
+        theme,
+        ctx.show_blame,
+        ctx.blame,
+        expanded_line.old_lineno,
+        ctx.now,
+    ));
+    line_spans.extend([
+        Span::styled(line_num.clone(), styles::expanded_context_style(theme)),
+        Span::styled("  ", styles::expanded_context_style(theme)),
+        Span::styled(
+            truncate_or_pad(&expanded_line.content, ctx.content_width),
+            styles::expanded_context_style(theme),
+        ),
+        Span::styled(" │ ", styles::dim_style(theme)),
+        Span::styled(line_num, styles::expanded_context_style(theme)),
+        Span::styled("  ", styles::expanded_context_style(theme)),
+        Span::styled(
+            truncate_or_pad(&expanded_line.content, ctx.content_width),
+            styles::expanded_context_style(theme),
+        ),
+    ]);
+    Line::from(line_spans)
 }
 
 /// Get cursor indicator (single character for inline content)
@@ -1259,30 +1634,44 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    // Update viewport height for scroll calculations
+    // Update viewport size for scroll calculations and comment wrapping
     app.diff_state.viewport_height = inner.height as usize;
+    app.set_diff_viewport_width(inner.width as usize);
 
     // Calculate column widths (split the area in half)
     // Layout: indicator(1) + linenum(4) + space(1) + prefix(1) + content + " │ "(3) + linenum(4) + space(1) + prefix(1) + content
     // Total overhead: 1 + 5 + 1 + 3 + 5 + 1 = 16
-    let available_width = inner.width.saturating_sub(16) as usize;
+    let blame_width = if app.show_blame {
+        blame_gutter::GUTTER_WIDTH as u16
+    } else {
+        0
+    };
+    let available_width = inner.width.saturating_sub(16 + blame_width) as usize;
     let content_width = available_width / 2;
 
     // Determine if we're in line comment mode (not file-level)
-    let comment_input_mode = app.input_mode == InputMode::Comment && !app.comment_is_file_level;
+    let comment_input_mode = app.is_writing_comment() && !app.comment_is_file_level;
 
-    let ctx = SideBySideContext {
+    let mut ctx = SideBySideContext {
         theme: &app.theme,
         content_width,
         current_line_idx: app.diff_state.cursor_line,
+        show_blame: app.show_blame,
+        blame: None,
+        now: chrono::Utc::now(),
+        diagnostics: &app.diagnostics,
+        path: Path::new(""),
         comment_input_mode,
         comment_line: app.comment_line,
-        comment_type: app.comment_type,
+        comment_types: &app.comment_types,
+        comment_type: &app.comment_type,
         comment_buffer: &app.comment_buffer,
         comment_cursor: app.comment_cursor,
         comment_line_range: app.comment_line_range.map(|(r, _)| r),
         editing_comment_id: app.editing_comment_id.as_deref(),
         supports_keyboard_enhancement: app.supports_keyboard_enhancement,
+        comment_width: app.comment_text_width(),
+        comment_highlights: &app.comment_highlights,
     };
 
     // Build all diff lines for side-by-side view
@@ -1294,32 +1683,43 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
     let mut comment_cursor_column: u16 = 0;
 
     for (file_idx, file) in app.diff_files.iter().enumerate() {
+        let hunks = app.shown_hunks(file_idx);
         let path = file.display_path();
         let status = file.status.as_char();
         let is_reviewed = app.session.is_file_reviewed(path);
+        ctx.blame = app.blame_for_file(file_idx);
+        ctx.path = path;
 
         // File header
         let indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
 
         let review_mark = if is_reviewed { "✓ " } else { "" };
+        let is_folded = app.is_file_folded(path);
+        let fold_mark = if is_folded { "⋯ " } else { "" };
 
         lines.push(Line::from(vec![
             Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
             Span::styled(
-                format!("═══ {}{} [{}] ", review_mark, path.display(), status),
+                format!(
+                    "═══ {}{} [{}] {}",
+                    review_mark,
+                    path.display(),
+                    status,
+                    fold_mark
+                ),
                 styles::file_header_style(&app.theme),
             ),
             Span::styled("═".repeat(40), styles::file_header_style(&app.theme)),
         ]));
         line_idx += 1;
 
-        // If file is reviewed, skip rendering the body
-        if is_reviewed {
+        // If file is folded, skip rendering the body
+        if is_folded {
             continue;
         }
 
         // Check if we're editing/adding a file-level comment for this file
-        let is_file_comment_mode = app.input_mode == InputMode::Comment
+        let is_file_comment_mode = app.is_writing_comment()
             && app.comment_is_file_level
             && file_idx == app.diff_state.current_file_idx;
 
@@ -1334,7 +1734,7 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                     // Render the inline input instead
                     let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                         &app.theme,
-                        app.comment_type,
+                        &app.comment_types.resolve(&app.comment_type),
                         &app.comment_buffer,
                         app.comment_cursor,
                         None,
@@ -1359,9 +1759,11 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                 } else {
                     let comment_lines = comment_panel::format_comment_lines(
                         &app.theme,
-                        comment.comment_type,
-                        &comment.content,
+                        &app.comment_types.resolve(&comment.comment_type),
+                        comment,
                         None,
+                        app.comment_text_width(),
+                        app.comment_highlights.get(comment),
                     );
                     for mut comment_line in comment_lines {
                         let indicator = cursor_indicator(line_idx, ctx.current_line_idx);
@@ -1383,7 +1785,7 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
         if is_file_comment_mode && app.editing_comment_id.is_none() {
             let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                 &app.theme,
-                app.comment_type,
+                &app.comment_types.resolve(&app.comment_type),
                 &app.comment_buffer,
                 app.comment_cursor,
                 None,
@@ -1404,14 +1806,18 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
             }
         }
 
-        if file.is_binary {
+        if app.is_file_collapsed(file) {
+            let indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
+            lines.push(collapsed_file_line(&app.theme, indicator, file));
+            line_idx += 1;
+        } else if file.is_binary {
             let indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
             lines.push(Line::from(vec![
                 Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
                 Span::styled("(binary file)", styles::dim_style(&app.theme)),
             ]));
             line_idx += 1;
-        } else if file.hunks.is_empty() {
+        } else if hunks.is_empty() {
             let indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
             lines.push(Line::from(vec![
                 Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
@@ -1427,10 +1833,10 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                 .cloned()
                 .unwrap_or_default();
 
-            for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+            for (hunk_idx, hunk) in hunks.iter().enumerate() {
                 // Calculate and render gap before this hunk
                 let prev_hunk = if hunk_idx > 0 {
-                    file.hunks.get(hunk_idx - 1)
+                    hunks.get(hunk_idx - 1)
                 } else {
                     None
                 };
@@ -1447,38 +1853,11 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                         if let Some(expanded_lines) = app.expanded_content.get(&gap_id) {
                             for expanded_line in expanded_lines {
                                 let indicator = cursor_indicator(line_idx, ctx.current_line_idx);
-                                let line_num = expanded_line
-                                    .new_lineno
-                                    .map(|n| format!("{n:>4} "))
-                                    .unwrap_or_else(|| "     ".to_string());
-
-                                // In side-by-side, show context on both sides
-                                let line_spans = vec![
-                                    Span::styled(
-                                        indicator,
-                                        styles::current_line_indicator_style(&app.theme),
-                                    ),
-                                    Span::styled(
-                                        line_num.clone(),
-                                        styles::expanded_context_style(&app.theme),
-                                    ),
-                                    Span::styled("  ", styles::expanded_context_style(&app.theme)),
-                                    Span::styled(
-                                        truncate_or_pad(&expanded_line.content, ctx.content_width),
-                                        styles::expanded_context_style(&app.theme),
-                                    ),
-                                    Span::styled(" │ ", styles::dim_style(&app.theme)),
-                                    Span::styled(
-                                        line_num,
-                                        styles::expanded_context_style(&app.theme),
-                                    ),
-                                    Span::styled("  ", styles::expanded_context_style(&app.theme)),
-                                    Span::styled(
-                                        truncate_or_pad(&expanded_line.content, ctx.content_width),
-                                        styles::expanded_context_style(&app.theme),
-                                    ),
-                                ];
-                                lines.push(Line::from(line_spans));
+                                lines.push(expanded_context_line_side_by_side(
+                                    &ctx,
+                                    indicator,
+                                    expanded_line,
+                                ));
                                 line_idx += 1;
                             }
                         }
@@ -1501,14 +1880,17 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
 
                 // Hunk header
                 let indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
-                lines.push(Line::from(vec![
-                    Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
-                    Span::styled(
-                        hunk.header.to_string(),
-                        styles::diff_hunk_header_style(&app.theme),
-                    ),
-                ]));
+                let is_hunk_folded = app.is_hunk_folded(path, hunk_idx);
+                lines.push(hunk_header_line(
+                    &app.theme,
+                    indicator,
+                    hunk,
+                    is_hunk_folded,
+                ));
                 line_idx += 1;
+                if is_hunk_folded {
+                    continue;
+                }
 
                 // Process diff lines in side-by-side format
                 let (new_line_idx, cursor_info) = render_hunk_lines_side_by_side(
@@ -1524,6 +1906,23 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
                     comment_cursor_column = cursor_info.map(|(_, col)| col).unwrap_or(0);
                 }
             }
+
+            // Trailing context after the last hunk, once expanded
+            let tail_gap = GapId {
+                file_idx,
+                hunk_idx: hunks.len(),
+            };
+            if let Some(expanded_lines) = app.expanded_content.get(&tail_gap) {
+                for expanded_line in expanded_lines {
+                    let indicator = cursor_indicator(line_idx, ctx.current_line_idx);
+                    lines.push(expanded_context_line_side_by_side(
+                        &ctx,
+                        indicator,
+                        expanded_line,
+                    ));
+                    line_idx += 1;
+                }
+            }
         }
 
         // Spacing between files
@@ -1554,7 +1953,6 @@ fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let max_content_width = line_widths.iter().copied().max().unwrap_or(0);
 
-    app.diff_state.viewport_width = inner.width as usize;
     app.diff_state.max_content_width = max_content_width;
 
     // Calculate how many logical lines actually fit in the viewport when wrapped
@@ -1728,11 +2126,21 @@ fn render_context_line_side_by_side(
 
     let indicator = cursor_indicator(line_idx, ctx.current_line_idx);
 
-    let mut spans = vec![
-        Span::styled(indicator, styles::current_line_indicator_style(ctx.theme)),
+    let mut spans = vec![Span::styled(
+        indicator,
+        styles::current_line_indicator_style(ctx.theme),
+    )];
+    spans.extend(blame_gutter_span(
+        ctx.theme,
+        ctx.show_blame,
+        ctx.blame,
+        diff_line.old_lineno,
+        ctx.now,
+    ));
+    spans.extend([
         Span::styled(format!("{line_num} "), styles::dim_style(ctx.theme)),
         Span::styled(" ".to_string(), styles::diff_context_style(ctx.theme)),
-    ];
+    ]);
 
     // Left side content - use syntax highlighting if available
     if let Some(ref highlighted) = diff_line.highlighted_spans {
@@ -1774,9 +2182,10 @@ fn render_context_line_side_by_side(
     lines.push(Line::from(spans));
     line_idx += 1;
 
-    // Add comments if any
+    // Add diagnostics and comments if any
     let mut cursor_info_out: Option<(usize, u16)> = None;
     if let Some(new_ln) = diff_line.new_lineno {
+        line_idx = add_diagnostics_to_line(new_ln, line_comments, ctx, line_idx, lines);
         let (new_line_idx, cursor_info) =
             add_comments_to_line(new_ln, line_comments, LineSide::New, ctx, line_idx, lines);
         line_idx = new_line_idx;
@@ -1822,6 +2231,16 @@ fn render_deletion_addition_pair_side_by_side(
             indicator,
             styles::current_line_indicator_style(ctx.theme),
         )];
+        let del_old_lineno = (offset < del_count)
+            .then(|| hunk_lines[start_idx + offset].old_lineno)
+            .flatten();
+        spans.extend(blame_gutter_span(
+            ctx.theme,
+            ctx.show_blame,
+            ctx.blame,
+            del_old_lineno,
+            ctx.now,
+        ));
 
         // Left side (deletion)
         if offset < del_count {
@@ -1844,6 +2263,13 @@ fn render_deletion_addition_pair_side_by_side(
         lines.push(Line::from(spans));
         line_idx += 1;
 
+        if let Some(new_ln) = (offset < add_count)
+            .then(|| hunk_lines[add_start + offset].new_lineno)
+            .flatten()
+        {
+            line_idx = add_diagnostics_to_line(new_ln, line_comments, ctx, line_idx, lines);
+        }
+
         // Add comments for deletion
         if offset < del_count {
             let del_line = &hunk_lines[start_idx + offset];
@@ -1901,6 +2327,13 @@ fn render_standalone_addition_side_by_side(
         indicator,
         styles::current_line_indicator_style(ctx.theme),
     )];
+    spans.extend(blame_gutter_span(
+        ctx.theme,
+        ctx.show_blame,
+        None,
+        None,
+        ctx.now,
+    ));
     add_empty_column_spans(&mut spans, ctx.content_width);
     spans.push(Span::styled(" │ ", styles::dim_style(ctx.theme)));
     add_addition_spans(ctx.theme, &mut spans, diff_line, ctx.content_width);
@@ -1908,9 +2341,10 @@ fn render_standalone_addition_side_by_side(
     lines.push(Line::from(spans));
     line_idx += 1;
 
-    // Add comments if any
+    // Add diagnostics and comments if any
     let mut cursor_info_out: Option<(usize, u16)> = None;
     if let Some(new_ln) = diff_line.new_lineno {
+        line_idx = add_diagnostics_to_line(new_ln, line_comments, ctx, line_idx, lines);
         let (new_line_idx, cursor_info) =
             add_comments_to_line(new_ln, line_comments, LineSide::New, ctx, line_idx, lines);
         line_idx = new_line_idx;
@@ -1920,6 +2354,24 @@ fn render_standalone_addition_side_by_side(
     (line_idx, cursor_info_out)
 }
 
+/// Add the diagnostics shown under new-side line `line_num`
+/// Returns the new line_idx
+fn add_diagnostics_to_line(
+    line_num: u32,
+    line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
+    ctx: &SideBySideContext,
+    mut line_idx: usize,
+    lines: &mut Vec<Line>,
+) -> usize {
+    let comments = line_comments.get(&line_num).map_or(&[][..], Vec::as_slice);
+    for (_, diagnostic) in ctx.diagnostics.shown_on_line(ctx.path, line_num, comments) {
+        let indicator = cursor_indicator(line_idx, ctx.current_line_idx);
+        lines.push(diagnostic_line(ctx.theme, indicator, diagnostic));
+        line_idx += 1;
+    }
+    line_idx
+}
+
 /// Add deletion line spans to the spans vector
 fn add_deletion_spans(
     theme: &Theme,
@@ -2020,7 +2472,7 @@ fn add_comments_to_line(
                         .or_else(|| Some(LineRange::single(line_num)));
                     let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                         ctx.theme,
-                        ctx.comment_type,
+                        &ctx.comment_types.resolve(ctx.comment_type),
                         ctx.comment_buffer,
                         ctx.comment_cursor,
                         line_range,
@@ -2048,9 +2500,11 @@ fn add_comments_to_line(
                         .or_else(|| Some(LineRange::single(line_num)));
                     let comment_lines = comment_panel::format_comment_lines(
                         ctx.theme,
-                        comment.comment_type,
-                        &comment.content,
+                        &ctx.comment_types.resolve(&comment.comment_type),
+                        comment,
                         line_range,
+                        ctx.comment_width,
+                        ctx.comment_highlights.get(comment),
                     );
                     for mut comment_line in comment_lines {
                         let indicator = cursor_indicator(line_idx, ctx.current_line_idx);
@@ -2076,7 +2530,7 @@ fn add_comments_to_line(
             .or_else(|| Some(LineRange::single(line_num)));
         let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
             ctx.theme,
-            ctx.comment_type,
+            &ctx.comment_types.resolve(ctx.comment_type),
             ctx.comment_buffer,
             ctx.comment_cursor,
             line_range,
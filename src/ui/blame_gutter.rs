@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use ratatui::text::Span;
+
+use crate::theme::Theme;
+use crate::ui::styles;
+use crate::vcs::BlameLine;
+
+/// Display width of the blame gutter: 7-char commit, space, 4-char age, space
+pub const GUTTER_WIDTH: usize = 13;
+
+/// Build the blame gutter span for a line.
+/// Lines without blame (additions, unknown lines) get a blank gutter to keep columns aligned.
+pub fn gutter_span(theme: &Theme, blame: Option<&BlameLine>, now: DateTime<Utc>) -> Span<'static> {
+    match blame {
+        Some(blame) => {
+            let short_id: String = blame.short_id.chars().take(7).collect();
+            Span::styled(
+                format!("{short_id:<7} {:>4} ", format_age(blame.time, now)),
+                styles::blame_style(theme),
+            )
+        }
+        None => Span::raw(" ".repeat(GUTTER_WIDTH)),
+    }
+}
+
+/// Format the age of a commit compactly, e.g. `3h`, `12d`, `5mo`, `2y`.
+pub fn format_age(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = now.signed_duration_since(time);
+    let days = age.num_days();
+    if age.num_hours() < 1 {
+        "now".to_string()
+    } else if days < 1 {
+        format!("{}h", age.num_hours())
+    } else if days < 30 {
+        format!("{days}d")
+    } else if days < 365 {
+        format!("{}mo", days / 30)
+    } else {
+        format!("{}y", days / 365)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn should_format_age_in_largest_sensible_unit() {
+        // given
+        let now = Utc::now();
+
+        // when / then
+        assert_eq!(format_age(now - Duration::minutes(5), now), "now");
+        assert_eq!(format_age(now - Duration::hours(3), now), "3h");
+        assert_eq!(format_age(now - Duration::days(12), now), "12d");
+        assert_eq!(format_age(now - Duration::days(95), now), "3mo");
+        assert_eq!(format_age(now - Duration::days(800), now), "2y");
+    }
+
+    #[test]
+    fn should_pad_gutter_to_fixed_width() {
+        // given
+        let theme = Theme::dark();
+        let now = Utc::now();
+        let blame = BlameLine {
+            commit_id: "0123456789abcdef".to_string(),
+            short_id: "0123456789ab".to_string(),
+            author: "Test User".to_string(),
+            time: now - Duration::days(12),
+        };
+
+        // when
+        let filled = gutter_span(&theme, Some(&blame), now);
+        let blank = gutter_span(&theme, None, now);
+
+        // then
+        assert_eq!(filled.content, "0123456  12d ");
+        assert_eq!(filled.content.len(), GUTTER_WIDTH);
+        assert_eq!(blank.content.len(), GUTTER_WIDTH);
+    }
+}
@@ -8,8 +8,10 @@ use ratatui::{
 use unicode_width::UnicodeWidthStr;
 
 use crate::app::App;
-use crate::model::{CommentType, LineRange};
+use crate::model::{Comment, CommentTypeDef, LineRange};
+use crate::syntax::HighlightedLines;
 use crate::theme::Theme;
+use crate::ui::comment_text::{self, SpanKind};
 use crate::ui::styles;
 
 /// Information about where the cursor should be positioned within comment input
@@ -31,7 +33,7 @@ pub struct CommentCursorInfo {
 /// of the cursor within the formatted output for IME positioning.
 pub fn format_comment_input_lines(
     theme: &Theme,
-    comment_type: CommentType,
+    comment_type: &CommentTypeDef,
     buffer: &str,
     cursor_pos: usize,
     line_range: Option<LineRange>,
@@ -69,7 +71,7 @@ pub fn format_comment_input_lines(
     result.push(Line::from(vec![
         Span::styled("     ╭─ ", border_style),
         Span::styled(format!("{} ", action), styles::dim_style(theme)),
-        Span::styled(format!("[{}] ", comment_type.as_str()), type_style),
+        Span::styled(format!("[{}] ", comment_type.label()), type_style),
         Span::styled(line_info, styles::dim_style(theme)),
         Span::styled(
             format!("(Tab:type Enter:save {}:newline Esc:cancel)", newline_hint),
@@ -150,50 +152,117 @@ pub fn format_comment_input_lines(
     (result, cursor_info)
 }
 
-/// Format a comment as multiple lines with a box border (themed version)
+/// Format a comment as multiple lines with a box border (themed version),
+/// wrapping its text to `width` columns and coloring its code blocks from
+/// `highlighted`
 pub fn format_comment_lines(
     theme: &Theme,
-    comment_type: CommentType,
-    content: &str,
+    comment_type: &CommentTypeDef,
+    comment: &Comment,
     line_range: Option<LineRange>,
+    width: usize,
+    highlighted: &[Option<HighlightedLines>],
 ) -> Vec<Line<'static>> {
     let type_style = styles::comment_type_style(theme, comment_type);
     let border_style = styles::comment_border_style(theme, comment_type);
+    let content = comment.content.as_str();
 
-    let line_info = match line_range {
+    let mut line_info = match line_range {
         Some(range) if range.is_single() => format!("L{} ", range.start),
         Some(range) => format!("L{}-L{} ", range.start, range.end),
         None => String::new(),
     };
-    let content_lines: Vec<&str> = content.split('\n').collect();
+    if let Some(initials) = comment.author_initials() {
+        line_info.push_str(&format!("{initials} "));
+    }
 
     let mut result = Vec::new();
 
-    // Top border with type label
+    // Top border with type label, filled to the box width
+    let label = format!("[{}] ", comment_type.label());
+    let fill = (width + 2).saturating_sub(3 + label.width() + line_info.width());
     result.push(Line::from(vec![
         Span::styled("     ╭─ ", border_style),
-        Span::styled(format!("[{}] ", comment_type.as_str()), type_style),
+        Span::styled(label, type_style),
         Span::styled(line_info, styles::dim_style(theme)),
-        Span::styled("─".repeat(30), border_style),
+        Span::styled("─".repeat(fill.max(3)), border_style),
     ]));
 
-    // Content lines
-    for line in &content_lines {
-        result.push(Line::from(vec![
+    // Content lines, rendered from the Markdown subset
+    let layout = comment_text::layout(content, width);
+    for line in &layout.lines {
+        let mut spans = vec![
             Span::styled("     │ ", border_style),
-            Span::raw(line.to_string()),
-        ]));
+            Span::styled(line.prefix.clone(), styles::dim_style(theme)),
+        ];
+        for (kind, text) in &line.spans {
+            match *kind {
+                SpanKind::CodeBlock {
+                    block,
+                    line,
+                    offset,
+                } => {
+                    let line_spans = highlighted
+                        .get(block)
+                        .and_then(Option::as_ref)
+                        .and_then(|lines| lines.get(line))
+                        .and_then(Option::as_ref);
+                    spans.extend(code_block_spans(theme, line_spans, offset, text));
+                }
+                kind => spans.push(Span::styled(text.clone(), comment_text_style(theme, kind))),
+            }
+        }
+        result.push(Line::from(spans));
     }
 
     // Bottom border
     result.push(Line::from(vec![Span::styled(
-        "     ╰".to_string() + &"─".repeat(38),
+        "     ╰".to_string() + &"─".repeat(width + 2),
         border_style,
     )]));
 
     result
 }
 
+fn comment_text_style(theme: &Theme, kind: SpanKind) -> Style {
+    match kind {
+        SpanKind::Text => Style::default(),
+        SpanKind::Emphasis => Style::default().add_modifier(Modifier::ITALIC),
+        SpanKind::Strong => Style::default().add_modifier(Modifier::BOLD),
+        SpanKind::Heading => Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        SpanKind::Code | SpanKind::CodeBlock { .. } => styles::comment_code_style(theme),
+    }
+}
+
+/// Spans for `text`, the part of a code block line starting at byte
+/// `offset`, colored from the line's syntax highlighting when available
+fn code_block_spans(
+    theme: &Theme,
+    highlighted: Option<&Vec<(Style, String)>>,
+    offset: usize,
+    text: &str,
+) -> Vec<Span<'static>> {
+    let base = styles::comment_code_style(theme);
+    let Some(highlighted) = highlighted else {
+        return vec![Span::styled(text.to_string(), base)];
+    };
+
+    let end = offset + text.len();
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (style, part) in highlighted {
+        let part_end = start + part.len();
+        let from = start.max(offset);
+        let to = part_end.min(end);
+        if from < to {
+            let part = part.get(from - start..to - start).unwrap_or_default();
+            spans.push(Span::styled(part.to_string(), base.patch(*style)));
+        }
+        start = part_end;
+    }
+    spans
+}
+
 pub fn render_confirm_dialog(frame: &mut Frame, app: &App, message: &str) {
     let theme = &app.theme;
     let area = centered_rect(50, 20, frame.area());
@@ -238,12 +307,17 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::CommentType;
     use crate::theme::Theme;
 
     fn test_theme() -> Theme {
         Theme::default()
     }
 
+    fn note() -> CommentTypeDef {
+        CommentTypeDef::new("Note", "observations", 2)
+    }
+
     #[test]
     fn should_return_cursor_at_start_for_empty_buffer() {
         // given
@@ -251,7 +325,7 @@ mod tests {
 
         // when
         let (lines, cursor_info) =
-            format_comment_input_lines(&theme, CommentType::Note, "", 0, None, false, false);
+            format_comment_input_lines(&theme, &note(), "", 0, None, false, false);
 
         // then
         assert_eq!(lines.len(), 3); // header + content + footer
@@ -267,15 +341,8 @@ mod tests {
         let cursor_pos = 3; // cursor after "hel"
 
         // when
-        let (_, cursor_info) = format_comment_input_lines(
-            &theme,
-            CommentType::Note,
-            buffer,
-            cursor_pos,
-            None,
-            false,
-            false,
-        );
+        let (_, cursor_info) =
+            format_comment_input_lines(&theme, &note(), buffer, cursor_pos, None, false, false);
 
         // then
         assert_eq!(cursor_info.line_offset, 1); // first content line
@@ -290,15 +357,8 @@ mod tests {
         let cursor_pos = 3; // cursor after first multibyte char (after "안")
 
         // when
-        let (_, cursor_info) = format_comment_input_lines(
-            &theme,
-            CommentType::Note,
-            buffer,
-            cursor_pos,
-            None,
-            false,
-            false,
-        );
+        let (_, cursor_info) =
+            format_comment_input_lines(&theme, &note(), buffer, cursor_pos, None, false, false);
 
         // then
         assert_eq!(cursor_info.line_offset, 1);
@@ -314,15 +374,8 @@ mod tests {
         let cursor_pos = 4; // cursor at end
 
         // when
-        let (_, cursor_info) = format_comment_input_lines(
-            &theme,
-            CommentType::Note,
-            buffer,
-            cursor_pos,
-            None,
-            false,
-            false,
-        );
+        let (_, cursor_info) =
+            format_comment_input_lines(&theme, &note(), buffer, cursor_pos, None, false, false);
 
         // then
         assert_eq!(cursor_info.line_offset, 1);
@@ -337,15 +390,8 @@ mod tests {
         let cursor_pos = 8; // cursor after "li" in "line2"
 
         // when
-        let (lines, cursor_info) = format_comment_input_lines(
-            &theme,
-            CommentType::Note,
-            buffer,
-            cursor_pos,
-            None,
-            false,
-            false,
-        );
+        let (lines, cursor_info) =
+            format_comment_input_lines(&theme, &note(), buffer, cursor_pos, None, false, false);
 
         // then
         assert_eq!(lines.len(), 4); // header + 2 content lines + footer
@@ -361,19 +407,52 @@ mod tests {
         let cursor_pos = 4; // cursor after "a좋" (1 + 3 bytes)
 
         // when
-        let (_, cursor_info) = format_comment_input_lines(
-            &theme,
-            CommentType::Note,
-            buffer,
-            cursor_pos,
-            None,
-            false,
-            false,
-        );
+        let (_, cursor_info) =
+            format_comment_input_lines(&theme, &note(), buffer, cursor_pos, None, false, false);
 
         // then
         assert_eq!(cursor_info.line_offset, 1);
         // "a" = 1 display width, "좋" = 2 display width, total = 3
         assert_eq!(cursor_info.column, 7 + 3);
     }
+
+    #[test]
+    fn should_render_markdown_with_highlighted_code_blocks() {
+        // given
+        let theme = test_theme();
+        let content = "Use `?` **instead**:\n```rust\nlet x = foo()?;\n```";
+
+        let comment = Comment::new(content.to_string(), CommentType::new("note"), None);
+        let mut highlights = comment_text::CodeHighlights::default();
+        highlights.update([&comment], theme.syntax_highlighter());
+
+        // when
+        let lines = format_comment_lines(
+            &theme,
+            &note(),
+            &comment,
+            None,
+            40,
+            highlights.get(&comment),
+        );
+
+        // then
+        assert_eq!(lines.len(), 2 + comment_text::line_count(content, 40));
+        let text: Vec<&str> = lines[1].spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, vec!["     │ ", "", "Use ", "?", " ", "instead", ":"]);
+        assert!(
+            lines[1].spans[5]
+                .style
+                .add_modifier
+                .contains(Modifier::BOLD)
+        );
+        // Highlighting splits the code line into several colored spans
+        let code: String = lines[2].spans[2..]
+            .iter()
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert_eq!(code, "let x = foo()?;");
+        assert!(lines[2].spans.len() > 3);
+        assert_eq!(lines.last().unwrap().spans[0].content.width(), 6 + 42);
+    }
 }
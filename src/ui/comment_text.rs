@@ -0,0 +1,478 @@
+//! Layout of comment bodies: the Markdown subset shown in comment boxes,
+//! wrapped to the box width.
+//!
+//! Every source line stays a line break. Supported are `code` spans,
+//! `*emphasis*`/`_emphasis_`, `**strong**`/`__strong__`, `#` headings,
+//! `-`/`*`/`+`/`1.` list items and fenced code blocks. The layout is pure
+//! text so the diff view can count comment rows without rendering them.
+
+use std::collections::{HashMap, HashSet};
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::model::Comment;
+use crate::syntax::{HighlightedLines, SyntaxHighlighter};
+
+/// Columns of a diff view row taken by a comment box rather than its text:
+/// the cursor indicator, the box's left edge, and the bottom border running
+/// one column past the text on either side
+const COMMENT_BOX_COLUMNS: usize = 9;
+/// Narrowest comment text is wrapped to, however narrow the diff view
+const MIN_TEXT_WIDTH: usize = 20;
+
+/// Display columns available for comment text in a diff view `viewport_width`
+/// columns wide
+pub fn text_width(viewport_width: usize) -> usize {
+    viewport_width
+        .saturating_sub(COMMENT_BOX_COLUMNS)
+        .max(MIN_TEXT_WIDTH)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    Text,
+    Emphasis,
+    Strong,
+    Heading,
+    Code,
+    /// Part of fenced block `block`, line `line`, starting at byte `offset`
+    CodeBlock {
+        block: usize,
+        line: usize,
+        offset: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextLine {
+    /// List marker or its hanging indent
+    pub prefix: String,
+    pub spans: Vec<(SpanKind, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    /// Info string after the opening fence, e.g. `rust`
+    pub language: String,
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommentLayout {
+    pub lines: Vec<TextLine>,
+    pub code_blocks: Vec<CodeBlock>,
+}
+
+pub fn layout(content: &str, width: usize) -> CommentLayout {
+    let width = width.max(1);
+    let mut layout = CommentLayout::default();
+    let mut fence: Option<&str> = None;
+
+    for line in content.split('\n') {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) && trimmed.trim_start_matches(marker).trim().is_empty() {
+                fence = None;
+            } else {
+                push_code_line(&mut layout, line, width);
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some(marker);
+            let language = trimmed[marker.len()..]
+                .split_whitespace()
+                .next()
+                .unwrap_or("");
+            layout.code_blocks.push(CodeBlock {
+                language: language.to_string(),
+                lines: Vec::new(),
+            });
+            continue;
+        }
+
+        let (prefix, text, heading) = split_block_marker(line);
+        let mut spans = parse_inline(text);
+        if heading {
+            for (kind, _) in &mut spans {
+                *kind = SpanKind::Heading;
+            }
+        }
+        layout.lines.extend(wrap_spans(prefix, spans, width));
+    }
+    layout
+}
+
+/// Number of rows `content` takes inside a comment box `width` columns wide
+pub fn line_count(content: &str, width: usize) -> usize {
+    layout(content, width).lines.len()
+}
+
+/// Syntax highlighting of comments' fenced code blocks, by comment id.
+/// Highlighting is slow, so it is redone only when a comment's text changes
+/// rather than every time the comment is drawn.
+#[derive(Default)]
+pub struct CodeHighlights {
+    comments: HashMap<String, (String, Vec<Option<HighlightedLines>>)>,
+}
+
+impl CodeHighlights {
+    /// Highlight the code blocks of new and edited comments and forget
+    /// deleted ones
+    pub fn update<'a>(
+        &mut self,
+        comments: impl IntoIterator<Item = &'a Comment>,
+        highlighter: &SyntaxHighlighter,
+    ) {
+        let mut ids = HashSet::new();
+        for comment in comments {
+            ids.insert(comment.id.as_str());
+            if self
+                .comments
+                .get(&comment.id)
+                .is_some_and(|(content, _)| *content == comment.content)
+            {
+                continue;
+            }
+            // Code blocks are the same at any width
+            let blocks = layout(&comment.content, MIN_TEXT_WIDTH)
+                .code_blocks
+                .iter()
+                .map(|block| highlighter.highlight_code_block(&block.language, &block.lines))
+                .collect();
+            self.comments
+                .insert(comment.id.clone(), (comment.content.clone(), blocks));
+        }
+        if self.comments.len() > ids.len() {
+            self.comments.retain(|id, _| ids.contains(id.as_str()));
+        }
+    }
+
+    /// Highlighted lines of each code block of `comment`, in order; empty
+    /// if the comment has not been highlighted
+    pub fn get(&self, comment: &Comment) -> &[Option<HighlightedLines>] {
+        self.comments
+            .get(&comment.id)
+            .filter(|(content, _)| *content == comment.content)
+            .map_or(&[], |(_, blocks)| blocks)
+    }
+}
+
+/// Split off a list marker or heading: `(prefix, text, is_heading)`
+fn split_block_marker(line: &str) -> (String, &str, bool) {
+    let text = line.trim_start();
+    let indent = " ".repeat(line.len() - text.len());
+
+    if let Some(rest) = text.strip_prefix('#') {
+        let rest = rest.trim_start_matches('#');
+        if let Some(heading) = rest.strip_prefix(' ') {
+            return (indent, heading.trim(), true);
+        }
+    }
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(rest) = text.strip_prefix(bullet) {
+            return (format!("{indent}• "), rest, false);
+        }
+    }
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0
+        && let Some(rest) = text[digits..]
+            .strip_prefix(". ")
+            .or_else(|| text[digits..].strip_prefix(") "))
+    {
+        return (format!("{indent}{}. ", &text[..digits]), rest, false);
+    }
+    (indent, text, false)
+}
+
+fn parse_inline(text: &str) -> Vec<(SpanKind, String)> {
+    let mut spans: Vec<(SpanKind, String)> = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let styled = match c {
+            '`' => delimited(rest, "`").map(|(inner, len)| (SpanKind::Code, inner, len)),
+            '*' | '_' => {
+                // `_` only opens at a word start so snake_case stays plain
+                let word_start = c == '*' || plain.is_empty() || plain.ends_with(' ');
+                let strong = if c == '*' { "**" } else { "__" };
+                let emphasis = if c == '*' { "*" } else { "_" };
+                delimited(rest, strong)
+                    .map(|(inner, len)| (SpanKind::Strong, inner, len))
+                    .or_else(|| {
+                        delimited(rest, emphasis)
+                            .map(|(inner, len)| (SpanKind::Emphasis, inner, len))
+                    })
+                    .filter(|_| word_start)
+            }
+            _ => None,
+        };
+        match styled {
+            Some((kind, inner, len)) => {
+                if !plain.is_empty() {
+                    spans.push((SpanKind::Text, std::mem::take(&mut plain)));
+                }
+                spans.push((kind, inner.to_string()));
+                rest = &rest[len..];
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        spans.push((SpanKind::Text, plain));
+    }
+    spans
+}
+
+/// `text` starting with `delimiter`: the non-empty inner text up to the
+/// closing delimiter and the total byte length including both delimiters
+fn delimited<'a>(text: &'a str, delimiter: &str) -> Option<(&'a str, usize)> {
+    let body = text.strip_prefix(delimiter)?;
+    let end = body.find(delimiter)?;
+    let inner = &body[..end];
+    if inner.is_empty() || inner.starts_with(' ') || inner.ends_with(' ') {
+        return None;
+    }
+    Some((inner, end + 2 * delimiter.len()))
+}
+
+/// Greedy word wrap; words wider than a line are broken between characters
+fn wrap_spans(prefix: String, spans: Vec<(SpanKind, String)>, width: usize) -> Vec<TextLine> {
+    let hanging = " ".repeat(prefix.width());
+    let available = width.saturating_sub(prefix.width()).max(1);
+    let mut lines = vec![TextLine {
+        prefix,
+        spans: Vec::new(),
+    }];
+    let mut used = 0;
+
+    for (kind, text) in spans {
+        for piece in split_words(&text) {
+            let piece_width = piece.width();
+            let is_space = piece.starts_with(' ');
+            if used + piece_width > available && used > 0 {
+                start_line(&mut lines, &hanging);
+                used = 0;
+                if is_space {
+                    continue;
+                }
+            }
+            for chunk in break_to_width(piece, available - used, available) {
+                if used > 0 && used + chunk.width() > available {
+                    start_line(&mut lines, &hanging);
+                    used = 0;
+                }
+                used += chunk.width();
+                push_span(lines.last_mut().expect("at least one line"), kind, chunk);
+            }
+        }
+    }
+    lines
+}
+
+/// Continue on a new line, dropping the space the line was broken at
+fn start_line(lines: &mut Vec<TextLine>, hanging: &str) {
+    if let Some(line) = lines.last_mut()
+        && let Some((_, text)) = line.spans.last_mut()
+    {
+        text.truncate(text.trim_end_matches(' ').len());
+        if text.is_empty() {
+            line.spans.pop();
+        }
+    }
+    lines.push(TextLine {
+        prefix: hanging.to_string(),
+        spans: Vec::new(),
+    });
+}
+
+/// Alternating runs of spaces and non-spaces
+fn split_words(text: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (idx, c) in text.char_indices() {
+        let space = c == ' ';
+        if in_space.is_some_and(|s| s != space) {
+            pieces.push(&text[start..idx]);
+            start = idx;
+        }
+        in_space = Some(space);
+    }
+    if start < text.len() {
+        pieces.push(&text[start..]);
+    }
+    pieces
+}
+
+/// Split `text` so the first chunk fits `first` columns and the rest `width`
+fn break_to_width(text: &str, first: usize, width: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut limit = first.max(1);
+    let mut start = 0;
+    let mut used = 0;
+    for (idx, c) in text.char_indices() {
+        let c_width = c.width().unwrap_or(0);
+        if used + c_width > limit && idx > start {
+            chunks.push(&text[start..idx]);
+            start = idx;
+            used = 0;
+            limit = width;
+        }
+        used += c_width;
+    }
+    chunks.push(&text[start..]);
+    chunks
+}
+
+fn push_span(line: &mut TextLine, kind: SpanKind, text: &str) {
+    match line.spans.last_mut() {
+        Some((last_kind, last_text))
+            if *last_kind == kind && !matches!(kind, SpanKind::CodeBlock { .. }) =>
+        {
+            last_text.push_str(text)
+        }
+        _ => line.spans.push((kind, text.to_string())),
+    }
+}
+
+fn push_code_line(layout: &mut CommentLayout, line: &str, width: usize) {
+    let block_idx = layout.code_blocks.len() - 1;
+    let block = &mut layout.code_blocks[block_idx];
+    let line_idx = block.lines.len();
+    block.lines.push(line.to_string());
+
+    let mut offset = 0;
+    for chunk in break_to_width(line, width, width) {
+        layout.lines.push(TextLine {
+            prefix: String::new(),
+            spans: vec![(
+                SpanKind::CodeBlock {
+                    block: block_idx,
+                    line: line_idx,
+                    offset,
+                },
+                chunk.to_string(),
+            )],
+        });
+        offset += chunk.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::CommentType;
+
+    fn texts(layout: &CommentLayout) -> Vec<String> {
+        layout
+            .lines
+            .iter()
+            .map(|line| {
+                let text: String = line.spans.iter().map(|(_, t)| t.as_str()).collect();
+                format!("{}{}", line.prefix, text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_parse_inline_styles() {
+        // when
+        let spans = parse_inline("use `foo()` **now**, *maybe* not my_var_name");
+
+        // then
+        assert_eq!(
+            spans,
+            vec![
+                (SpanKind::Text, "use ".to_string()),
+                (SpanKind::Code, "foo()".to_string()),
+                (SpanKind::Text, " ".to_string()),
+                (SpanKind::Strong, "now".to_string()),
+                (SpanKind::Text, ", ".to_string()),
+                (SpanKind::Emphasis, "maybe".to_string()),
+                (SpanKind::Text, " not my_var_name".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_wrap_words_with_hanging_list_indent() {
+        // when
+        let layout = layout("- one two three four\n2. five", 12);
+
+        // then
+        assert_eq!(texts(&layout), vec!["• one two", "  three four", "2. five"]);
+    }
+
+    #[test]
+    fn should_break_words_longer_than_the_line() {
+        let layout = layout("abcdefghij", 4);
+        assert_eq!(texts(&layout), vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn should_collect_fenced_code_blocks_without_fences() {
+        // when
+        let layout = layout("Try:\n```rust\nlet x = 1;\n\n```\n# Done", 6);
+
+        // then
+        assert_eq!(
+            layout.code_blocks,
+            vec![CodeBlock {
+                language: "rust".to_string(),
+                lines: vec!["let x = 1;".to_string(), String::new()],
+            }]
+        );
+        assert_eq!(texts(&layout), vec!["Try:", "let x ", "= 1;", "", "Done"]);
+        assert_eq!(
+            layout.lines[2].spans[0].0,
+            SpanKind::CodeBlock {
+                block: 0,
+                line: 0,
+                offset: 6
+            }
+        );
+        assert_eq!(layout.lines[4].spans[0].0, SpanKind::Heading);
+    }
+
+    #[test]
+    fn should_count_one_row_per_short_line() {
+        assert_eq!(line_count("a\nb", 20), 2);
+        assert_eq!(line_count("", 20), 1);
+    }
+
+    #[test]
+    fn should_highlight_comments_again_only_when_edited() {
+        // given
+        let highlighter = SyntaxHighlighter::default();
+        let mut comment = Comment::new(
+            "```rust\nlet x = 1;\n```".to_string(),
+            CommentType::new("note"),
+            None,
+        );
+        let other = Comment::new("No code".to_string(), CommentType::new("note"), None);
+        let mut highlights = CodeHighlights::default();
+        highlights.update([&comment, &other], &highlighter);
+        assert_eq!(highlights.get(&comment).len(), 1);
+
+        // when
+        comment.content = "```rust\nlet x = 1;\n```\n```py\nx = 1\n```".to_string();
+        let stale = highlights.get(&comment).len();
+        highlights.update([&comment], &highlighter);
+
+        // then
+        assert_eq!(stale, 0);
+        assert_eq!(highlights.get(&comment).len(), 2);
+        assert!(!highlights.comments.contains_key(&other.id));
+    }
+
+    #[test]
+    fn should_fit_text_width_to_the_viewport() {
+        assert_eq!(text_width(100), 91);
+        assert_eq!(text_width(10), MIN_TEXT_WIDTH);
+    }
+}
@@ -87,6 +87,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Next/prev search match"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  m/M       ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Next/prev comment (:cnext/:cprev <type> for one type)"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  Enter     ",
@@ -94,6 +101,41 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Expand/collapse hidden context"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  E         ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Expand/collapse all context in file"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  F         ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Cycle full-file view (new/old/off)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  X         ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Expand/collapse generated file"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  za/zo/zc  ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Toggle/open/close fold (hunk or file)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  zM/zR     ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Fold/unfold all files"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  Tab       ",
@@ -122,6 +164,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Toggle file list visibility"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  ;c        ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Toggle comments panel (Enter jump, i edit, dd delete)"),
+        ]),
         Line::from(""),
         Line::from(Span::styled(
             "Commit Selector (multi-commit reviews)",
@@ -231,6 +280,20 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Delete comment at cursor"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  P         ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Turn diagnostic at cursor into a comment"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  u/Ctrl-r  ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Undo/redo comment and reviewed changes"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  y         ",
@@ -283,7 +346,14 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
                 "  Tab       ",
                 Style::default().add_modifier(Modifier::BOLD),
             ),
-            Span::raw("Toggle type: Note/Suggestion/Issue/Praise"),
+            Span::raw("Cycle comment type"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  Ctrl-T    ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Insert a snippet"),
         ]),
         Line::from(vec![
             Span::styled(
@@ -347,6 +417,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Save review session"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :w!       ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Save even if another tuicr has the session open"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :e        ",
@@ -361,6 +438,27 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Copy review to clipboard"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :export <fmt> [file]",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Export as markdown, sarif, github, gitlab or patch"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :annotate ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Insert comments into the files as REVIEW code comments"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :unannotate",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Remove REVIEW code comments from the files"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :set wrap ",
@@ -375,6 +473,45 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Toggle line wrap in diff view"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set blame",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Show blame gutter (noblame hides, blame! toggles)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set context=N",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Context lines (also whitespace=, renames, ignoreblanklines)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set foldreviewed",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Fold files once reviewed (nofoldreviewed, foldreviewed!)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :filter <mode>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Show all, unreviewed, commented, A/M/D/R, path <text> or author <name>"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :sort <mode>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Order files by path, churn or status"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :set flat", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" Flat file list instead of a tree (noflat, flat!)"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :diff     ",
@@ -382,6 +519,20 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Toggle unified/side-by-side diff view"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :stats    ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Show review statistics"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :comments ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Toggle comments panel"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :commits  ",
@@ -389,6 +540,34 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Select commits or uncommitted changes"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :sessions ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Browse saved sessions (Enter open, r rename, p pin, dd delete)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :name [text]",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Name the current session"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :merge [either|both] <file>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Add the comments of another session file"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :diagnostics [file]",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Show lint diagnostics from file (none: clear)"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :set commits",
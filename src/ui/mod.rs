@@ -1,8 +1,13 @@
 pub mod app_layout;
+pub mod blame_gutter;
 pub mod comment_panel;
+pub mod comment_text;
 pub mod diff_view;
 pub mod file_list;
 pub mod help_popup;
+pub mod sessions_popup;
+pub mod snippet_popup;
+pub mod stats_popup;
 pub mod status_bar;
 pub mod styles;
 
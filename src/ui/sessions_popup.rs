@@ -0,0 +1,98 @@
+// Stored review session browser popup
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+};
+
+use crate::app::{App, InputMode};
+use crate::ui::styles;
+
+const MAX_VISIBLE_SESSIONS: u16 = 15;
+
+pub fn render_sessions(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let renaming = app.input_mode == InputMode::SessionRename;
+    let list_height = (app.session_list.len() as u16).clamp(1, MAX_VISIBLE_SESSIONS);
+    // Header row, list, rename line and borders
+    let area = centered_rect(80, list_height + 4, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Sessions ({}) ", app.session_list.len()))
+        .borders(Borders::ALL)
+        .style(styles::popup_style(theme))
+        .border_style(styles::border_style(theme, true));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [table_area, input_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+
+    if app.session_list.is_empty() {
+        let empty = Paragraph::new(Span::styled(" No saved sessions", styles::dim_style(theme)));
+        frame.render_widget(empty, table_area);
+        return;
+    }
+
+    let header = Row::new(["#", "Name", "Repo", "Branch", "Source", "Age", "Comments"])
+        .style(styles::header_style(theme));
+    let rows: Vec<Row> = app
+        .session_list
+        .iter()
+        .enumerate()
+        .map(|(i, stored)| {
+            let session = &stored.session;
+            let mut name = stored.label().to_string();
+            if session.pinned {
+                name.push_str(" (pinned)");
+            }
+            if session.id == app.session.id {
+                name.push_str(" (open)");
+            }
+            Row::new([
+                Cell::from((i + 1).to_string()),
+                Cell::from(name),
+                Cell::from(stored.repo_name()),
+                Cell::from(session.branch_name.clone().unwrap_or_default()),
+                Cell::from(stored.source_label()),
+                Cell::from(stored.age_label()),
+                Cell::from(session.comment_count().to_string()),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Length(22),
+            Constraint::Length(4),
+            Constraint::Length(8),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(styles::selected_style(theme));
+    let mut state = TableState::default().with_selected(Some(app.sessions_cursor));
+    frame.render_stateful_widget(table, table_area, &mut state);
+
+    if renaming {
+        let input = Paragraph::new(Line::from(vec![
+            Span::styled(" Name: ", styles::dim_style(theme)),
+            Span::raw(app.session_rename_buffer.as_str()),
+        ]));
+        frame.render_widget(input, input_area);
+    }
+}
+
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
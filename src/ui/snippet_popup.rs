@@ -0,0 +1,74 @@
+// Comment snippet picker popup
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::app::App;
+use crate::ui::styles;
+
+const MAX_VISIBLE_SNIPPETS: u16 = 10;
+
+pub fn render_snippet_picker(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let snippets = app.filtered_snippets();
+    let list_height = (snippets.len() as u16).clamp(1, MAX_VISIBLE_SNIPPETS);
+    // Filter line, list and borders
+    let area = centered_rect(60, list_height + 3, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Insert snippet ")
+        .borders(Borders::ALL)
+        .style(styles::popup_style(theme))
+        .border_style(styles::border_style(theme, true));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [filter_area, list_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(inner);
+    let filter = Paragraph::new(Line::from(vec![
+        Span::styled(" > ", styles::dim_style(theme)),
+        Span::raw(app.snippet_filter.as_str()),
+    ]));
+    frame.render_widget(filter, filter_area);
+
+    if snippets.is_empty() {
+        let empty = Paragraph::new(Span::styled(
+            " No matching snippets",
+            styles::dim_style(theme),
+        ));
+        frame.render_widget(empty, list_area);
+        return;
+    }
+
+    let items: Vec<ListItem> = snippets
+        .iter()
+        .enumerate()
+        .map(|(i, snippet)| {
+            let preview = snippet.text.lines().next().unwrap_or("");
+            let name_style = if i == app.snippet_cursor {
+                styles::selected_style(theme)
+            } else {
+                styles::header_style(theme)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {}  ", snippet.name), name_style),
+                Span::styled(preview.to_string(), styles::dim_style(theme)),
+            ]))
+        })
+        .collect();
+
+    let mut state = ListState::default().with_selected(Some(app.snippet_cursor));
+    frame.render_stateful_widget(List::new(items), list_area, &mut state);
+}
+
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
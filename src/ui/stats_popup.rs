@@ -0,0 +1,106 @@
+// Review statistics popup
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::app::App;
+use crate::model::stats::ReviewStats;
+use crate::ui::styles;
+
+const LABEL_WIDTH: usize = 12;
+const PROGRESS_WIDTH: usize = 20;
+
+pub fn render_stats(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let stats = ReviewStats::compute(&app.diff_files, &app.session, &app.comment_types);
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+
+    let heading = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("  {label:<LABEL_WIDTH$}"), bold),
+            Span::raw(value),
+        ])
+    };
+    let entry = |label: &str, count: usize, style: Style| {
+        Line::from(vec![
+            Span::styled(format!("    {label:<LABEL_WIDTH$}"), style),
+            Span::raw(count.to_string()),
+        ])
+    };
+
+    let mut lines = vec![Line::from(""), heading("Files", stats.files.to_string())];
+    for (status, count) in &stats.by_status {
+        let name = format!("{status:?}");
+        lines.push(entry(
+            &name,
+            *count,
+            styles::file_status_style(theme, status.as_char()),
+        ));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(format!("  {:<LABEL_WIDTH$}", "Lines"), bold),
+        Span::styled(
+            format!("+{}", stats.additions),
+            styles::stat_add_style(theme),
+        ),
+        Span::raw(" "),
+        Span::styled(
+            format!("-{}", stats.deletions),
+            styles::stat_del_style(theme),
+        ),
+        Span::raw(format!(" in {} hunks", stats.hunks)),
+    ]));
+
+    lines.push(Line::from(""));
+    lines.push(heading("Comments", stats.comment_count().to_string()));
+    for (comment_type, count) in &stats.comments {
+        let comment_type = app.comment_types.resolve(comment_type);
+        lines.push(entry(
+            &comment_type.label(),
+            *count,
+            styles::comment_type_style(theme, &comment_type),
+        ));
+    }
+
+    let percent = stats.reviewed_percent();
+    let filled = percent * PROGRESS_WIDTH / 100;
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(format!("  {:<LABEL_WIDTH$}", "Reviewed"), bold),
+        Span::styled("█".repeat(filled), styles::reviewed_style(theme)),
+        Span::styled(
+            "░".repeat(PROGRESS_WIDTH - filled),
+            styles::dim_style(theme),
+        ),
+        Span::raw(format!(" {percent}% ({}/{})", stats.reviewed, stats.files)),
+    ]));
+
+    let height = lines.len() as u16 + 3;
+    let area = centered_rect(50, height, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Review statistics - Press q or Esc to close ")
+        .borders(Borders::ALL)
+        .style(styles::popup_style(theme))
+        .border_style(styles::border_style(theme, true));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(styles::popup_style(theme));
+    frame.render_widget(paragraph, area);
+}
+
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
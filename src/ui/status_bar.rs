@@ -175,6 +175,10 @@ pub fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             InputMode::Search => " SEARCH ".to_string(),
             InputMode::Comment => " COMMENT ".to_string(),
             InputMode::Help => " HELP ".to_string(),
+            InputMode::Stats => " STATS ".to_string(),
+            InputMode::SnippetPicker => " SNIPPET ".to_string(),
+            InputMode::Sessions => " SESSIONS ".to_string(),
+            InputMode::SessionRename => " RENAME ".to_string(),
             InputMode::Confirm => " CONFIRM ".to_string(),
             InputMode::CommitSelect => " SELECT ".to_string(),
             InputMode::VisualSelect => {
@@ -198,8 +202,14 @@ pub fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             }
             InputMode::Command => " Enter:execute  Esc:cancel ",
             InputMode::Search => " Enter:search  Esc:cancel ",
-            InputMode::Comment => " Ctrl-S:save  Esc:cancel ",
+            InputMode::Comment => " Ctrl-S:save  Ctrl-T:snippet  Esc:cancel ",
             InputMode::Help => " q/?/Esc:close ",
+            InputMode::Stats => " q/Esc:close ",
+            InputMode::SnippetPicker => " type:filter  ↑/↓:select  Enter:insert  Esc:back ",
+            InputMode::Sessions => {
+                " j/k:navigate  Enter:open  r:rename  p:pin  dd:delete  q/Esc:close "
+            }
+            InputMode::SessionRename => " Enter:save  Esc:cancel ",
             InputMode::Confirm => " y:yes  n:no ",
             InputMode::CommitSelect => {
                 " j/k:navigate  Space:select  Enter:confirm  Esc:back  q:quit "
@@ -1,4 +1,4 @@
-use ratatui::style::{Modifier, Style};
+use ratatui::style::{Color, Modifier, Style};
 
 use crate::theme::Theme;
 
@@ -25,6 +25,15 @@ pub fn diff_del_style(theme: &Theme) -> Style {
     Style::default().fg(theme.diff_del).bg(theme.diff_del_bg)
 }
 
+/// Added line count in file statistics, without the diff background
+pub fn stat_add_style(theme: &Theme) -> Style {
+    Style::default().fg(theme.diff_add)
+}
+
+pub fn stat_del_style(theme: &Theme) -> Style {
+    Style::default().fg(theme.diff_del)
+}
+
 pub fn diff_context_style(theme: &Theme) -> Style {
     Style::default().fg(theme.diff_context)
 }
@@ -101,28 +110,50 @@ pub fn hash_style(theme: &Theme) -> Style {
     Style::default().fg(theme.cursor_color)
 }
 
+pub fn blame_style(theme: &Theme) -> Style {
+    Style::default().fg(theme.fg_secondary)
+}
+
 pub fn dir_icon_style(theme: &Theme) -> Style {
     Style::default().fg(theme.diff_hunk_header)
 }
 
-pub fn comment_type_style(theme: &Theme, comment_type: crate::model::CommentType) -> Style {
-    use crate::model::CommentType;
-    let color = match comment_type {
-        CommentType::Note => theme.comment_note,
-        CommentType::Suggestion => theme.comment_suggestion,
-        CommentType::Issue => theme.comment_issue,
-        CommentType::Praise => theme.comment_praise,
-    };
-    Style::default().fg(color).add_modifier(Modifier::BOLD)
+/// Configured color of a comment type, falling back to the theme's colors
+/// for the built-in types
+fn comment_type_color(theme: &Theme, comment_type: &crate::model::CommentTypeDef) -> Color {
+    if let Some(color) = comment_type.color {
+        return color;
+    }
+    match comment_type.id.id() {
+        "note" => theme.comment_note,
+        "suggestion" => theme.comment_suggestion,
+        "issue" => theme.comment_issue,
+        "praise" => theme.comment_praise,
+        _ => theme.fg_primary,
+    }
+}
+
+pub fn comment_type_style(theme: &Theme, comment_type: &crate::model::CommentTypeDef) -> Style {
+    Style::default()
+        .fg(comment_type_color(theme, comment_type))
+        .add_modifier(Modifier::BOLD)
+}
+
+pub fn comment_border_style(theme: &Theme, comment_type: &crate::model::CommentTypeDef) -> Style {
+    Style::default().fg(comment_type_color(theme, comment_type))
+}
+
+/// Inline code and code blocks in comment bodies
+pub fn comment_code_style(theme: &Theme) -> Style {
+    Style::default().fg(theme.fg_primary).bg(theme.bg_highlight)
 }
 
-pub fn comment_border_style(theme: &Theme, comment_type: crate::model::CommentType) -> Style {
-    use crate::model::CommentType;
-    let color = match comment_type {
-        CommentType::Note => theme.comment_note,
-        CommentType::Suggestion => theme.comment_suggestion,
-        CommentType::Issue => theme.comment_issue,
-        CommentType::Praise => theme.comment_praise,
+pub fn diagnostic_style(theme: &Theme, severity: crate::diagnostics::Severity) -> Style {
+    use crate::diagnostics::Severity;
+    let color = match severity {
+        Severity::Error => theme.comment_issue,
+        Severity::Warning => theme.pending,
+        Severity::Note => theme.fg_secondary,
     };
     Style::default().fg(color)
 }
@@ -0,0 +1,194 @@
+//! User-configurable options that control how diffs are generated.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+/// Default number of unchanged lines shown around each change
+pub const DEFAULT_CONTEXT_LINES: u32 = 3;
+/// Default similarity (percent) for a delete/add pair to count as a rename or copy
+pub const DEFAULT_RENAME_SIMILARITY: u16 = 50;
+
+/// How whitespace differences are treated when diffing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WhitespaceMode {
+    /// Whitespace changes are shown like any other change
+    #[default]
+    None,
+    /// Ignore all whitespace
+    All,
+    /// Ignore whitespace changes at the end of lines
+    AtEol,
+    /// Ignore changes in the amount of whitespace
+    Change,
+}
+
+impl FromStr for WhitespaceMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "all" => Ok(Self::All),
+            "at-eol" => Ok(Self::AtEol),
+            "change" => Ok(Self::Change),
+            _ => Err(format!(
+                "Invalid whitespace mode: {s} (expected none, all, at-eol or change)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for WhitespaceMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::None => "none",
+            Self::All => "all",
+            Self::AtEol => "at-eol",
+            Self::Change => "change",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Options applied by every VCS backend when generating diffs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffOptions {
+    pub whitespace: WhitespaceMode,
+    pub context_lines: u32,
+    /// Detect renamed and copied files
+    pub detect_renames: bool,
+    /// Minimum similarity (0-100) for rename/copy detection
+    pub rename_similarity: u16,
+    pub ignore_blank_lines: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            whitespace: WhitespaceMode::None,
+            context_lines: DEFAULT_CONTEXT_LINES,
+            detect_renames: false,
+            rename_similarity: DEFAULT_RENAME_SIMILARITY,
+            ignore_blank_lines: false,
+        }
+    }
+}
+
+/// Option names accepted by `:set`, with or without a `no` prefix or `=value`
+const SETTING_NAMES: &[&str] = &[
+    "whitespace",
+    "context",
+    "renames",
+    "norenames",
+    "ignoreblanklines",
+    "noignoreblanklines",
+];
+
+impl DiffOptions {
+    /// Whether a `:set` argument names a diff option.
+    pub fn is_setting(setting: &str) -> bool {
+        let name = setting.split_once('=').map_or(setting, |(name, _)| name);
+        SETTING_NAMES.contains(&name)
+    }
+
+    /// Apply a `:set` argument such as `context=5`, `whitespace=at-eol`,
+    /// `renames=60` or `noignoreblanklines`.
+    pub fn apply_setting(&mut self, setting: &str) -> Result<(), String> {
+        let (name, value) = match setting.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (setting, None),
+        };
+
+        match (name, value) {
+            ("whitespace", Some(value)) => self.whitespace = value.parse()?,
+            ("context", Some(value)) => {
+                self.context_lines = value
+                    .parse()
+                    .map_err(|_| format!("Invalid context line count: {value}"))?;
+            }
+            ("renames", None) => self.detect_renames = true,
+            ("renames", Some(value)) => {
+                self.rename_similarity = value
+                    .parse()
+                    .ok()
+                    .filter(|similarity| *similarity <= 100)
+                    .ok_or_else(|| {
+                        format!("Invalid rename similarity: {value} (expected 0-100)")
+                    })?;
+                self.detect_renames = true;
+            }
+            ("norenames", None) => self.detect_renames = false,
+            ("ignoreblanklines", None) => self.ignore_blank_lines = true,
+            ("noignoreblanklines", None) => self.ignore_blank_lines = false,
+            _ => return Err(format!("Invalid diff option: {setting}")),
+        }
+        Ok(())
+    }
+
+    /// Short description of the active options for status messages
+    pub fn summary(&self) -> String {
+        let renames = if self.detect_renames {
+            format!("{}%", self.rename_similarity)
+        } else {
+            "off".to_string()
+        };
+        let blank_lines = if self.ignore_blank_lines {
+            "ignored"
+        } else {
+            "shown"
+        };
+        format!(
+            "whitespace={} context={} renames={renames} blank lines {blank_lines}",
+            self.whitespace, self.context_lines
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_recognize_diff_settings() {
+        assert!(DiffOptions::is_setting("context=5"));
+        assert!(DiffOptions::is_setting("norenames"));
+        assert!(DiffOptions::is_setting("whitespace=all"));
+        assert!(!DiffOptions::is_setting("wrap"));
+        assert!(!DiffOptions::is_setting("blame"));
+    }
+
+    #[test]
+    fn should_apply_settings() {
+        // given
+        let mut options = DiffOptions::default();
+
+        // when
+        options.apply_setting("whitespace=at-eol").unwrap();
+        options.apply_setting("context=7").unwrap();
+        options.apply_setting("renames=60").unwrap();
+        options.apply_setting("ignoreblanklines").unwrap();
+
+        // then
+        assert_eq!(options.whitespace, WhitespaceMode::AtEol);
+        assert_eq!(options.context_lines, 7);
+        assert!(options.detect_renames);
+        assert_eq!(options.rename_similarity, 60);
+        assert!(options.ignore_blank_lines);
+    }
+
+    #[test]
+    fn should_reject_invalid_setting_values() {
+        // given
+        let mut options = DiffOptions::default();
+
+        // when / then
+        assert!(options.apply_setting("whitespace=some").is_err());
+        assert!(options.apply_setting("context=-1").is_err());
+        assert!(options.apply_setting("renames=150").is_err());
+        assert!(options.apply_setting("norenames=1").is_err());
+        assert_eq!(options, DiffOptions::default());
+    }
+}
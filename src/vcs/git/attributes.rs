@@ -0,0 +1,44 @@
+use git2::{AttrCheckFlags, AttrValue, Repository};
+use std::path::Path;
+
+use crate::error::Result;
+
+/// Whether `.gitattributes` marks a file as generated or vendored.
+///
+/// Matches `linguist-generated`, `linguist-vendored`, and `-diff` (which
+/// `binary` also implies), the attributes GitHub uses to hide files in diffs.
+pub fn is_generated(repo: &Repository, path: &Path) -> Result<bool> {
+    let flags = AttrCheckFlags::FILE_THEN_INDEX;
+    for name in ["linguist-generated", "linguist-vendored"] {
+        let value = repo.get_attr(path, name, flags)?;
+        if matches!(AttrValue::from_string(value), AttrValue::True) || value == Some("true") {
+            return Ok(true);
+        }
+    }
+    let diff = repo.get_attr(path, "diff", flags)?;
+    Ok(matches!(AttrValue::from_string(diff), AttrValue::False))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn should_detect_generated_attributes() {
+        // given
+        let temp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(temp.path()).unwrap();
+        fs::write(
+            temp.path().join(".gitattributes"),
+            "*.pb.go linguist-generated\nthird_party/** linguist-vendored=true\n*.snap -diff\n",
+        )
+        .unwrap();
+
+        // when / then
+        assert!(is_generated(&repo, Path::new("api/service.pb.go")).unwrap());
+        assert!(is_generated(&repo, Path::new("third_party/lib/a.c")).unwrap());
+        assert!(is_generated(&repo, Path::new("tests/ui.snap")).unwrap());
+        assert!(!is_generated(&repo, Path::new("src/main.rs")).unwrap());
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::{TimeZone, Utc};
+use git2::{BlameOptions, Oid, Repository};
+use std::path::Path;
+
+use crate::error::{Result, TuicrError};
+use crate::vcs::traits::BlameLine;
+
+/// Blame the old side of a file.
+///
+/// With `base_commit`, blames the file as of that commit's parent;
+/// otherwise as of HEAD (the old side of a working tree diff).
+pub fn blame_file(
+    repo: &Repository,
+    file_path: &Path,
+    base_commit: Option<&str>,
+) -> Result<Vec<BlameLine>> {
+    let newest = match base_commit {
+        Some(id) => {
+            let commit = repo.find_commit(Oid::from_str(id)?)?;
+            if commit.parent_count() == 0 {
+                // Root commit: the old side is empty
+                return Ok(Vec::new());
+            }
+            commit.parent_id(0)?
+        }
+        None => repo.head()?.peel_to_commit()?.id(),
+    };
+
+    let mut opts = BlameOptions::new();
+    opts.newest_commit(newest);
+    let blame = repo
+        .blame_file(file_path, Some(&mut opts))
+        .map_err(|e| TuicrError::VcsCommand(format!("Blame failed: {e}")))?;
+
+    let mut lines = Vec::new();
+    for hunk in blame.iter() {
+        let commit_id = hunk.final_commit_id().to_string();
+        let short_id = commit_id[..7.min(commit_id.len())].to_string();
+        let signature = hunk.final_signature();
+        let author = signature.name().unwrap_or("Unknown").to_string();
+        let time = Utc
+            .timestamp_opt(signature.when().seconds(), 0)
+            .single()
+            .unwrap_or_else(Utc::now);
+
+        let entry = BlameLine {
+            commit_id,
+            short_id,
+            author,
+            time,
+        };
+        lines.extend(std::iter::repeat_n(entry, hunk.lines_in_hunk()));
+    }
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::fs;
+
+    fn commit_file(repo: &Repository, name: &str, content: &str, message: &str) -> Oid {
+        let root = repo.workdir().unwrap();
+        fs::write(root.join(name), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let parents: Vec<git2::Commit> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parent_refs)
+            .unwrap()
+    }
+
+    #[test]
+    fn should_blame_each_line_of_head_version() {
+        // given
+        let temp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(temp.path()).unwrap();
+        let first = commit_file(&repo, "a.txt", "one\ntwo\n", "First");
+        let second = commit_file(&repo, "a.txt", "one\ntwo\nthree\n", "Second");
+
+        // when
+        let lines = blame_file(&repo, Path::new("a.txt"), None).unwrap();
+
+        // then
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].commit_id, first.to_string());
+        assert_eq!(lines[1].commit_id, first.to_string());
+        assert_eq!(lines[2].commit_id, second.to_string());
+        assert_eq!(lines[2].short_id.len(), 7);
+        assert_eq!(lines[2].author, "Test User");
+    }
+
+    #[test]
+    fn should_blame_parent_of_base_commit() {
+        // given
+        let temp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(temp.path()).unwrap();
+        let first = commit_file(&repo, "a.txt", "one\n", "First");
+        let second = commit_file(&repo, "a.txt", "one\ntwo\n", "Second");
+
+        // when
+        let lines = blame_file(&repo, Path::new("a.txt"), Some(&second.to_string())).unwrap();
+
+        // then
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].commit_id, first.to_string());
+    }
+
+    #[test]
+    fn should_return_empty_blame_for_root_commit() {
+        // given
+        let temp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(temp.path()).unwrap();
+        let first = commit_file(&repo, "a.txt", "one\n", "First");
+
+        // when
+        let lines = blame_file(&repo, Path::new("a.txt"), Some(&first.to_string())).unwrap();
+
+        // then
+        assert!(lines.is_empty());
+    }
+}
@@ -3,6 +3,7 @@ use std::path::Path;
 
 use crate::error::{Result, TuicrError};
 use crate::model::{DiffLine, FileStatus, LineOrigin};
+use crate::vcs::traits::FileVersion;
 
 /// Fetch context lines from a file for gap expansion.
 ///
@@ -37,15 +38,17 @@ pub fn fetch_context_lines(
 
     for line_num in start_line..=end_line {
         let idx = (line_num - 1) as usize;
-        if idx < lines.len() {
-            result.push(DiffLine {
-                origin: LineOrigin::Context,
-                content: lines[idx].to_string(),
-                old_lineno: Some(line_num),
-                new_lineno: Some(line_num),
-                highlighted_spans: None,
-            });
-        }
+        let Some(line) = lines.get(idx) else {
+            // Past the end of the file (e.g. an open-ended trailing gap)
+            break;
+        };
+        result.push(DiffLine {
+            origin: LineOrigin::Context,
+            content: line.to_string(),
+            old_lineno: Some(line_num),
+            new_lineno: Some(line_num),
+            highlighted_spans: None,
+        });
     }
 
     Ok(result)
@@ -61,6 +64,29 @@ fn fetch_blob_content(repo: &Repository, file_path: &Path) -> Result<String> {
     Ok(content.to_string())
 }
 
+/// Read the full content of a file at the given version.
+pub fn read_file_version(
+    repo: &Repository,
+    file_path: &Path,
+    version: FileVersion<'_>,
+) -> Result<String> {
+    let commit = match version {
+        FileVersion::WorkingTree => {
+            let workdir = repo.workdir().ok_or(TuicrError::NotARepository)?;
+            return Ok(std::fs::read_to_string(workdir.join(file_path))?);
+        }
+        FileVersion::Commit(id) => repo.find_commit(git2::Oid::from_str(id)?)?,
+        FileVersion::ParentOf(Some(id)) => repo.find_commit(git2::Oid::from_str(id)?)?.parent(0)?,
+        FileVersion::ParentOf(None) => repo.head()?.peel_to_commit()?,
+    };
+
+    let entry = commit.tree()?.get_path(file_path)?;
+    let blob = repo.find_blob(entry.id())?;
+    let content = std::str::from_utf8(blob.content())
+        .map_err(|e| TuicrError::CorruptedSession(format!("Invalid UTF-8 in file: {e}")))?;
+    Ok(content.to_string())
+}
+
 /// Calculate the number of hidden lines (gap) before a hunk.
 ///
 /// Returns the count of lines between the end of the previous hunk
@@ -85,6 +111,69 @@ pub fn calculate_gap(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use git2::Signature;
+
+    fn commit_file(repo: &Repository, name: &str, content: &str) -> git2::Oid {
+        let root = repo.workdir().unwrap();
+        std::fs::write(root.join(name), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let parents: Vec<git2::Commit> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, "Commit", &tree, &parent_refs)
+            .unwrap()
+    }
+
+    #[test]
+    fn should_read_each_file_version() {
+        // given
+        let temp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(temp.path()).unwrap();
+        commit_file(&repo, "a.txt", "one\n");
+        let second = commit_file(&repo, "a.txt", "two\n");
+        std::fs::write(temp.path().join("a.txt"), "three\n").unwrap();
+        let path = Path::new("a.txt");
+        let second_id = second.to_string();
+
+        // when
+        let working = read_file_version(&repo, path, FileVersion::WorkingTree).unwrap();
+        let commit = read_file_version(&repo, path, FileVersion::Commit(&second_id)).unwrap();
+        let parent =
+            read_file_version(&repo, path, FileVersion::ParentOf(Some(&second_id))).unwrap();
+        let head = read_file_version(&repo, path, FileVersion::ParentOf(None)).unwrap();
+
+        // then
+        assert_eq!(working, "three\n");
+        assert_eq!(commit, "two\n");
+        assert_eq!(parent, "one\n");
+        assert_eq!(head, "two\n");
+    }
+
+    #[test]
+    fn should_fetch_trailing_context_up_to_end_of_file() {
+        // given
+        let temp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(temp.path()).unwrap();
+        commit_file(&repo, "a.txt", "one\ntwo\nthree\n");
+
+        // when
+        let lines =
+            fetch_context_lines(&repo, Path::new("a.txt"), FileStatus::Modified, 2, u32::MAX)
+                .unwrap();
+
+        // then
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].content, "three");
+        assert_eq!(lines[1].new_lineno, Some(3));
+    }
 
     #[test]
     fn should_calculate_gap_before_first_hunk() {
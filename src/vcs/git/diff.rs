@@ -1,22 +1,61 @@
-use git2::{Delta, Diff, DiffOptions, Repository};
+use git2::{Delta, Diff, DiffFindOptions, Repository};
 use std::path::PathBuf;
 
 use crate::error::{Result, TuicrError};
 use crate::model::{DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin};
 use crate::syntax::SyntaxHighlighter;
+use crate::vcs::diff_options::{DiffOptions, WhitespaceMode};
+
+/// Build git2 diff options from the user's diff options.
+fn git_diff_options(options: &DiffOptions) -> git2::DiffOptions {
+    let mut opts = git2::DiffOptions::new();
+    opts.context_lines(options.context_lines);
+    opts.ignore_blank_lines(options.ignore_blank_lines);
+    match options.whitespace {
+        WhitespaceMode::None => {}
+        WhitespaceMode::All => {
+            opts.ignore_whitespace(true);
+        }
+        WhitespaceMode::AtEol => {
+            opts.ignore_whitespace_eol(true);
+        }
+        WhitespaceMode::Change => {
+            opts.ignore_whitespace_change(true);
+        }
+    }
+    opts
+}
+
+/// Pair up deleted/added files as renames and copies when enabled.
+fn find_similar(diff: &mut Diff, options: &DiffOptions) -> Result<()> {
+    if !options.detect_renames {
+        return Ok(());
+    }
+    let mut find_opts = DiffFindOptions::new();
+    find_opts
+        .renames(true)
+        .copies(true)
+        .for_untracked(true)
+        .rename_threshold(options.rename_similarity)
+        .copy_threshold(options.rename_similarity);
+    diff.find_similar(Some(&mut find_opts))?;
+    Ok(())
+}
 
 pub fn get_working_tree_diff(
     repo: &Repository,
+    options: &DiffOptions,
     highlighter: &SyntaxHighlighter,
 ) -> Result<Vec<DiffFile>> {
     let head = repo.head()?.peel_to_tree()?;
 
-    let mut opts = DiffOptions::new();
+    let mut opts = git_diff_options(options);
     opts.include_untracked(true);
     opts.show_untracked_content(true);
     opts.recurse_untracked_dirs(true);
 
-    let diff = repo.diff_tree_to_workdir_with_index(Some(&head), Some(&mut opts))?;
+    let mut diff = repo.diff_tree_to_workdir_with_index(Some(&head), Some(&mut opts))?;
+    find_similar(&mut diff, options)?;
 
     parse_diff(&diff, highlighter)
 }
@@ -27,6 +66,7 @@ pub fn get_working_tree_diff(
 pub fn get_commit_range_diff(
     repo: &Repository,
     commit_ids: &[String],
+    options: &DiffOptions,
     highlighter: &SyntaxHighlighter,
 ) -> Result<Vec<DiffFile>> {
     if commit_ids.is_empty() {
@@ -50,7 +90,9 @@ pub fn get_commit_range_diff(
 
     let new_tree = newest_commit.tree()?;
 
-    let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)?;
+    let mut opts = git_diff_options(options);
+    let mut diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), Some(&mut opts))?;
+    find_similar(&mut diff, options)?;
 
     parse_diff(&diff, highlighter)
 }
@@ -60,6 +102,7 @@ pub fn get_commit_range_diff(
 pub fn get_working_tree_with_commits_diff(
     repo: &Repository,
     commit_ids: &[String],
+    options: &DiffOptions,
     highlighter: &SyntaxHighlighter,
 ) -> Result<Vec<DiffFile>> {
     if commit_ids.is_empty() {
@@ -77,12 +120,13 @@ pub fn get_working_tree_with_commits_diff(
         None
     };
 
-    let mut opts = DiffOptions::new();
+    let mut opts = git_diff_options(options);
     opts.include_untracked(true);
     opts.show_untracked_content(true);
     opts.recurse_untracked_dirs(true);
 
-    let diff = repo.diff_tree_to_workdir_with_index(old_tree.as_ref(), Some(&mut opts))?;
+    let mut diff = repo.diff_tree_to_workdir_with_index(old_tree.as_ref(), Some(&mut opts))?;
+    find_similar(&mut diff, options)?;
 
     parse_diff(&diff, highlighter)
 }
@@ -230,6 +274,23 @@ fn parse_hunks(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use git2::Signature;
+    use std::fs;
+    use std::path::Path;
+
+    fn commit_all(repo: &Repository, files: &[(&str, &str)]) -> git2::Oid {
+        let root = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for (name, content) in files {
+            fs::write(root.join(name), content).unwrap();
+            index.add_path(Path::new(name)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "Initial", &tree, &[])
+            .unwrap()
+    }
 
     #[test]
     fn should_return_no_changes_for_clean_repo() {
@@ -247,4 +308,53 @@ mod tests {
         // then
         assert!(matches!(result, Err(TuicrError::NoChanges)));
     }
+
+    #[test]
+    fn should_ignore_whitespace_changes_when_configured() {
+        // given
+        let temp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(temp.path()).unwrap();
+        commit_all(&repo, &[("a.txt", "one\ntwo\n")]);
+        fs::write(temp.path().join("a.txt"), "one  \ntwo\n").unwrap();
+        let highlighter = SyntaxHighlighter::default();
+        let options = DiffOptions {
+            whitespace: WhitespaceMode::AtEol,
+            ..DiffOptions::default()
+        };
+
+        // when
+        let default_diff = get_working_tree_diff(&repo, &DiffOptions::default(), &highlighter);
+        let ignoring_diff = get_working_tree_diff(&repo, &options, &highlighter);
+
+        // then
+        assert_eq!(default_diff.unwrap()[0].hunks.len(), 1);
+        // git still lists the file, with its whitespace-only hunk dropped
+        let ignoring_diff = ignoring_diff.unwrap();
+        assert_eq!(ignoring_diff.len(), 1);
+        assert!(ignoring_diff[0].hunks.is_empty());
+    }
+
+    #[test]
+    fn should_detect_renames_when_configured() {
+        // given
+        let temp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(temp.path()).unwrap();
+        let content = "line 1\nline 2\nline 3\nline 4\n";
+        commit_all(&repo, &[("old.txt", content)]);
+        fs::rename(temp.path().join("old.txt"), temp.path().join("new.txt")).unwrap();
+        let highlighter = SyntaxHighlighter::default();
+        let options = DiffOptions {
+            detect_renames: true,
+            ..DiffOptions::default()
+        };
+
+        // when
+        let files = get_working_tree_diff(&repo, &options, &highlighter).unwrap();
+
+        // then
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].status, FileStatus::Renamed);
+        assert_eq!(files[0].old_path, Some(PathBuf::from("old.txt")));
+        assert_eq!(files[0].new_path, Some(PathBuf::from("new.txt")));
+    }
 }
@@ -1,3 +1,5 @@
+pub mod attributes;
+pub mod blame;
 pub mod context;
 pub mod diff;
 pub mod repository;
@@ -9,16 +11,18 @@ use crate::error::{Result, TuicrError};
 use crate::model::{DiffFile, DiffLine, FileStatus};
 use crate::syntax::SyntaxHighlighter;
 
-use super::traits::{CommitInfo, VcsBackend, VcsInfo, VcsType};
+use super::diff_options::DiffOptions;
+use super::traits::{BlameLine, CommitInfo, FileVersion, VcsBackend, VcsInfo, VcsType};
 
 // Re-export commonly used functions
-pub use context::{calculate_gap, fetch_context_lines};
+pub use context::{calculate_gap, fetch_context_lines, read_file_version};
 pub use diff::{get_commit_range_diff, get_working_tree_diff, get_working_tree_with_commits_diff};
 
 /// Git backend implementation using git2 library
 pub struct GitBackend {
     repo: Repository,
     info: VcsInfo,
+    diff_options: DiffOptions,
 }
 
 impl GitBackend {
@@ -54,7 +58,11 @@ impl GitBackend {
             vcs_type: VcsType::Git,
         };
 
-        Ok(Self { repo, info })
+        Ok(Self {
+            repo,
+            info,
+            diff_options: DiffOptions::default(),
+        })
     }
 }
 
@@ -63,8 +71,13 @@ impl VcsBackend for GitBackend {
         &self.info
     }
 
+    fn set_diff_options(&mut self, options: DiffOptions) -> Result<()> {
+        self.diff_options = options;
+        Ok(())
+    }
+
     fn get_working_tree_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-        get_working_tree_diff(&self.repo, highlighter)
+        get_working_tree_diff(&self.repo, &self.diff_options, highlighter)
     }
 
     fn fetch_context_lines(
@@ -86,6 +99,7 @@ impl VcsBackend for GitBackend {
                 short_id: c.short_id,
                 branch_name: c.branch_name,
                 summary: c.summary,
+                message: c.message,
                 author: c.author,
                 time: c.time,
             })
@@ -101,7 +115,7 @@ impl VcsBackend for GitBackend {
         commit_ids: &[String],
         highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
-        get_commit_range_diff(&self.repo, commit_ids, highlighter)
+        get_commit_range_diff(&self.repo, commit_ids, &self.diff_options, highlighter)
     }
 
     fn get_commits_info(&self, ids: &[String]) -> Result<Vec<CommitInfo>> {
@@ -113,17 +127,34 @@ impl VcsBackend for GitBackend {
                 short_id: c.short_id,
                 branch_name: c.branch_name,
                 summary: c.summary,
+                message: c.message,
                 author: c.author,
                 time: c.time,
             })
             .collect())
     }
 
+    fn blame(&self, file_path: &Path, base_commit: Option<&str>) -> Result<Vec<BlameLine>> {
+        blame::blame_file(&self.repo, file_path, base_commit)
+    }
+
+    fn file_content(&self, file_path: &Path, version: FileVersion<'_>) -> Result<String> {
+        read_file_version(&self.repo, file_path, version)
+    }
+
+    fn is_generated(&self, file_path: &Path) -> Result<bool> {
+        attributes::is_generated(&self.repo, file_path)
+    }
+
+    fn user_name(&self) -> Result<String> {
+        Ok(self.repo.config()?.get_string("user.name")?)
+    }
+
     fn get_working_tree_with_commits_diff(
         &self,
         commit_ids: &[String],
         highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
-        get_working_tree_with_commits_diff(&self.repo, commit_ids, highlighter)
+        get_working_tree_with_commits_diff(&self.repo, commit_ids, &self.diff_options, highlighter)
     }
 }
@@ -10,6 +10,7 @@ pub struct CommitInfo {
     pub short_id: String,
     pub branch_name: Option<String>,
     pub summary: String,
+    pub message: String,
     pub author: String,
     pub time: DateTime<Utc>,
 }
@@ -58,6 +59,7 @@ pub fn get_recent_commits(
         let id = oid.to_string();
         let short_id = id[..7.min(id.len())].to_string();
         let summary = commit.summary().unwrap_or("(no message)").to_string();
+        let message = String::from_utf8_lossy(commit.message_bytes()).into_owned();
         let author = commit.author().name().unwrap_or("Unknown").to_string();
         let branch_name = branch_tip_names
             .get(&oid)
@@ -72,6 +74,7 @@ pub fn get_recent_commits(
             short_id,
             branch_name,
             summary,
+            message,
             author,
             time,
         });
@@ -96,6 +99,7 @@ pub fn get_commits_info(repo: &Repository, ids: &[String]) -> Result<Vec<CommitI
         let id = oid.to_string();
         let short_id = id[..7.min(id.len())].to_string();
         let summary = commit.summary().unwrap_or("(no message)").to_string();
+        let message = String::from_utf8_lossy(commit.message_bytes()).into_owned();
         let author = commit.author().name().unwrap_or("Unknown").to_string();
         let branch_name = branch_tip_names
             .get(&oid)
@@ -110,6 +114,7 @@ pub fn get_commits_info(repo: &Repository, ids: &[String]) -> Result<Vec<CommitI
             short_id,
             branch_name,
             summary,
+            message,
             author,
             time,
         });
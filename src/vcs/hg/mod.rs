@@ -7,12 +7,14 @@ use chrono::{TimeZone, Utc};
 use crate::error::{Result, TuicrError};
 use crate::model::{DiffFile, DiffLine, FileStatus, LineOrigin};
 use crate::syntax::SyntaxHighlighter;
+use crate::vcs::diff_options::{DEFAULT_RENAME_SIMILARITY, DiffOptions, WhitespaceMode};
 use crate::vcs::diff_parser::{self, DiffFormat};
-use crate::vcs::traits::{CommitInfo, VcsBackend, VcsInfo, VcsType};
+use crate::vcs::traits::{BlameLine, CommitInfo, FileVersion, VcsBackend, VcsInfo, VcsType};
 
 /// Mercurial backend implementation using hg CLI commands
 pub struct HgBackend {
     info: VcsInfo,
+    diff_options: DiffOptions,
 }
 
 impl HgBackend {
@@ -55,7 +57,19 @@ impl HgBackend {
             vcs_type: VcsType::Mercurial,
         };
 
-        Ok(Self { info })
+        Ok(Self {
+            info,
+            diff_options: DiffOptions::default(),
+        })
+    }
+
+    /// Run `hg diff` with the configured diff options
+    fn run_diff(&self, revs: &[&str]) -> Result<String> {
+        let flags = diff_flags(&self.diff_options);
+        let mut args = vec!["diff"];
+        args.extend(flags.iter().map(String::as_str));
+        args.extend_from_slice(revs);
+        run_hg_command(&self.info.root_path, &args)
     }
 }
 
@@ -64,9 +78,21 @@ impl VcsBackend for HgBackend {
         &self.info
     }
 
+    fn set_diff_options(&mut self, options: DiffOptions) -> Result<()> {
+        let unsupported = unsupported_options(&options);
+        self.diff_options = options;
+        if unsupported.is_empty() {
+            return Ok(());
+        }
+        Err(TuicrError::UnsupportedOperation(format!(
+            "hg diffs ignore {}",
+            unsupported.join(", ")
+        )))
+    }
+
     fn get_working_tree_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
         // Get unified diff output from hg
-        let diff_output = run_hg_command(&self.info.root_path, &["diff"])?;
+        let diff_output = self.run_diff(&[])?;
 
         if diff_output.trim().is_empty() {
             return Err(TuicrError::NoChanges);
@@ -106,15 +132,16 @@ impl VcsBackend for HgBackend {
 
         for line_num in start_line..=end_line {
             let idx = (line_num - 1) as usize;
-            if idx < lines.len() {
-                result.push(DiffLine {
-                    origin: LineOrigin::Context,
-                    content: lines[idx].to_string(),
-                    old_lineno: Some(line_num),
-                    new_lineno: Some(line_num),
-                    highlighted_spans: None,
-                });
-            }
+            let Some(line) = lines.get(idx) else {
+                break;
+            };
+            result.push(DiffLine {
+                origin: LineOrigin::Context,
+                content: line.to_string(),
+                old_lineno: Some(line_num),
+                new_lineno: Some(line_num),
+                highlighted_spans: None,
+            });
         }
 
         Ok(result)
@@ -151,8 +178,7 @@ impl VcsBackend for HgBackend {
         // hg log doesn't have a --skip option, so we fetch offset+limit commits
         // and skip the first `offset` in Rust code
         let fetch_count = offset + limit;
-        let template =
-            "{node}\\x00{node|short}\\x00{desc|firstline}\\x00{author|user}\\x00{date|hgdate}\\x01";
+        let template = "{node}\\x00{node|short}\\x00{desc|firstline}\\x00{author|user}\\x00{date|hgdate}\\x00{desc}\\x01";
         let output = run_hg_command(
             &self.info.root_path,
             &[
@@ -180,6 +206,7 @@ impl VcsBackend for HgBackend {
             let short_id = parts[1].to_string();
             let summary = parts[2].to_string();
             let author = parts[3].to_string();
+            let message = parts.get(5).map(|s| s.to_string()).unwrap_or_default();
 
             // hgdate format is "unix_timestamp timezone_offset"
             let time = parts[4]
@@ -194,6 +221,7 @@ impl VcsBackend for HgBackend {
                 short_id,
                 branch_name: None,
                 summary,
+                message,
                 author,
                 time,
             });
@@ -250,10 +278,7 @@ impl VcsBackend for HgBackend {
             _ => "null".to_string(),
         };
 
-        let diff_output = run_hg_command(
-            &self.info.root_path,
-            &["diff", "-r", &from_rev, "-r", newest_short],
-        )?;
+        let diff_output = self.run_diff(&["-r", &from_rev, "-r", newest_short])?;
 
         if diff_output.trim().is_empty() {
             return Err(TuicrError::NoChanges);
@@ -278,8 +303,7 @@ impl VcsBackend for HgBackend {
             })
             .collect::<Vec<_>>()
             .join(" | ");
-        let template =
-            "{node}\\x00{node|short}\\x00{desc|firstline}\\x00{author|user}\\x00{date|hgdate}\\x01";
+        let template = "{node}\\x00{node|short}\\x00{desc|firstline}\\x00{author|user}\\x00{date|hgdate}\\x00{desc}\\x01";
         let output = run_hg_command(
             &self.info.root_path,
             &["log", "-r", &revset, "--template", template],
@@ -299,6 +323,7 @@ impl VcsBackend for HgBackend {
             let short_id = parts[1].to_string();
             let summary = parts[2].to_string();
             let author = parts[3].to_string();
+            let message = parts.get(5).map(|s| s.to_string()).unwrap_or_default();
             let time = parts[4]
                 .split_whitespace()
                 .next()
@@ -312,6 +337,7 @@ impl VcsBackend for HgBackend {
                     short_id,
                     branch_name: None,
                     summary,
+                    message,
                     author,
                     time,
                 },
@@ -322,6 +348,60 @@ impl VcsBackend for HgBackend {
         Ok(ids.iter().filter_map(|id| by_id.remove(id)).collect())
     }
 
+    fn blame(&self, file_path: &Path, base_commit: Option<&str>) -> Result<Vec<BlameLine>> {
+        // The old side is the parent of the oldest reviewed commit, or the
+        // working directory parent for uncommitted changes
+        let rev = match base_commit {
+            Some(id) => format!("p1({})", if id.len() > 12 { &id[..12] } else { id }),
+            None => ".".to_string(),
+        };
+        let template = "{lines % '{node}\\x00{node|short}\\x00{user|user}\\x00{date|hgdate}\\x01'}";
+        let output = run_hg_command(
+            &self.info.root_path,
+            &[
+                "annotate",
+                "-r",
+                &rev,
+                "--template",
+                template,
+                &file_path.to_string_lossy(),
+            ],
+        )?;
+
+        Ok(parse_annotate_output(&output))
+    }
+
+    fn user_name(&self) -> Result<String> {
+        let username = run_hg_command(&self.info.root_path, &["config", "ui.username"])?;
+        username_name(&username)
+            .map(str::to_string)
+            .ok_or_else(|| TuicrError::VcsCommand("ui.username has no name".to_string()))
+    }
+
+    fn file_content(&self, file_path: &Path, version: FileVersion<'_>) -> Result<String> {
+        let short = |id: &str| {
+            if id.len() > 12 {
+                id[..12].to_string()
+            } else {
+                id.to_string()
+            }
+        };
+        let rev = match version {
+            FileVersion::WorkingTree => {
+                return Ok(std::fs::read_to_string(
+                    self.info.root_path.join(file_path),
+                )?);
+            }
+            FileVersion::Commit(id) => short(id),
+            FileVersion::ParentOf(Some(id)) => format!("p1({})", short(id)),
+            FileVersion::ParentOf(None) => ".".to_string(),
+        };
+        run_hg_command(
+            &self.info.root_path,
+            &["cat", "-r", &rev, &file_path.to_string_lossy()],
+        )
+    }
+
     fn get_working_tree_with_commits_diff(
         &self,
         commit_ids: &[String],
@@ -357,7 +437,7 @@ impl VcsBackend for HgBackend {
         };
 
         // Diff from parent of oldest to working directory (omit --to)
-        let diff_output = run_hg_command(&self.info.root_path, &["diff", "-r", &from_rev])?;
+        let diff_output = self.run_diff(&["-r", &from_rev])?;
 
         if diff_output.trim().is_empty() {
             return Err(TuicrError::NoChanges);
@@ -367,6 +447,61 @@ impl VcsBackend for HgBackend {
     }
 }
 
+/// `hg diff` flags for the given diff options
+fn diff_flags(options: &DiffOptions) -> Vec<String> {
+    let mut flags = vec!["-U".to_string(), options.context_lines.to_string()];
+    match options.whitespace {
+        WhitespaceMode::None => {}
+        WhitespaceMode::All => flags.push("--ignore-all-space".to_string()),
+        WhitespaceMode::AtEol => flags.push("--ignore-space-at-eol".to_string()),
+        WhitespaceMode::Change => flags.push("--ignore-space-change".to_string()),
+    }
+    if options.ignore_blank_lines {
+        flags.push("--ignore-blank-lines".to_string());
+    }
+    // hg records renames and copies explicitly rather than by similarity;
+    // they only show up in git-style output
+    if options.detect_renames {
+        flags.push("--git".to_string());
+    }
+    flags
+}
+
+/// `:set` names of the options `hg diff` has no equivalent for: renames are
+/// recorded when committing, so there is no similarity threshold
+fn unsupported_options(options: &DiffOptions) -> Vec<String> {
+    if options.detect_renames && options.rename_similarity != DEFAULT_RENAME_SIMILARITY {
+        vec![format!("renames={}", options.rename_similarity)]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Parse `hg annotate` template output (one `\x01`-terminated record per line)
+fn parse_annotate_output(output: &str) -> Vec<BlameLine> {
+    output
+        .split('\x01')
+        .filter_map(|record| {
+            let parts: Vec<&str> = record.split('\x00').collect();
+            if parts.len() < 4 {
+                return None;
+            }
+            let time = parts[3]
+                .split_whitespace()
+                .next()
+                .and_then(|s| s.parse::<i64>().ok())
+                .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+                .unwrap_or_else(Utc::now);
+            Some(BlameLine {
+                commit_id: parts[0].to_string(),
+                short_id: parts[1].to_string(),
+                author: parts[2].to_string(),
+                time,
+            })
+        })
+        .collect()
+}
+
 /// Run an hg command and return its stdout
 fn run_hg_command(root: &Path, args: &[&str]) -> Result<String> {
     let output = Command::new("hg")
@@ -387,6 +522,12 @@ fn run_hg_command(root: &Path, args: &[&str]) -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// The name part of a `Name <email>` username
+fn username_name(username: &str) -> Option<&str> {
+    let name = username.split('<').next().unwrap_or_default().trim();
+    (!name.is_empty()).then_some(name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -894,4 +1035,117 @@ mod tests {
         // Don't assert exact path/status as hg implementations differ (Sapling vs standard hg)
         let _path = file.display_path();
     }
+
+    #[test]
+    fn test_hg_parse_annotate_output() {
+        let output =
+            "aaa111\x00aaa\x00alice\x001705314600 0\x01bbb222\x00bbb\x00bob\x001705314700 3600\x01";
+
+        let lines = parse_annotate_output(output);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].commit_id, "aaa111");
+        assert_eq!(lines[0].short_id, "aaa");
+        assert_eq!(lines[0].author, "alice");
+        assert_eq!(lines[0].time.timestamp(), 1705314600);
+        assert_eq!(lines[1].author, "bob");
+    }
+
+    #[test]
+    fn test_hg_blame() {
+        let Some(temp) = setup_test_repo_with_commits() else {
+            eprintln!("Skipping test: hg command not available");
+            return;
+        };
+
+        let backend =
+            HgBackend::from_path(temp.path().to_path_buf()).expect("Failed to create hg backend");
+
+        let lines = backend
+            .blame(Path::new("file1.txt"), None)
+            .expect("Failed to blame file");
+
+        // file1.txt has two lines as of the working copy parent
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| !l.commit_id.is_empty()));
+        assert_ne!(lines[0].commit_id, lines[1].commit_id);
+    }
+
+    #[test]
+    fn test_hg_file_content() {
+        let Some(temp) = setup_test_repo_with_commits() else {
+            eprintln!("Skipping test: hg command not available");
+            return;
+        };
+        fs::write(temp.path().join("file1.txt"), "changed\n").expect("Failed to write file");
+
+        let backend =
+            HgBackend::from_path(temp.path().to_path_buf()).expect("Failed to create hg backend");
+        let path = Path::new("file1.txt");
+
+        let parent = backend
+            .file_content(path, FileVersion::ParentOf(None))
+            .expect("Failed to read parent version");
+        let working = backend
+            .file_content(path, FileVersion::WorkingTree)
+            .expect("Failed to read working tree version");
+
+        assert_eq!(parent, "first file\nmodified\n");
+        assert_eq!(working, "changed\n");
+    }
+
+    #[test]
+    fn should_map_diff_options_to_hg_flags() {
+        // given
+        let options = DiffOptions {
+            whitespace: WhitespaceMode::AtEol,
+            context_lines: 5,
+            detect_renames: true,
+            rename_similarity: 50,
+            ignore_blank_lines: true,
+        };
+
+        // when
+        let flags = diff_flags(&options);
+
+        // then
+        assert_eq!(
+            flags,
+            vec![
+                "-U",
+                "5",
+                "--ignore-space-at-eol",
+                "--ignore-blank-lines",
+                "--git"
+            ]
+        );
+        assert_eq!(diff_flags(&DiffOptions::default()), vec!["-U", "3"]);
+        assert!(unsupported_options(&options).is_empty());
+    }
+
+    #[test]
+    fn should_report_rename_similarity_as_unsupported() {
+        // given
+        let options = DiffOptions {
+            detect_renames: true,
+            rename_similarity: 80,
+            ..DiffOptions::default()
+        };
+
+        // when
+        let unsupported = unsupported_options(&options);
+
+        // then
+        assert_eq!(unsupported, vec!["renames=80"]);
+    }
+
+    #[test]
+    fn should_take_the_name_from_a_username() {
+        assert_eq!(
+            username_name("Jane Doe <jane@example.com>\n"),
+            Some("Jane Doe")
+        );
+        assert_eq!(username_name("jane\n"), Some("jane"));
+        assert_eq!(username_name("<jane@example.com>"), None);
+    }
 }
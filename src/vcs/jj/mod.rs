@@ -9,12 +9,14 @@ use chrono::{DateTime, Utc};
 use crate::error::{Result, TuicrError};
 use crate::model::{DiffFile, DiffLine, FileStatus, LineOrigin};
 use crate::syntax::SyntaxHighlighter;
+use crate::vcs::diff_options::{DEFAULT_RENAME_SIMILARITY, DiffOptions, WhitespaceMode};
 use crate::vcs::diff_parser::{self, DiffFormat};
-use crate::vcs::traits::{CommitInfo, VcsBackend, VcsInfo, VcsType};
+use crate::vcs::traits::{BlameLine, CommitInfo, FileVersion, VcsBackend, VcsInfo, VcsType};
 
 /// Jujutsu backend implementation using jj CLI commands
 pub struct JjBackend {
     info: VcsInfo,
+    diff_options: DiffOptions,
 }
 
 impl JjBackend {
@@ -92,7 +94,19 @@ impl JjBackend {
             vcs_type: VcsType::Jujutsu,
         };
 
-        Ok(Self { info })
+        Ok(Self {
+            info,
+            diff_options: DiffOptions::default(),
+        })
+    }
+
+    /// Run `jj diff --git` with the configured diff options
+    fn run_diff(&self, revs: &[&str]) -> Result<String> {
+        let flags = diff_flags(&self.diff_options);
+        let mut args = vec!["diff"];
+        args.extend_from_slice(revs);
+        args.extend(flags.iter().map(String::as_str));
+        run_jj_command(&self.info.root_path, &args)
     }
 }
 
@@ -101,9 +115,21 @@ impl VcsBackend for JjBackend {
         &self.info
     }
 
+    fn set_diff_options(&mut self, options: DiffOptions) -> Result<()> {
+        let unsupported = unsupported_options(&options);
+        self.diff_options = options;
+        if unsupported.is_empty() {
+            return Ok(());
+        }
+        Err(TuicrError::UnsupportedOperation(format!(
+            "jj diffs ignore {}",
+            unsupported.join(", ")
+        )))
+    }
+
     fn get_working_tree_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
         // Get unified diff output from jj using --git format
-        let diff_output = run_jj_command(&self.info.root_path, &["diff", "--git"])?;
+        let diff_output = self.run_diff(&[])?;
 
         if diff_output.trim().is_empty() {
             return Err(TuicrError::NoChanges);
@@ -143,15 +169,16 @@ impl VcsBackend for JjBackend {
 
         for line_num in start_line..=end_line {
             let idx = (line_num - 1) as usize;
-            if idx < lines.len() {
-                result.push(DiffLine {
-                    origin: LineOrigin::Context,
-                    content: lines[idx].to_string(),
-                    old_lineno: Some(line_num),
-                    new_lineno: Some(line_num),
-                    highlighted_spans: None,
-                });
-            }
+            let Some(line) = lines.get(idx) else {
+                break;
+            };
+            result.push(DiffLine {
+                origin: LineOrigin::Context,
+                content: line.to_string(),
+                old_lineno: Some(line_num),
+                new_lineno: Some(line_num),
+                highlighted_spans: None,
+            });
         }
 
         Ok(result)
@@ -196,7 +223,7 @@ impl VcsBackend for JjBackend {
         // jj log doesn't have a --skip option, so we fetch offset+limit commits
         // and skip the first `offset` in Rust code
         let fetch_count = offset + limit;
-        let template = r#"commit_id ++ "\x00" ++ commit_id.short() ++ "\x00" ++ description.first_line() ++ "\x00" ++ author.email() ++ "\x00" ++ committer.timestamp() ++ "\x01""#;
+        let template = r#"commit_id ++ "\x00" ++ commit_id.short() ++ "\x00" ++ description.first_line() ++ "\x00" ++ author.email() ++ "\x00" ++ committer.timestamp() ++ "\x00" ++ description ++ "\x01""#;
         let output = run_jj_command(
             &self.info.root_path,
             &[
@@ -227,6 +254,7 @@ impl VcsBackend for JjBackend {
             let short_id = parts[1].to_string();
             let summary = parts[2].to_string();
             let author = parts[3].to_string();
+            let message = parts.get(5).map(|s| s.to_string()).unwrap_or_default();
 
             // jj timestamp format is ISO 8601: "2024-01-15T10:30:00.000-05:00"
             let time = DateTime::parse_from_rfc3339(parts[4])
@@ -238,6 +266,7 @@ impl VcsBackend for JjBackend {
                 short_id,
                 branch_name: None,
                 summary,
+                message,
                 author,
                 time,
             });
@@ -261,17 +290,7 @@ impl VcsBackend for JjBackend {
 
         // Get the parent of the oldest commit to include its changes
         // In jj, we use {commit}- to get the parent(s)
-        let diff_output = run_jj_command(
-            &self.info.root_path,
-            &[
-                "diff",
-                "--from",
-                &format!("{}-", oldest),
-                "--to",
-                newest,
-                "--git",
-            ],
-        )?;
+        let diff_output = self.run_diff(&["--from", &format!("{}-", oldest), "--to", newest])?;
 
         if diff_output.trim().is_empty() {
             return Err(TuicrError::NoChanges);
@@ -290,7 +309,7 @@ impl VcsBackend for JjBackend {
             .map(|id| id.as_str())
             .collect::<Vec<_>>()
             .join(" | ");
-        let template = r#"commit_id ++ "\x00" ++ commit_id.short() ++ "\x00" ++ description.first_line() ++ "\x00" ++ author.email() ++ "\x00" ++ committer.timestamp() ++ "\x01""#;
+        let template = r#"commit_id ++ "\x00" ++ commit_id.short() ++ "\x00" ++ description.first_line() ++ "\x00" ++ author.email() ++ "\x00" ++ committer.timestamp() ++ "\x00" ++ description ++ "\x01""#;
         let output = run_jj_command(
             &self.info.root_path,
             &["log", "-r", &revset, "--no-graph", "-T", template],
@@ -310,6 +329,7 @@ impl VcsBackend for JjBackend {
             let short_id = parts[1].to_string();
             let summary = parts[2].to_string();
             let author = parts[3].to_string();
+            let message = parts.get(5).map(|s| s.to_string()).unwrap_or_default();
             let time = DateTime::parse_from_rfc3339(parts[4])
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_else(|_| Utc::now());
@@ -320,6 +340,7 @@ impl VcsBackend for JjBackend {
                     short_id,
                     branch_name: None,
                     summary,
+                    message,
                     author,
                     time,
                 },
@@ -330,6 +351,55 @@ impl VcsBackend for JjBackend {
         Ok(ids.iter().filter_map(|id| by_id.remove(id)).collect())
     }
 
+    fn blame(&self, file_path: &Path, base_commit: Option<&str>) -> Result<Vec<BlameLine>> {
+        // The old side is the parent of the oldest reviewed commit, or the
+        // parent of the working-copy commit for uncommitted changes
+        let rev = match base_commit {
+            Some(id) => format!("{}-", id),
+            None => "@-".to_string(),
+        };
+        let template = r#"commit.commit_id() ++ "\x00" ++ commit.commit_id().short() ++ "\x00" ++ commit.author().email() ++ "\x00" ++ commit.committer().timestamp() ++ "\x01""#;
+        let output = run_jj_command(
+            &self.info.root_path,
+            &[
+                "file",
+                "annotate",
+                "-r",
+                &rev,
+                "-T",
+                template,
+                &file_path.to_string_lossy(),
+            ],
+        )?;
+
+        Ok(parse_annotate_output(&output))
+    }
+
+    fn user_name(&self) -> Result<String> {
+        let name = run_jj_command(&self.info.root_path, &["config", "get", "user.name"])?;
+        match name.trim() {
+            "" => Err(TuicrError::VcsCommand("user.name is not set".to_string())),
+            name => Ok(name.to_string()),
+        }
+    }
+
+    fn file_content(&self, file_path: &Path, version: FileVersion<'_>) -> Result<String> {
+        let rev = match version {
+            FileVersion::WorkingTree => {
+                return Ok(std::fs::read_to_string(
+                    self.info.root_path.join(file_path),
+                )?);
+            }
+            FileVersion::Commit(id) => id.to_string(),
+            FileVersion::ParentOf(Some(id)) => format!("{id}-"),
+            FileVersion::ParentOf(None) => "@-".to_string(),
+        };
+        run_jj_command(
+            &self.info.root_path,
+            &["file", "show", "-r", &rev, &file_path.to_string_lossy()],
+        )
+    }
+
     fn get_working_tree_with_commits_diff(
         &self,
         commit_ids: &[String],
@@ -343,17 +413,7 @@ impl VcsBackend for JjBackend {
         let oldest = &commit_ids[0];
 
         // Diff from the parent of the oldest commit to the working copy (@)
-        let diff_output = run_jj_command(
-            &self.info.root_path,
-            &[
-                "diff",
-                "--from",
-                &format!("{}-", oldest),
-                "--to",
-                "@",
-                "--git",
-            ],
-        )?;
+        let diff_output = self.run_diff(&["--from", &format!("{}-", oldest), "--to", "@"])?;
 
         if diff_output.trim().is_empty() {
             return Err(TuicrError::NoChanges);
@@ -363,6 +423,61 @@ impl VcsBackend for JjBackend {
     }
 }
 
+/// `jj diff` flags for the given diff options, leaving out the
+/// [unsupported ones](unsupported_options)
+fn diff_flags(options: &DiffOptions) -> Vec<String> {
+    let mut flags = vec![
+        "--git".to_string(),
+        "--context".to_string(),
+        options.context_lines.to_string(),
+    ];
+    match options.whitespace {
+        WhitespaceMode::None | WhitespaceMode::AtEol => {}
+        WhitespaceMode::All => flags.push("--ignore-all-space".to_string()),
+        WhitespaceMode::Change => flags.push("--ignore-space-change".to_string()),
+    }
+    flags
+}
+
+/// `:set` names of the options `jj diff` has no equivalent for: it can't
+/// ignore only trailing whitespace or blank lines, and reports the renames
+/// and copies it tracks without a similarity threshold
+fn unsupported_options(options: &DiffOptions) -> Vec<String> {
+    let mut unsupported = Vec::new();
+    if options.whitespace == WhitespaceMode::AtEol {
+        unsupported.push("whitespace=at-eol".to_string());
+    }
+    if options.ignore_blank_lines {
+        unsupported.push("ignoreblanklines".to_string());
+    }
+    if options.detect_renames && options.rename_similarity != DEFAULT_RENAME_SIMILARITY {
+        unsupported.push(format!("renames={}", options.rename_similarity));
+    }
+    unsupported
+}
+
+/// Parse `jj file annotate` template output (one `\x01`-terminated record per line)
+fn parse_annotate_output(output: &str) -> Vec<BlameLine> {
+    output
+        .split('\x01')
+        .filter_map(|record| {
+            let parts: Vec<&str> = record.split('\x00').collect();
+            if parts.len() < 4 {
+                return None;
+            }
+            let time = DateTime::parse_from_rfc3339(parts[3])
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now());
+            Some(BlameLine {
+                commit_id: parts[0].to_string(),
+                short_id: parts[1].to_string(),
+                author: parts[2].to_string(),
+                time,
+            })
+        })
+        .collect()
+}
+
 /// Run a jj command and return its stdout
 fn run_jj_command(root: &Path, args: &[&str]) -> Result<String> {
     let output = Command::new("jj")
@@ -973,4 +1088,104 @@ mod tests {
             info.branch_name
         );
     }
+
+    #[test]
+    fn test_jj_parse_annotate_output() {
+        let output = "aaa111\x00aaa\x00alice\x002024-01-15T10:30:00.000+00:00\x01bbb222\x00bbb\x00bob\x002024-01-15T10:31:40.000+01:00\x01";
+
+        let lines = parse_annotate_output(output);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].commit_id, "aaa111");
+        assert_eq!(lines[0].short_id, "aaa");
+        assert_eq!(lines[0].author, "alice");
+        assert_eq!(lines[0].time.timestamp(), 1705314600);
+        assert_eq!(lines[1].author, "bob");
+    }
+
+    #[test]
+    fn test_jj_blame() {
+        let Some(temp) = setup_test_repo_with_commits() else {
+            eprintln!("Skipping test: jj command not available");
+            return;
+        };
+
+        let backend =
+            JjBackend::from_path(temp.path().to_path_buf()).expect("Failed to create jj backend");
+
+        let lines = backend
+            .blame(Path::new("file1.txt"), None)
+            .expect("Failed to blame file");
+
+        // file1.txt has two lines as of the working copy parent
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| !l.commit_id.is_empty()));
+        assert_ne!(lines[0].commit_id, lines[1].commit_id);
+    }
+
+    #[test]
+    fn test_jj_file_content() {
+        let Some(temp) = setup_test_repo_with_commits() else {
+            eprintln!("Skipping test: jj command not available");
+            return;
+        };
+        fs::write(temp.path().join("file1.txt"), "changed\n").expect("Failed to write file");
+
+        let backend =
+            JjBackend::from_path(temp.path().to_path_buf()).expect("Failed to create jj backend");
+        let path = Path::new("file1.txt");
+
+        let parent = backend
+            .file_content(path, FileVersion::ParentOf(None))
+            .expect("Failed to read parent version");
+        let working = backend
+            .file_content(path, FileVersion::WorkingTree)
+            .expect("Failed to read working tree version");
+
+        assert_eq!(parent, "first file\nmodified\n");
+        assert_eq!(working, "changed\n");
+    }
+
+    #[test]
+    fn should_map_diff_options_to_jj_flags() {
+        // given
+        let options = DiffOptions {
+            whitespace: WhitespaceMode::All,
+            context_lines: 10,
+            ..DiffOptions::default()
+        };
+
+        // when
+        let flags = diff_flags(&options);
+
+        // then
+        assert_eq!(
+            flags,
+            vec!["--git", "--context", "10", "--ignore-all-space"]
+        );
+    }
+
+    #[test]
+    fn should_report_diff_options_jj_cannot_apply() {
+        // given
+        let options = DiffOptions {
+            whitespace: WhitespaceMode::AtEol,
+            detect_renames: true,
+            rename_similarity: 80,
+            ignore_blank_lines: true,
+            ..DiffOptions::default()
+        };
+
+        // when
+        let flags = diff_flags(&options);
+        let unsupported = unsupported_options(&options);
+
+        // then
+        assert_eq!(flags, vec!["--git", "--context", "3"]);
+        assert_eq!(
+            unsupported,
+            vec!["whitespace=at-eol", "ignoreblanklines", "renames=80"]
+        );
+        assert!(unsupported_options(&DiffOptions::default()).is_empty());
+    }
 }
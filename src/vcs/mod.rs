@@ -11,16 +11,18 @@
 //! are Git-backed and contain a `.git` directory. If jj detection fails, Git
 //! is tried next, then Mercurial.
 
+pub mod diff_options;
 mod diff_parser;
 pub mod git;
 mod hg;
 mod jj;
 mod traits;
 
+pub use diff_options::{DiffOptions, WhitespaceMode};
 pub use git::GitBackend;
 pub use hg::HgBackend;
 pub use jj::JjBackend;
-pub use traits::{CommitInfo, VcsBackend, VcsInfo};
+pub use traits::{BlameLine, CommitInfo, FileVersion, VcsBackend, VcsInfo, VcsType};
 
 use crate::error::{Result, TuicrError};
 
@@ -73,6 +75,7 @@ mod tests {
             short_id: "abc".to_string(),
             branch_name: Some("main".to_string()),
             summary: "test".to_string(),
+            message: "test".to_string(),
             author: "author".to_string(),
             time: chrono::Utc::now(),
         };
@@ -4,6 +4,7 @@ use std::path::{Path, PathBuf};
 use crate::error::Result;
 use crate::model::{DiffFile, DiffLine, FileStatus};
 use crate::syntax::SyntaxHighlighter;
+use crate::vcs::diff_options::DiffOptions;
 
 /// Information about the VCS type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -42,15 +43,61 @@ pub struct CommitInfo {
     /// For Git this is populated for commits that are branch tips.
     pub branch_name: Option<String>,
     pub summary: String,
+    /// Full commit message (summary, body and trailers).
+    pub message: String,
     pub author: String,
     pub time: DateTime<Utc>,
 }
 
+impl CommitInfo {
+    /// Render the commit header and full message as reviewable text lines.
+    pub fn message_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("commit {}", self.id),
+            format!("Author: {}", self.author),
+            format!("Date:   {}", self.time.format("%Y-%m-%d %H:%M:%S %z")),
+            String::new(),
+        ];
+        let message = if self.message.trim().is_empty() {
+            self.summary.as_str()
+        } else {
+            self.message.trim_end()
+        };
+        lines.extend(message.lines().map(|line| line.replace('\t', "    ")));
+        lines
+    }
+}
+
+/// Blame information for a single line on the old side of a diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameLine {
+    pub commit_id: String,
+    pub short_id: String,
+    pub author: String,
+    pub time: DateTime<Utc>,
+}
+
+/// Which version of a file to read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileVersion<'a> {
+    /// The file as it is on disk
+    WorkingTree,
+    /// The file as of a commit
+    Commit(&'a str),
+    /// The file as of a commit's first parent; `None` means the working copy's parent
+    ParentOf(Option<&'a str>),
+}
+
 /// Trait for VCS backend implementations
 pub trait VcsBackend: Send {
     /// Get repository information
     fn info(&self) -> &VcsInfo;
 
+    /// Set the options used for all subsequent diffs. Options the backend
+    /// can't apply are left out of its diffs and reported as
+    /// `UnsupportedOperation`.
+    fn set_diff_options(&mut self, options: DiffOptions) -> Result<()>;
+
     /// Get the working tree diff (uncommitted changes)
     fn get_working_tree_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>>;
 
@@ -96,6 +143,41 @@ pub trait VcsBackend: Send {
         Ok(Vec::new())
     }
 
+    /// Blame the old side of a file.
+    /// With `base_commit` set, blames the file as of that commit's parent (the old
+    /// side of a commit range diff); otherwise as of the working copy's parent.
+    /// Returns one entry per line, so index 0 describes line 1.
+    /// Returns error if not supported (default).
+    fn blame(&self, _file_path: &Path, _base_commit: Option<&str>) -> Result<Vec<BlameLine>> {
+        Err(crate::error::TuicrError::UnsupportedOperation(
+            "Blame not supported for this VCS".into(),
+        ))
+    }
+
+    /// Read the full content of a file at the given version.
+    /// Returns error if not supported (default).
+    fn file_content(&self, _file_path: &Path, _version: FileVersion<'_>) -> Result<String> {
+        Err(crate::error::TuicrError::UnsupportedOperation(
+            "Reading file versions not supported for this VCS".into(),
+        ))
+    }
+
+    /// Whether the repository marks a file as generated or vendored.
+    /// Returns error if not supported (default).
+    fn is_generated(&self, _file_path: &Path) -> Result<bool> {
+        Err(crate::error::TuicrError::UnsupportedOperation(
+            "Generated file attributes not supported for this VCS".into(),
+        ))
+    }
+
+    /// The user's name from the VCS configuration, to attribute comments to.
+    /// Returns error if not supported or not configured (default).
+    fn user_name(&self) -> Result<String> {
+        Err(crate::error::TuicrError::UnsupportedOperation(
+            "User name not supported for this VCS".into(),
+        ))
+    }
+
     /// Get a combined diff from the parent of the oldest commit through to the working tree.
     /// This shows both committed and uncommitted changes in a single diff.
     /// Returns error if not supported (default).
@@ -113,6 +195,7 @@ pub trait VcsBackend: Send {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
 
     #[test]
     fn vcs_type_display_git() {
@@ -173,6 +256,7 @@ mod tests {
             short_id: "abc123d".to_string(),
             branch_name: Some("main".to_string()),
             summary: "Fix bug".to_string(),
+            message: "Fix bug\n\nDetails".to_string(),
             author: "Test User".to_string(),
             time: Utc::now(),
         };
@@ -184,4 +268,57 @@ mod tests {
         assert_eq!(cloned.summary, "Fix bug");
         assert_eq!(cloned.author, "Test User");
     }
+
+    #[test]
+    fn should_render_commit_message_lines_with_header() {
+        // given
+        let commit = CommitInfo {
+            id: "abc123def456".to_string(),
+            short_id: "abc123d".to_string(),
+            branch_name: None,
+            summary: "Fix bug".to_string(),
+            message: "Fix bug\n\nLonger body.\n\nSigned-off-by: Test User\n".to_string(),
+            author: "Test User".to_string(),
+            time: Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap(),
+        };
+
+        // when
+        let lines = commit.message_lines();
+
+        // then
+        assert_eq!(
+            lines,
+            vec![
+                "commit abc123def456",
+                "Author: Test User",
+                "Date:   2024-01-15 10:30:00 +0000",
+                "",
+                "Fix bug",
+                "",
+                "Longer body.",
+                "",
+                "Signed-off-by: Test User",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fall_back_to_summary_when_message_is_empty() {
+        // given
+        let commit = CommitInfo {
+            id: "abc123def456".to_string(),
+            short_id: "abc123d".to_string(),
+            branch_name: None,
+            summary: "Fix bug".to_string(),
+            message: String::new(),
+            author: "Test User".to_string(),
+            time: Utc::now(),
+        };
+
+        // when
+        let lines = commit.message_lines();
+
+        // then
+        assert_eq!(lines.last().map(String::as_str), Some("Fix bug"));
+    }
 }